@@ -0,0 +1,149 @@
+/* AF-PQC: hybrid Dilithium3 + Ed25519 signatures - C API */
+
+#ifndef AF_PQC_H
+#define AF_PQC_H
+
+/* Generated by cbindgen from ffi/src/lib.rs - do not edit by hand. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// Status code returned by every fallible function
+typedef enum AfStatus {
+  AF_STATUS_OK = 0,
+  AF_STATUS_NULL_POINTER = 1,
+  AF_STATUS_INVALID_KEY = 2,
+  AF_STATUS_SIGNATURE_VERIFICATION = 3,
+  AF_STATUS_UNSUPPORTED_ALGORITHM = 4,
+  AF_STATUS_INVALID_OPERATION = 5,
+  AF_STATUS_KEY_USAGE_EXCEEDED = 6,
+  AF_STATUS_SERIALIZATION = 7,
+  AF_STATUS_PANIC = 8,
+  AF_STATUS_OTHER = 9,
+  AF_STATUS_MALFORMED_ENCODING = 10,
+} AfStatus;
+
+// Opaque hybrid private key
+typedef struct AfPrivateKey AfPrivateKey;
+
+// Opaque hybrid public key
+typedef struct AfPublicKey AfPublicKey;
+
+// Opaque hybrid signature
+typedef struct AfSignature AfSignature;
+
+// Byte buffer allocated by the library; release with `af_buffer_free`
+typedef struct AfBuffer {
+  uint8_t *data;
+  size_t len;
+} AfBuffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Describe the most recent failure on this thread, or NULL if none
+//
+// The returned string is owned by the library and valid until the next
+// failing call on the same thread.
+const char *af_last_error_message(void);
+
+// Generate a random hybrid keypair
+//
+// # Safety
+// `out_private` and `out_public` must be valid pointers to writable handle slots.
+enum AfStatus af_private_key_generate(struct AfPrivateKey **out_private,
+                                      struct AfPublicKey **out_public);
+
+// Deterministically derive a hybrid keypair from a 32-byte seed
+//
+// # Safety
+// `seed` must point to 32 readable bytes; the out-pointers must be writable.
+enum AfStatus af_generate_key_from_seed(const uint8_t *seed,
+                                        struct AfPrivateKey **out_private,
+                                        struct AfPublicKey **out_public);
+
+// Extract the public key of a private key
+//
+// # Safety
+// `private_key` must be a live handle; `out_public` must be writable.
+enum AfStatus af_private_key_public_key(const struct AfPrivateKey *private_key,
+                                        struct AfPublicKey **out_public);
+
+// Create a hybrid signature over `message`
+//
+// # Safety
+// `private_key` must be a live handle, `message` must point to `message_len`
+// readable bytes and `out_signature` must be writable.
+enum AfStatus af_sign(const struct AfPrivateKey *private_key,
+                      const uint8_t *message,
+                      size_t message_len,
+                      struct AfSignature **out_signature);
+
+// Verify a hybrid signature; returns `AF_STATUS_OK` only if both halves verify
+//
+// # Safety
+// `public_key` and `signature` must be live handles and `message` must point
+// to `message_len` readable bytes.
+enum AfStatus af_verify(const struct AfPublicKey *public_key,
+                        const uint8_t *message,
+                        size_t message_len,
+                        const struct AfSignature *signature);
+
+// Serialize a public key to the JSON format used by `af-cli`
+//
+// # Safety
+// `public_key` must be a live handle and `out` must be writable.
+enum AfStatus af_public_key_to_json(const struct AfPublicKey *public_key, struct AfBuffer *out);
+
+// Parse a public key from JSON
+//
+// # Safety
+// `json` must point to `json_len` readable bytes and `out_public` must be writable.
+enum AfStatus af_public_key_from_json(const uint8_t *json,
+                                      size_t json_len,
+                                      struct AfPublicKey **out_public);
+
+// Serialize a signature to the JSON format used by `af-cli`
+//
+// # Safety
+// `signature` must be a live handle and `out` must be writable.
+enum AfStatus af_signature_to_json(const struct AfSignature *signature, struct AfBuffer *out);
+
+// Parse a signature from JSON
+//
+// # Safety
+// `json` must point to `json_len` readable bytes and `out_signature` must be writable.
+enum AfStatus af_signature_from_json(const uint8_t *json,
+                                     size_t json_len,
+                                     struct AfSignature **out_signature);
+
+// Zeroize and free a private key; NULL is ignored
+//
+// # Safety
+// `private_key` must be NULL or a handle not yet freed.
+void af_private_key_free(struct AfPrivateKey *private_key);
+
+// Free a public key; NULL is ignored
+//
+// # Safety
+// `public_key` must be NULL or a handle not yet freed.
+void af_public_key_free(struct AfPublicKey *public_key);
+
+// Free a signature; NULL is ignored
+//
+// # Safety
+// `signature` must be NULL or a handle not yet freed.
+void af_signature_free(struct AfSignature *signature);
+
+// Free a buffer returned by the library and reset it to empty
+//
+// # Safety
+// `buffer` must be NULL or point to a buffer filled by this library.
+void af_buffer_free(struct AfBuffer *buffer);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* AF_PQC_H */
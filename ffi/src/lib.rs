@@ -0,0 +1,372 @@
+//! # AF-PQC C ABI
+//!
+//! Stable `extern "C"` interface over the hybrid Dilithium3 + Ed25519 API.
+//!
+//! All keys and signatures are opaque heap handles owned by the caller and
+//! released with the matching `*_free` function; freeing a private key
+//! zeroizes its secret material first. Every fallible function returns an
+//! [`AfStatus`] and writes its result through an out-pointer only on success.
+//! A human-readable description of the most recent failure on the calling
+//! thread is available from [`af_last_error_message`].
+//!
+//! The C header `include/af_pqc.h` is generated from this file by cbindgen.
+
+use af_pqc::{CryptoError, PrivateKey, PublicKey, Signature};
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use zeroize::Zeroize;
+
+/// Status code returned by every fallible function
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AfStatus {
+    Ok = 0,
+    NullPointer = 1,
+    InvalidKey = 2,
+    SignatureVerification = 3,
+    UnsupportedAlgorithm = 4,
+    InvalidOperation = 5,
+    KeyUsageExceeded = 6,
+    Serialization = 7,
+    Panic = 8,
+    Other = 9,
+    MalformedEncoding = 10,
+}
+
+/// Opaque hybrid private key
+pub struct AfPrivateKey {
+    inner: PrivateKey,
+}
+
+/// Opaque hybrid public key
+pub struct AfPublicKey {
+    inner: PublicKey,
+}
+
+/// Opaque hybrid signature
+pub struct AfSignature {
+    inner: Signature,
+}
+
+/// Byte buffer allocated by the library; release with `af_buffer_free`
+#[repr(C)]
+pub struct AfBuffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn status_from_error(error: &CryptoError) -> AfStatus {
+    match error {
+        CryptoError::InvalidKey { .. } => AfStatus::InvalidKey,
+        CryptoError::SignatureVerification { .. } => AfStatus::SignatureVerification,
+        CryptoError::MalformedEncoding { .. } => AfStatus::MalformedEncoding,
+        CryptoError::UnsupportedAlgorithm(_) => AfStatus::UnsupportedAlgorithm,
+        CryptoError::InvalidOperation { .. } => AfStatus::InvalidOperation,
+        CryptoError::KeyUsageExceeded { .. } => AfStatus::KeyUsageExceeded,
+        _ => AfStatus::Other,
+    }
+}
+
+/// Run an FFI body, converting errors and panics into status codes
+fn guard(body: impl FnOnce() -> Result<(), (AfStatus, String)>) -> AfStatus {
+    match catch_unwind(AssertUnwindSafe(body)) {
+        Ok(Ok(())) => AfStatus::Ok,
+        Ok(Err((status, message))) => {
+            set_last_error(message);
+            status
+        }
+        Err(_) => {
+            set_last_error("Panic in AF-PQC library".to_string());
+            AfStatus::Panic
+        }
+    }
+}
+
+fn crypto(error: CryptoError) -> (AfStatus, String) {
+    (status_from_error(&error), error.to_string())
+}
+
+fn null_pointer(name: &str) -> (AfStatus, String) {
+    (AfStatus::NullPointer, format!("Null pointer passed for '{}'", name))
+}
+
+/// Borrow `len` bytes from C, treating `(NULL, 0)` as an empty slice
+unsafe fn input_bytes<'a>(data: *const u8, len: usize, name: &str) -> Result<&'a [u8], (AfStatus, String)> {
+    if data.is_null() {
+        return if len == 0 { Ok(&[]) } else { Err(null_pointer(name)) };
+    }
+    Ok(std::slice::from_raw_parts(data, len))
+}
+
+fn into_buffer(bytes: Vec<u8>) -> AfBuffer {
+    let mut bytes = bytes.into_boxed_slice();
+    let buffer = AfBuffer { data: bytes.as_mut_ptr(), len: bytes.len() };
+    std::mem::forget(bytes);
+    buffer
+}
+
+unsafe fn write_keypair(
+    (private_key, public_key): (PrivateKey, PublicKey),
+    out_private: *mut *mut AfPrivateKey,
+    out_public: *mut *mut AfPublicKey,
+) {
+    *out_private = Box::into_raw(Box::new(AfPrivateKey { inner: private_key }));
+    *out_public = Box::into_raw(Box::new(AfPublicKey { inner: public_key }));
+}
+
+/// Describe the most recent failure on this thread, or NULL if none
+///
+/// The returned string is owned by the library and valid until the next
+/// failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn af_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+}
+
+/// Generate a random hybrid keypair
+///
+/// # Safety
+/// `out_private` and `out_public` must be valid pointers to writable handle slots.
+#[no_mangle]
+pub unsafe extern "C" fn af_private_key_generate(
+    out_private: *mut *mut AfPrivateKey,
+    out_public: *mut *mut AfPublicKey,
+) -> AfStatus {
+    guard(|| {
+        if out_private.is_null() || out_public.is_null() {
+            return Err(null_pointer("out_private/out_public"));
+        }
+        let keypair = PrivateKey::generate().map_err(crypto)?;
+        write_keypair(keypair, out_private, out_public);
+        Ok(())
+    })
+}
+
+/// Deterministically derive a hybrid keypair from a 32-byte seed
+///
+/// # Safety
+/// `seed` must point to 32 readable bytes; the out-pointers must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn af_generate_key_from_seed(
+    seed: *const u8,
+    out_private: *mut *mut AfPrivateKey,
+    out_public: *mut *mut AfPublicKey,
+) -> AfStatus {
+    guard(|| {
+        if seed.is_null() {
+            return Err(null_pointer("seed"));
+        }
+        if out_private.is_null() || out_public.is_null() {
+            return Err(null_pointer("out_private/out_public"));
+        }
+        let mut seed_bytes = [0u8; 32];
+        seed_bytes.copy_from_slice(std::slice::from_raw_parts(seed, 32));
+        let result = af_pqc::generate_key_from_seed(&seed_bytes);
+        seed_bytes.zeroize();
+        write_keypair(result.map_err(crypto)?, out_private, out_public);
+        Ok(())
+    })
+}
+
+/// Extract the public key of a private key
+///
+/// # Safety
+/// `private_key` must be a live handle; `out_public` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn af_private_key_public_key(
+    private_key: *const AfPrivateKey,
+    out_public: *mut *mut AfPublicKey,
+) -> AfStatus {
+    guard(|| {
+        let private_key = private_key.as_ref().ok_or_else(|| null_pointer("private_key"))?;
+        if out_public.is_null() {
+            return Err(null_pointer("out_public"));
+        }
+        let public_key = private_key.inner.public_key().map_err(crypto)?;
+        *out_public = Box::into_raw(Box::new(AfPublicKey { inner: public_key }));
+        Ok(())
+    })
+}
+
+/// Create a hybrid signature over `message`
+///
+/// # Safety
+/// `private_key` must be a live handle, `message` must point to `message_len`
+/// readable bytes and `out_signature` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn af_sign(
+    private_key: *const AfPrivateKey,
+    message: *const u8,
+    message_len: usize,
+    out_signature: *mut *mut AfSignature,
+) -> AfStatus {
+    guard(|| {
+        let private_key = private_key.as_ref().ok_or_else(|| null_pointer("private_key"))?;
+        let message = input_bytes(message, message_len, "message")?;
+        if out_signature.is_null() {
+            return Err(null_pointer("out_signature"));
+        }
+        let signature = private_key.inner.sign(message).map_err(crypto)?;
+        *out_signature = Box::into_raw(Box::new(AfSignature { inner: signature }));
+        Ok(())
+    })
+}
+
+/// Verify a hybrid signature; returns `AF_STATUS_OK` only if both halves verify
+///
+/// # Safety
+/// `public_key` and `signature` must be live handles and `message` must point
+/// to `message_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn af_verify(
+    public_key: *const AfPublicKey,
+    message: *const u8,
+    message_len: usize,
+    signature: *const AfSignature,
+) -> AfStatus {
+    guard(|| {
+        let public_key = public_key.as_ref().ok_or_else(|| null_pointer("public_key"))?;
+        let signature = signature.as_ref().ok_or_else(|| null_pointer("signature"))?;
+        let message = input_bytes(message, message_len, "message")?;
+        public_key.inner.verify(message, &signature.inner).map_err(crypto)
+    })
+}
+
+/// Serialize a public key to the JSON format used by `af-cli`
+///
+/// # Safety
+/// `public_key` must be a live handle and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn af_public_key_to_json(public_key: *const AfPublicKey, out: *mut AfBuffer) -> AfStatus {
+    guard(|| {
+        let public_key = public_key.as_ref().ok_or_else(|| null_pointer("public_key"))?;
+        let out = out.as_mut().ok_or_else(|| null_pointer("out"))?;
+        let json = serde_json::to_vec(&public_key.inner)
+            .map_err(|e| (AfStatus::Serialization, e.to_string()))?;
+        *out = into_buffer(json);
+        Ok(())
+    })
+}
+
+/// Parse a public key from JSON
+///
+/// # Safety
+/// `json` must point to `json_len` readable bytes and `out_public` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn af_public_key_from_json(
+    json: *const u8,
+    json_len: usize,
+    out_public: *mut *mut AfPublicKey,
+) -> AfStatus {
+    guard(|| {
+        let json = input_bytes(json, json_len, "json")?;
+        if out_public.is_null() {
+            return Err(null_pointer("out_public"));
+        }
+        let public_key: PublicKey = serde_json::from_slice(json)
+            .map_err(|e| (AfStatus::Serialization, e.to_string()))?;
+        *out_public = Box::into_raw(Box::new(AfPublicKey { inner: public_key }));
+        Ok(())
+    })
+}
+
+/// Serialize a signature to the JSON format used by `af-cli`
+///
+/// # Safety
+/// `signature` must be a live handle and `out` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn af_signature_to_json(signature: *const AfSignature, out: *mut AfBuffer) -> AfStatus {
+    guard(|| {
+        let signature = signature.as_ref().ok_or_else(|| null_pointer("signature"))?;
+        let out = out.as_mut().ok_or_else(|| null_pointer("out"))?;
+        let json = serde_json::to_vec(&signature.inner)
+            .map_err(|e| (AfStatus::Serialization, e.to_string()))?;
+        *out = into_buffer(json);
+        Ok(())
+    })
+}
+
+/// Parse a signature from JSON
+///
+/// # Safety
+/// `json` must point to `json_len` readable bytes and `out_signature` must be writable.
+#[no_mangle]
+pub unsafe extern "C" fn af_signature_from_json(
+    json: *const u8,
+    json_len: usize,
+    out_signature: *mut *mut AfSignature,
+) -> AfStatus {
+    guard(|| {
+        let json = input_bytes(json, json_len, "json")?;
+        if out_signature.is_null() {
+            return Err(null_pointer("out_signature"));
+        }
+        let signature: Signature = serde_json::from_slice(json)
+            .map_err(|e| (AfStatus::Serialization, e.to_string()))?;
+        *out_signature = Box::into_raw(Box::new(AfSignature { inner: signature }));
+        Ok(())
+    })
+}
+
+/// Zeroize and free a private key; NULL is ignored
+///
+/// # Safety
+/// `private_key` must be NULL or a handle not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn af_private_key_free(private_key: *mut AfPrivateKey) {
+    if private_key.is_null() {
+        return;
+    }
+    // Key material zeroizes itself on drop
+    drop(Box::from_raw(private_key));
+}
+
+/// Free a public key; NULL is ignored
+///
+/// # Safety
+/// `public_key` must be NULL or a handle not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn af_public_key_free(public_key: *mut AfPublicKey) {
+    if !public_key.is_null() {
+        drop(Box::from_raw(public_key));
+    }
+}
+
+/// Free a signature; NULL is ignored
+///
+/// # Safety
+/// `signature` must be NULL or a handle not yet freed.
+#[no_mangle]
+pub unsafe extern "C" fn af_signature_free(signature: *mut AfSignature) {
+    if !signature.is_null() {
+        drop(Box::from_raw(signature));
+    }
+}
+
+/// Free a buffer returned by the library and reset it to empty
+///
+/// # Safety
+/// `buffer` must be NULL or point to a buffer filled by this library.
+#[no_mangle]
+pub unsafe extern "C" fn af_buffer_free(buffer: *mut AfBuffer) {
+    let Some(buffer) = buffer.as_mut() else {
+        return;
+    };
+    if !buffer.data.is_null() {
+        let slice = ptr::slice_from_raw_parts_mut(buffer.data, buffer.len);
+        drop(Box::from_raw(slice));
+    }
+    buffer.data = ptr::null_mut();
+    buffer.len = 0;
+}
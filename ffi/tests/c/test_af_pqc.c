@@ -0,0 +1,106 @@
+/* Exercises the AF-PQC C API end to end; run by tests/c_api.rs */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "af_pqc.h"
+
+#define CHECK(expr)                                                          \
+    do {                                                                     \
+        if (!(expr)) {                                                       \
+            const char *detail = af_last_error_message();                    \
+            fprintf(stderr, "%s:%d: check failed: %s (%s)\n", __FILE__,      \
+                    __LINE__, #expr, detail ? detail : "no error message");  \
+            return 1;                                                        \
+        }                                                                    \
+    } while (0)
+
+static int test_random_key(void) {
+    AfPrivateKey *private_key = NULL;
+    AfPublicKey *public_key = NULL;
+    AfSignature *signature = NULL;
+    const uint8_t message[] = "hello from C";
+
+    CHECK(af_private_key_generate(&private_key, &public_key) == AF_STATUS_OK);
+    CHECK(af_sign(private_key, message, sizeof message, &signature) == AF_STATUS_OK);
+    CHECK(af_verify(public_key, message, sizeof message, signature) == AF_STATUS_OK);
+
+    const uint8_t other[] = "tampered";
+    CHECK(af_verify(public_key, other, sizeof other, signature) == AF_STATUS_SIGNATURE_VERIFICATION);
+    CHECK(af_last_error_message() != NULL);
+
+    /* Empty messages are rejected by the library */
+    AfSignature *empty = NULL;
+    CHECK(af_sign(private_key, NULL, 0, &empty) == AF_STATUS_INVALID_OPERATION);
+    CHECK(empty == NULL);
+
+    af_signature_free(signature);
+    af_public_key_free(public_key);
+    af_private_key_free(private_key);
+    return 0;
+}
+
+static int test_seed_and_json(void) {
+    uint8_t seed[32];
+    memset(seed, 0x42, sizeof seed);
+
+    AfPrivateKey *private_key = NULL;
+    AfPublicKey *public_key = NULL;
+    AfPublicKey *derived = NULL;
+    AfSignature *signature = NULL;
+    const uint8_t message[] = "serialized round trip";
+
+    CHECK(af_generate_key_from_seed(seed, &private_key, &public_key) == AF_STATUS_OK);
+    CHECK(af_private_key_public_key(private_key, &derived) == AF_STATUS_OK);
+    CHECK(af_sign(private_key, message, sizeof message, &signature) == AF_STATUS_OK);
+
+    AfBuffer key_json = {0};
+    AfBuffer sig_json = {0};
+    CHECK(af_public_key_to_json(derived, &key_json) == AF_STATUS_OK);
+    CHECK(af_signature_to_json(signature, &sig_json) == AF_STATUS_OK);
+    CHECK(key_json.len > 0 && sig_json.len > 0);
+
+    AfPublicKey *parsed_key = NULL;
+    AfSignature *parsed_sig = NULL;
+    CHECK(af_public_key_from_json(key_json.data, key_json.len, &parsed_key) == AF_STATUS_OK);
+    CHECK(af_signature_from_json(sig_json.data, sig_json.len, &parsed_sig) == AF_STATUS_OK);
+    CHECK(af_verify(parsed_key, message, sizeof message, parsed_sig) == AF_STATUS_OK);
+
+    const uint8_t garbage[] = "{not json";
+    AfPublicKey *bad = NULL;
+    CHECK(af_public_key_from_json(garbage, sizeof garbage - 1, &bad) == AF_STATUS_SERIALIZATION);
+    CHECK(bad == NULL);
+
+    af_buffer_free(&key_json);
+    af_buffer_free(&sig_json);
+    CHECK(key_json.data == NULL && key_json.len == 0);
+
+    af_signature_free(parsed_sig);
+    af_public_key_free(parsed_key);
+    af_signature_free(signature);
+    af_public_key_free(derived);
+    af_public_key_free(public_key);
+    af_private_key_free(private_key);
+    return 0;
+}
+
+static int test_null_handling(void) {
+    AfSignature *signature = NULL;
+    CHECK(af_sign(NULL, (const uint8_t *)"x", 1, &signature) == AF_STATUS_NULL_POINTER);
+    CHECK(af_generate_key_from_seed(NULL, NULL, NULL) == AF_STATUS_NULL_POINTER);
+
+    /* Freeing NULL is a no-op */
+    af_private_key_free(NULL);
+    af_public_key_free(NULL);
+    af_signature_free(NULL);
+    af_buffer_free(NULL);
+    return 0;
+}
+
+int main(void) {
+    if (test_random_key() != 0) return 1;
+    if (test_seed_and_json() != 0) return 1;
+    if (test_null_handling() != 0) return 1;
+    printf("af_pqc C API: all checks passed\n");
+    return 0;
+}
@@ -0,0 +1,67 @@
+//! Compiles and runs the C test program against the freshly built shared library
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Directory holding the library artifacts (`target/<profile>`)
+fn artifact_dir() -> PathBuf {
+    let exe = std::env::current_exe().expect("test executable path");
+    // target/<profile>/deps/c_api-<hash> -> target/<profile>
+    exe.parent().and_then(Path::parent).expect("artifact directory").to_path_buf()
+}
+
+/// `cargo test` only builds the rlib, so build the shared library explicitly
+fn build_shared_library() {
+    let mut command = Command::new(env!("CARGO"));
+    command.args(["build", "--lib", "-p", "af-pqc-ffi"]);
+    if !cfg!(debug_assertions) {
+        command.arg("--release");
+    }
+    let status = command.status().expect("failed to run cargo");
+    assert!(status.success(), "building the shared library failed");
+}
+
+#[test]
+fn c_program_exercises_api() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    build_shared_library();
+    let lib_dir = artifact_dir();
+    assert!(
+        lib_dir.join("libaf_pqc_ffi.so").exists() || lib_dir.join("libaf_pqc_ffi.dylib").exists(),
+        "shared library not found in {}",
+        lib_dir.display()
+    );
+
+    let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let binary = out_dir.join("test_af_pqc");
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+    let status = Command::new(&compiler)
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir.join("include"))
+        .arg(manifest_dir.join("tests/c/test_af_pqc.c"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg("-laf_pqc_ffi")
+        .arg("-o")
+        .arg(&binary)
+        .status()
+        .expect("failed to run C compiler");
+    assert!(status.success(), "C test program failed to compile");
+
+    let output = Command::new(&binary)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .env("DYLD_LIBRARY_PATH", &lib_dir)
+        .current_dir(&out_dir)
+        .output()
+        .expect("failed to run C test program");
+    assert!(
+        output.status.success(),
+        "C test program failed:\n{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,93 @@
+//! Fuzz harnesses shared by the cargo-fuzz targets and the regression test
+//!
+//! Each harness takes raw fuzzer input and must never panic. `corpus/<target>/`
+//! holds the in-tree seeds; minimized crashing inputs go into
+//! `regressions/<target>/` so `cargo test` replays them.
+
+use af_pqc::{AlgorithmVersion, PublicKey, Signature};
+use bincode::Options;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Size limit matching the agent frame and Python binary codecs
+const MAX_BINARY_LEN: u64 = 64 * 1024;
+
+/// Harness taking raw fuzzer input
+pub type Harness = fn(&[u8]);
+
+/// Harness names, matching the `[[bin]]` targets and corpus directories
+pub const TARGETS: &[(&str, Harness)] = &[
+    ("deserialize_public_key", deserialize_public_key),
+    ("deserialize_signature", deserialize_signature),
+    ("verify", verify),
+    ("cache_file", cache_file),
+];
+
+fn binary_codec() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_limit(MAX_BINARY_LEN)
+}
+
+/// Anything that parses must re-encode to a form that parses to the same encoding
+fn check_round_trip<T: Serialize + DeserializeOwned>(data: &[u8]) {
+    if let Ok(value) = serde_json::from_slice::<T>(data) {
+        let encoded = serde_json::to_vec(&value).expect("parsed value re-encodes as JSON");
+        let reparsed: T = serde_json::from_slice(&encoded).expect("re-encoded JSON parses");
+        assert_eq!(serde_json::to_vec(&reparsed).unwrap(), encoded);
+    }
+    if let Ok(value) = binary_codec().deserialize::<T>(data) {
+        let encoded = binary_codec().serialize(&value).expect("parsed value re-encodes as bincode");
+        let reparsed: T = binary_codec().deserialize(&encoded).expect("re-encoded bincode parses");
+        assert_eq!(binary_codec().serialize(&reparsed).unwrap(), encoded);
+    }
+}
+
+/// JSON and bincode parsing of [`PublicKey`]
+pub fn deserialize_public_key(data: &[u8]) {
+    check_round_trip::<PublicKey>(data);
+}
+
+/// JSON and bincode parsing of [`Signature`]
+pub fn deserialize_signature(data: &[u8]) {
+    check_round_trip::<Signature>(data);
+}
+
+/// [`PublicKey::verify`] on attacker-controlled key, signature and message bytes
+///
+/// Input layout: key length (u16 LE), signature length (u16 LE), key bytes,
+/// signature bytes, message. Lengths are truncated to the bytes available.
+pub fn verify(data: &[u8]) {
+    let Some((header, rest)) = data.split_first_chunk::<4>() else {
+        return;
+    };
+    let key_len = usize::from(u16::from_le_bytes([header[0], header[1]])).min(rest.len());
+    let (key_bytes, rest) = rest.split_at(key_len);
+    let signature_len = usize::from(u16::from_le_bytes([header[2], header[3]])).min(rest.len());
+    let (signature_bytes, message) = rest.split_at(signature_len);
+
+    let public_key = PublicKey {
+        algorithm: AlgorithmVersion::MandatoryHybrid,
+        bytes: key_bytes.to_vec(),
+        created_at: 0,
+        operation_id: 0,
+    };
+    let signature = Signature {
+        algorithm: AlgorithmVersion::MandatoryHybrid,
+        bytes: signature_bytes.to_vec(),
+        created_at: 0,
+        operation_id: 0,
+        signer_key_id: String::new(),
+    };
+    let _ = public_key.verify(message, &signature);
+}
+
+/// Encrypted Dilithium cache file parsing
+///
+/// Input layout: 32-byte seed followed by the file contents.
+pub fn cache_file(data: &[u8]) {
+    let Some((seed, file_data)) = data.split_first_chunk::<32>() else {
+        return;
+    };
+    let _ = af_pqc::fuzzing::parse_cache_file(seed, file_data);
+}
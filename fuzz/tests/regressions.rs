@@ -0,0 +1,30 @@
+//! Replays the seed corpus and every recorded crash through the fuzz harnesses
+
+use af_pqc_fuzz::Harness;
+use std::path::Path;
+
+fn replay(directory: &Path, harness: Harness) -> usize {
+    let Ok(entries) = std::fs::read_dir(directory) else {
+        return 0;
+    };
+    let mut replayed = 0;
+    for entry in entries {
+        let path = entry.unwrap().path();
+        let data = std::fs::read(&path).unwrap();
+        if std::panic::catch_unwind(|| harness(&data)).is_err() {
+            panic!("{} still crashes", path.display());
+        }
+        replayed += 1;
+    }
+    replayed
+}
+
+#[test]
+fn corpus_and_regressions_do_not_crash() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    for (target, harness) in af_pqc_fuzz::TARGETS {
+        let seeds = replay(&root.join("corpus").join(target), *harness);
+        assert!(seeds > 0, "{} has no seed corpus", target);
+        replay(&root.join("regressions").join(target), *harness);
+    }
+}
@@ -0,0 +1,22 @@
+//! `#![no_std]` consumer of AF-PQC used to check that key types, signature
+//! parsing and [`PublicKey::verify`] build without `std`.
+//!
+//! Built on its own by `tests/no_std.rs` so workspace feature unification
+//! cannot re-enable `std` on `af-pqc`.
+
+#![no_std]
+
+extern crate alloc;
+
+use af_pqc::{CryptoError, PublicKey, Signature};
+
+/// Parse a JSON public key and signature and verify `message`
+pub fn verify_json(public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), CryptoError> {
+    let public_key: PublicKey = serde_json::from_slice(public_key).map_err(|_| CryptoError::MalformedEncoding {
+        details: alloc::string::String::from("Malformed public key"),
+    })?;
+    let signature: Signature = serde_json::from_slice(signature).map_err(|_| CryptoError::MalformedEncoding {
+        details: alloc::string::String::from("Malformed signature"),
+    })?;
+    public_key.verify(message, &signature)
+}
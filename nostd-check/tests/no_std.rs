@@ -0,0 +1,30 @@
+//! Builds this crate on its own and checks `af-pqc` resolves without `std`
+
+use std::process::Command;
+
+fn cargo(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO"))
+        .args(args)
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .expect("failed to run cargo");
+    assert!(
+        output.status.success(),
+        "cargo {} failed:\n{}",
+        args.join(" "),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn core_builds_without_std() {
+    let features = cargo(&["tree", "-p", "af-pqc-nostd-check", "-e", "features", "-i", "af-pqc", "--prefix", "none"]);
+    assert!(
+        !features.lines().any(|line| line.starts_with("af-pqc feature \"std\"")),
+        "af-pqc was built with std:\n{}",
+        features
+    );
+
+    cargo(&["check", "--lib", "-p", "af-pqc-nostd-check"]);
+}
@@ -0,0 +1,315 @@
+//! # AF-PQC Python Bindings
+//!
+//! PyO3 module `af_pqc` exposing hybrid key generation, signing, verification
+//! and the JSON / binary serialization formats. All message, seed and
+//! serialized inputs are `bytes` and all binary outputs are `bytes`.
+//!
+//! Every [`CryptoError`] variant is raised as its own exception class, all
+//! deriving from `af_pqc.AfPqcError`.
+
+use af_pqc::{AlgorithmVersion, CryptoError};
+use bincode::Options;
+use zeroize::Zeroizing;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+
+create_exception!(af_pqc, AfPqcError, PyException, "Base class of all AF-PQC errors");
+create_exception!(af_pqc, InsufficientGuardianApprovalError, AfPqcError);
+create_exception!(af_pqc, InvalidEphemeralTTLError, AfPqcError);
+create_exception!(af_pqc, ExpiredEphemeralKeyError, AfPqcError);
+create_exception!(af_pqc, KeyGenerationError, AfPqcError);
+create_exception!(af_pqc, InvalidKeyError, AfPqcError);
+create_exception!(af_pqc, SignatureVerificationError, AfPqcError);
+create_exception!(af_pqc, MalformedEncodingError, AfPqcError);
+create_exception!(af_pqc, UnsupportedAlgorithmError, AfPqcError);
+create_exception!(af_pqc, InvalidOperationError, AfPqcError);
+create_exception!(af_pqc, AuditFailureError, AfPqcError);
+create_exception!(af_pqc, KeyUsageExceededError, AfPqcError);
+create_exception!(af_pqc, SideChannelAttackError, AfPqcError);
+create_exception!(af_pqc, TimingAttackError, AfPqcError);
+create_exception!(af_pqc, KeyNotFoundError, AfPqcError);
+create_exception!(af_pqc, KeystoreError, AfPqcError);
+create_exception!(af_pqc, AgentError, AfPqcError);
+create_exception!(af_pqc, SerializationError, AfPqcError, "Malformed JSON or binary encoding");
+
+/// Largest accepted binary encoding, comfortably above any key or signature
+const MAX_BINARY_LEN: u64 = 64 * 1024;
+
+fn to_py_err(error: CryptoError) -> PyErr {
+    let message = error.to_string();
+    match error {
+        CryptoError::InsufficientGuardianApproval => InsufficientGuardianApprovalError::new_err(message),
+        CryptoError::InvalidEphemeralTTL => InvalidEphemeralTTLError::new_err(message),
+        CryptoError::ExpiredEphemeralKey => ExpiredEphemeralKeyError::new_err(message),
+        CryptoError::KeyGeneration { .. } => KeyGenerationError::new_err(message),
+        CryptoError::InvalidKey { .. } => InvalidKeyError::new_err(message),
+        CryptoError::SignatureVerification { .. } => SignatureVerificationError::new_err(message),
+        CryptoError::MalformedEncoding { .. } => MalformedEncodingError::new_err(message),
+        CryptoError::UnsupportedAlgorithm(_) => UnsupportedAlgorithmError::new_err(message),
+        CryptoError::InvalidOperation { .. } => InvalidOperationError::new_err(message),
+        CryptoError::AuditFailure => AuditFailureError::new_err(message),
+        CryptoError::KeyUsageExceeded { .. } => KeyUsageExceededError::new_err(message),
+        CryptoError::SideChannelAttack { .. } => SideChannelAttackError::new_err(message),
+        CryptoError::TimingAttack { .. } => TimingAttackError::new_err(message),
+        CryptoError::KeyNotFound { .. } => KeyNotFoundError::new_err(message),
+        CryptoError::Keystore { .. } => KeystoreError::new_err(message),
+        CryptoError::Agent { .. } => AgentError::new_err(message),
+    }
+}
+
+fn serialization_err(error: impl std::fmt::Display) -> PyErr {
+    SerializationError::new_err(error.to_string())
+}
+
+fn binary_codec() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_limit(MAX_BINARY_LEN)
+}
+
+fn algorithm_name(algorithm: AlgorithmVersion) -> String {
+    format!("{:?}", algorithm)
+}
+
+fn parse_seed(seed: &[u8]) -> PyResult<Zeroizing<[u8; 32]>> {
+    seed.try_into()
+        .map(Zeroizing::new)
+        .map_err(|_| InvalidKeyError::new_err(format!("Seed must be exactly 32 bytes, got {}", seed.len())))
+}
+
+/// Hybrid Dilithium3 + Ed25519 private key
+#[pyclass(module = "af_pqc")]
+pub struct PrivateKey {
+    inner: af_pqc::PrivateKey,
+}
+
+/// Hybrid public key for signature verification
+#[pyclass(module = "af_pqc")]
+#[derive(Clone)]
+pub struct PublicKey {
+    inner: af_pqc::PublicKey,
+}
+
+/// Hybrid signature with provenance metadata
+#[pyclass(module = "af_pqc")]
+#[derive(Clone)]
+pub struct Signature {
+    inner: af_pqc::Signature,
+}
+
+fn keypair((private_key, public_key): (af_pqc::PrivateKey, af_pqc::PublicKey)) -> (PrivateKey, PublicKey) {
+    (PrivateKey { inner: private_key }, PublicKey { inner: public_key })
+}
+
+#[pymethods]
+impl PrivateKey {
+    /// Generate a random keypair, returning `(PrivateKey, PublicKey)`
+    #[staticmethod]
+    fn generate() -> PyResult<(PrivateKey, PublicKey)> {
+        af_pqc::PrivateKey::generate().map(keypair).map_err(to_py_err)
+    }
+
+    /// Deterministically derive a keypair from a 32-byte seed
+    #[staticmethod]
+    fn from_seed(seed: &[u8]) -> PyResult<(PrivateKey, PublicKey)> {
+        generate_key_from_seed(seed)
+    }
+
+    /// Public half of this key
+    fn public_key(&self) -> PyResult<PublicKey> {
+        self.inner.public_key().map(|inner| PublicKey { inner }).map_err(to_py_err)
+    }
+
+    /// Sign `message` with both Dilithium3 and Ed25519
+    fn sign(&self, py: Python<'_>, message: &[u8]) -> PyResult<Signature> {
+        py.allow_threads(|| self.inner.sign(message))
+            .map(|inner| Signature { inner })
+            .map_err(to_py_err)
+    }
+
+    #[getter]
+    fn key_id(&self) -> &str {
+        &self.inner.key_id
+    }
+
+    #[getter]
+    fn algorithm(&self) -> String {
+        algorithm_name(self.inner.algorithm)
+    }
+
+    #[getter]
+    fn created_at(&self) -> u64 {
+        self.inner.created_at
+    }
+
+    #[getter]
+    fn usage_count(&self) -> u64 {
+        self.inner.usage_count.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PrivateKey(key_id={:?}, algorithm={})", self.inner.key_id, self.algorithm())
+    }
+}
+
+#[pymethods]
+impl PublicKey {
+    /// Verify `signature` over `message`; raises `SignatureVerificationError` on failure
+    fn verify(&self, py: Python<'_>, message: &[u8], signature: &Signature) -> PyResult<()> {
+        py.allow_threads(|| self.inner.verify(message, &signature.inner))
+            .map_err(to_py_err)
+    }
+
+    /// Serialize to the JSON format used by `af-cli`
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(serialization_err)
+    }
+
+    /// Parse the JSON format used by `af-cli`
+    #[staticmethod]
+    fn from_json(data: &[u8]) -> PyResult<PublicKey> {
+        serde_json::from_slice(data).map(|inner| PublicKey { inner }).map_err(serialization_err)
+    }
+
+    /// Serialize to the compact binary (bincode) format
+    fn to_binary<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = binary_codec().serialize(&self.inner).map_err(serialization_err)?;
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+
+    /// Parse the compact binary (bincode) format
+    #[staticmethod]
+    fn from_binary(data: &[u8]) -> PyResult<PublicKey> {
+        binary_codec().deserialize(data).map(|inner| PublicKey { inner }).map_err(serialization_err)
+    }
+
+    /// Raw hybrid key bytes (Dilithium3 public key || Ed25519 public key)
+    #[getter]
+    fn bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.inner.bytes)
+    }
+
+    #[getter]
+    fn algorithm(&self) -> String {
+        algorithm_name(self.inner.algorithm)
+    }
+
+    #[getter]
+    fn created_at(&self) -> u64 {
+        self.inner.created_at
+    }
+
+    #[getter]
+    fn operation_id(&self) -> u64 {
+        self.inner.operation_id
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PublicKey(algorithm={}, bytes={})", self.algorithm(), self.inner.bytes.len())
+    }
+}
+
+#[pymethods]
+impl Signature {
+    /// Serialize to the JSON format used by `af-cli`
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(serialization_err)
+    }
+
+    /// Parse the JSON format used by `af-cli`
+    #[staticmethod]
+    fn from_json(data: &[u8]) -> PyResult<Signature> {
+        serde_json::from_slice(data).map(|inner| Signature { inner }).map_err(serialization_err)
+    }
+
+    /// Serialize to the compact binary (bincode) format
+    fn to_binary<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let bytes = binary_codec().serialize(&self.inner).map_err(serialization_err)?;
+        Ok(PyBytes::new_bound(py, &bytes))
+    }
+
+    /// Parse the compact binary (bincode) format
+    #[staticmethod]
+    fn from_binary(data: &[u8]) -> PyResult<Signature> {
+        binary_codec().deserialize(data).map(|inner| Signature { inner }).map_err(serialization_err)
+    }
+
+    /// Raw hybrid signature bytes (Dilithium3 signature || Ed25519 signature)
+    #[getter]
+    fn bytes<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new_bound(py, &self.inner.bytes)
+    }
+
+    #[getter]
+    fn algorithm(&self) -> String {
+        algorithm_name(self.inner.algorithm)
+    }
+
+    #[getter]
+    fn signer_key_id(&self) -> &str {
+        &self.inner.signer_key_id
+    }
+
+    #[getter]
+    fn created_at(&self) -> u64 {
+        self.inner.created_at
+    }
+
+    #[getter]
+    fn operation_id(&self) -> u64 {
+        self.inner.operation_id
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Signature(signer_key_id={:?}, bytes={})", self.inner.signer_key_id, self.inner.bytes.len())
+    }
+}
+
+/// Deterministically derive a keypair from a 32-byte seed, returning `(PrivateKey, PublicKey)`
+#[pyfunction]
+fn generate_key_from_seed(seed: &[u8]) -> PyResult<(PrivateKey, PublicKey)> {
+    let seed = parse_seed(seed)?;
+    af_pqc::generate_key_from_seed(&seed).map(keypair).map_err(to_py_err)
+}
+
+/// Fill and return `length` cryptographically secure random bytes
+#[pyfunction]
+fn secure_random_bytes(py: Python<'_>, length: usize) -> PyResult<Bound<'_, PyBytes>> {
+    let mut buffer = vec![0u8; length];
+    af_pqc::secure_random_bytes(&mut buffer).map_err(to_py_err)?;
+    Ok(PyBytes::new_bound(py, &buffer))
+}
+
+#[pymodule]
+#[pyo3(name = "af_pqc")]
+fn af_pqc_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+
+    m.add_class::<PrivateKey>()?;
+    m.add_class::<PublicKey>()?;
+    m.add_class::<Signature>()?;
+    m.add_function(wrap_pyfunction!(generate_key_from_seed, m)?)?;
+    m.add_function(wrap_pyfunction!(secure_random_bytes, m)?)?;
+
+    m.add("AfPqcError", py.get_type_bound::<AfPqcError>())?;
+    m.add("InsufficientGuardianApprovalError", py.get_type_bound::<InsufficientGuardianApprovalError>())?;
+    m.add("InvalidEphemeralTTLError", py.get_type_bound::<InvalidEphemeralTTLError>())?;
+    m.add("ExpiredEphemeralKeyError", py.get_type_bound::<ExpiredEphemeralKeyError>())?;
+    m.add("KeyGenerationError", py.get_type_bound::<KeyGenerationError>())?;
+    m.add("InvalidKeyError", py.get_type_bound::<InvalidKeyError>())?;
+    m.add("SignatureVerificationError", py.get_type_bound::<SignatureVerificationError>())?;
+    m.add("MalformedEncodingError", py.get_type_bound::<MalformedEncodingError>())?;
+    m.add("UnsupportedAlgorithmError", py.get_type_bound::<UnsupportedAlgorithmError>())?;
+    m.add("InvalidOperationError", py.get_type_bound::<InvalidOperationError>())?;
+    m.add("AuditFailureError", py.get_type_bound::<AuditFailureError>())?;
+    m.add("KeyUsageExceededError", py.get_type_bound::<KeyUsageExceededError>())?;
+    m.add("SideChannelAttackError", py.get_type_bound::<SideChannelAttackError>())?;
+    m.add("TimingAttackError", py.get_type_bound::<TimingAttackError>())?;
+    m.add("KeyNotFoundError", py.get_type_bound::<KeyNotFoundError>())?;
+    m.add("KeystoreError", py.get_type_bound::<KeystoreError>())?;
+    m.add("AgentError", py.get_type_bound::<AgentError>())?;
+    m.add("SerializationError", py.get_type_bound::<SerializationError>())?;
+    Ok(())
+}
@@ -0,0 +1,110 @@
+"""Tests for the af_pqc Python module.
+
+Build and install the wheel locally, then run pytest:
+
+    cd python
+    maturin build --release -o dist
+    pip install --force-reinstall dist/af_pqc-*.whl
+    pytest tests
+"""
+
+import json
+
+import pytest
+
+import af_pqc
+
+SEED = bytes(range(32))
+MESSAGE = b"hello from python"
+
+
+@pytest.fixture(autouse=True)
+def isolated_cwd(tmp_path, monkeypatch):
+    # Seed-derived keys cache their Dilithium half in the working directory
+    monkeypatch.chdir(tmp_path)
+
+
+def test_generate_sign_verify():
+    private_key, public_key = af_pqc.PrivateKey.generate()
+    signature = private_key.sign(MESSAGE)
+
+    public_key.verify(MESSAGE, signature)
+    assert signature.algorithm == "MandatoryHybrid"
+    assert signature.signer_key_id == private_key.key_id
+    assert private_key.usage_count == 1
+    assert isinstance(signature.bytes, bytes)
+
+
+def test_tampered_message_raises_signature_error():
+    private_key, public_key = af_pqc.PrivateKey.generate()
+    signature = private_key.sign(MESSAGE)
+
+    with pytest.raises(af_pqc.SignatureVerificationError):
+        public_key.verify(b"tampered", signature)
+
+
+def test_trailing_bytes_raise_malformed_encoding():
+    private_key, public_key = af_pqc.PrivateKey.generate()
+    encoded = json.loads(private_key.sign(MESSAGE).to_json())
+    encoded["bytes"].append(0)
+    padded = af_pqc.Signature.from_json(json.dumps(encoded).encode())
+
+    with pytest.raises(af_pqc.MalformedEncodingError):
+        public_key.verify(MESSAGE, padded)
+
+
+def test_exceptions_share_base_class():
+    private_key, _ = af_pqc.PrivateKey.generate()
+
+    with pytest.raises(af_pqc.InvalidOperationError):
+        private_key.sign(b"")
+    assert issubclass(af_pqc.InvalidKeyError, af_pqc.AfPqcError)
+    assert issubclass(af_pqc.SerializationError, af_pqc.AfPqcError)
+
+
+def test_seed_derivation_is_deterministic():
+    first_private, first_public = af_pqc.generate_key_from_seed(SEED)
+    second_private, second_public = af_pqc.PrivateKey.from_seed(SEED)
+
+    assert first_public.bytes == second_public.bytes
+    assert first_private.key_id == second_private.key_id
+    first_public.verify(MESSAGE, second_private.sign(MESSAGE))
+
+
+def test_seed_length_is_checked():
+    with pytest.raises(af_pqc.InvalidKeyError):
+        af_pqc.generate_key_from_seed(b"short")
+
+
+def test_json_round_trip():
+    private_key, public_key = af_pqc.PrivateKey.generate()
+    signature = private_key.sign(MESSAGE)
+
+    parsed_key = af_pqc.PublicKey.from_json(public_key.to_json().encode())
+    parsed_signature = af_pqc.Signature.from_json(signature.to_json().encode())
+
+    assert parsed_key.bytes == public_key.bytes
+    parsed_key.verify(MESSAGE, parsed_signature)
+
+
+def test_binary_round_trip():
+    private_key, public_key = af_pqc.PrivateKey.generate()
+    signature = private_key.sign(MESSAGE)
+
+    parsed_key = af_pqc.PublicKey.from_binary(public_key.to_binary())
+    parsed_signature = af_pqc.Signature.from_binary(signature.to_binary())
+
+    parsed_key.verify(MESSAGE, parsed_signature)
+
+
+def test_malformed_input_raises_serialization_error():
+    with pytest.raises(af_pqc.SerializationError):
+        af_pqc.PublicKey.from_json(b"{not json")
+    with pytest.raises(af_pqc.SerializationError):
+        af_pqc.Signature.from_binary(b"\x00\x01")
+
+
+def test_secure_random_bytes():
+    data = af_pqc.secure_random_bytes(32)
+    assert isinstance(data, bytes)
+    assert len(data) == 32
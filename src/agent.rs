@@ -0,0 +1,518 @@
+//! # Signing Agent
+//!
+//! An ssh-agent style daemon that keeps decrypted hybrid keys in memory and
+//! signs on behalf of local clients, so that seeds and passphrases never need
+//! to be loaded into every build process.
+//!
+//! ## Protocol
+//!
+//! The agent listens on a Unix domain socket (`$AF_AGENT_SOCK`, default
+//! `$AF_HOME/agent.sock`). Connections from a different user ID than the
+//! agent's own (or the one given to [`Agent::with_client_uid`]) are rejected
+//! using the socket peer credentials.
+//!
+//! Every message in either direction is a frame:
+//!
+//! ```text
+//! +----------------------+--------------------------------------+
+//! | length: u32 (BE)     | payload: bincode (fixint, little end) |
+//! +----------------------+--------------------------------------+
+//! ```
+//!
+//! The client sends one [`AgentRequest`] per frame and the agent answers each
+//! with exactly one [`AgentResponse`], in order, on the same connection.
+//! Frames larger than [`MAX_FRAME_LEN`] are rejected and close the connection.
+//!
+//! | Request                    | Success response             |
+//! |----------------------------|------------------------------|
+//! | `ListKeys`                 | `Keys` (empty while locked)  |
+//! | `PublicKey { name }`       | `PublicKey`                  |
+//! | `Sign { name, message }`   | `Signature`                  |
+//! | `AddKey { .. }`            | `Success`                    |
+//! | `RemoveKey { name }`       | `Success`                    |
+//! | `Lock { passphrase }`      | `Success`                    |
+//! | `Unlock { passphrase }`    | `Success`                    |
+//!
+//! Any request may instead be answered with `Failure { message }`.
+
+use crate::constant_time;
+use crate::keystore::Keystore;
+use crate::{CryptoError, PrivateKey, PublicKey, Signature};
+use bincode::Options;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+use zeroize::Zeroizing;
+
+/// Environment variable naming the agent socket
+pub const AF_AGENT_SOCK_ENV: &str = "AF_AGENT_SOCK";
+
+/// Largest accepted frame payload (a 1 MiB message plus envelope)
+pub const MAX_FRAME_LEN: u32 = 2 * 1024 * 1024;
+
+/// Request sent from a client to the agent; passphrases are zeroized on drop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AgentRequest {
+    ListKeys,
+    PublicKey { name: String },
+    Sign { name: String, message: Vec<u8> },
+    /// Load a key from the agent's keystore; `lifetime` is in seconds
+    AddKey { name: String, passphrase: Zeroizing<String>, lifetime: Option<u64> },
+    RemoveKey { name: String },
+    Lock { passphrase: Zeroizing<String> },
+    Unlock { passphrase: Zeroizing<String> },
+}
+
+/// Response sent from the agent to a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AgentResponse {
+    Keys(Vec<AgentKeyInfo>),
+    PublicKey(PublicKey),
+    Signature(Signature),
+    Success,
+    Failure { message: String },
+}
+
+/// Description of a key held by the agent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentKeyInfo {
+    pub name: String,
+    pub key_id: String,
+    /// Seconds until the key is forgotten, if it has a lifetime
+    pub expires_in: Option<u64>,
+}
+
+/// Default socket path: `$AF_AGENT_SOCK`, or `agent.sock` in the keystore root
+pub fn default_socket_path() -> Result<PathBuf, CryptoError> {
+    if let Some(path) = std::env::var_os(AF_AGENT_SOCK_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+    Ok(Keystore::default_root()?.join("agent.sock"))
+}
+
+struct LoadedKey {
+    private_key: PrivateKey,
+    public_key: PublicKey,
+    expires_at: Option<Instant>,
+}
+
+/// Lock passphrase stretched with the keystore's Argon2id under a random salt
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct LockHash {
+    salt: [u8; 16],
+    hash: [u8; 32],
+}
+
+impl LockHash {
+    /// Hash `passphrase` under a fresh random salt
+    fn new(passphrase: &str) -> Result<Self, CryptoError> {
+        let mut salt = [0u8; 16];
+        crate::secure_random_bytes(&mut salt)?;
+        let hash = *crate::keystore::derive_passphrase_key(passphrase.as_bytes(), &salt)?;
+        Ok(Self { salt, hash })
+    }
+
+    /// Whether `passphrase` is the one this hash was made from, in constant time
+    fn matches(&self, passphrase: &str) -> Result<bool, CryptoError> {
+        let hash = crate::keystore::derive_passphrase_key(passphrase.as_bytes(), &self.salt)?;
+        Ok(constant_time::eq(&self.hash, &*hash))
+    }
+}
+
+struct AgentState {
+    keys: BTreeMap<String, LoadedKey>,
+    /// Hash of the lock passphrase while the agent is locked
+    lock: Option<LockHash>,
+}
+
+impl AgentState {
+    fn purge_expired(&mut self) {
+        let now = Instant::now();
+        self.keys.retain(|name, key| {
+            let alive = key.expires_at.is_none_or(|expires_at| expires_at > now);
+            if !alive {
+                info!("Key '{}' lifetime expired - removed from agent", name);
+            }
+            alive
+        });
+    }
+}
+
+/// In-memory signing agent backed by a keystore
+#[derive(Clone)]
+pub struct Agent {
+    keystore: Keystore,
+    state: Arc<Mutex<AgentState>>,
+    client_uid: u32,
+}
+
+impl Agent {
+    /// Create an agent that loads keys from `keystore` and serves its own user
+    pub fn new(keystore: Keystore) -> Self {
+        Self {
+            keystore,
+            state: Arc::new(Mutex::new(AgentState {
+                keys: BTreeMap::new(),
+                lock: None,
+            })),
+            client_uid: current_uid(),
+        }
+    }
+
+    /// Accept connections only from `uid` instead of the agent's own user ID
+    pub fn with_client_uid(mut self, uid: u32) -> Self {
+        self.client_uid = uid;
+        self
+    }
+
+    /// Names of the keys held in memory, including expired keys not yet reaped
+    pub fn loaded_keys(&self) -> Result<Vec<String>, CryptoError> {
+        Ok(self.lock_state()?.keys.keys().cloned().collect())
+    }
+
+    /// Decrypt a keystore key and hold it in memory, optionally for a limited time
+    pub fn add_key(&self, name: &str, passphrase: &[u8], lifetime: Option<Duration>) -> Result<(), CryptoError> {
+        let private_key = self.keystore.load_private_key(name, passphrase)?;
+        let public_key = private_key.public_key()?;
+        let mut state = self.lock_state()?;
+        state.keys.insert(name.to_string(), LoadedKey {
+            private_key,
+            public_key,
+            expires_at: lifetime.map(|lifetime| Instant::now() + lifetime),
+        });
+        info!("Key '{}' added to agent", name);
+        Ok(())
+    }
+
+    /// Handle a single protocol request
+    pub fn handle(&self, request: AgentRequest) -> AgentResponse {
+        match self.dispatch(request) {
+            Ok(response) => response,
+            Err(e) => AgentResponse::Failure { message: e.to_string() },
+        }
+    }
+
+    fn dispatch(&self, request: AgentRequest) -> Result<AgentResponse, CryptoError> {
+        match request {
+            AgentRequest::ListKeys => {
+                let mut state = self.lock_state()?;
+                if state.lock.is_some() {
+                    // A locked agent behaves as if it held no keys
+                    return Ok(AgentResponse::Keys(Vec::new()));
+                }
+                state.purge_expired();
+                let now = Instant::now();
+                let keys = state.keys.iter()
+                    .map(|(name, key)| AgentKeyInfo {
+                        name: name.clone(),
+                        key_id: key.private_key.key_id.clone(),
+                        expires_in: key.expires_at.map(|at| at.saturating_duration_since(now).as_secs()),
+                    })
+                    .collect();
+                Ok(AgentResponse::Keys(keys))
+            }
+            AgentRequest::PublicKey { name } => {
+                let state = self.unlocked_state()?;
+                let key = state.keys.get(&name)
+                    .ok_or(CryptoError::KeyNotFound { name })?;
+                Ok(AgentResponse::PublicKey(key.public_key.clone()))
+            }
+            AgentRequest::Sign { name, message } => {
+                if !self.unlocked_state()?.keys.contains_key(&name) {
+                    return Err(CryptoError::KeyNotFound { name });
+                }
+                // Keep the keystore policy and persistent usage counter
+                // authoritative; its file IO runs without holding the state lock
+                self.keystore.record_usage(&name)?;
+                let state = self.unlocked_state()?;
+                let key = state.keys.get(&name)
+                    .ok_or_else(|| CryptoError::KeyNotFound { name: name.clone() })?;
+                debug!("Signing {} bytes with '{}'", message.len(), name);
+                Ok(AgentResponse::Signature(key.private_key.sign(&message)?))
+            }
+            AgentRequest::AddKey { name, passphrase, lifetime } => {
+                drop(self.unlocked_state()?);
+                self.add_key(&name, passphrase.as_bytes(), lifetime.map(Duration::from_secs))?;
+                Ok(AgentResponse::Success)
+            }
+            AgentRequest::RemoveKey { name } => {
+                let mut state = self.unlocked_state()?;
+                state.keys.remove(&name)
+                    .ok_or(CryptoError::KeyNotFound { name: name.clone() })?;
+                info!("Key '{}' removed from agent", name);
+                Ok(AgentResponse::Success)
+            }
+            // The passphrase is stretched without holding the state lock
+            AgentRequest::Lock { passphrase } => {
+                drop(self.unlocked_state()?);
+                if passphrase.is_empty() {
+                    return Err(agent_error("Lock passphrase must not be empty"));
+                }
+                let hash = LockHash::new(&passphrase)?;
+                let mut state = self.unlocked_state()?;
+                state.lock = Some(hash);
+                info!("Agent locked");
+                Ok(AgentResponse::Success)
+            }
+            AgentRequest::Unlock { passphrase } => {
+                let hash = self.lock_state()?.lock.ok_or_else(|| agent_error("Agent is not locked"))?;
+                if !hash.matches(&passphrase)? {
+                    return Err(agent_error("Incorrect lock passphrase"));
+                }
+                let mut state = self.lock_state()?;
+                // Another client may have unlocked and locked again meanwhile
+                if state.lock != Some(hash) {
+                    return Err(agent_error("Agent lock changed during unlock"));
+                }
+                state.lock = None;
+                info!("Agent unlocked");
+                Ok(AgentResponse::Success)
+            }
+        }
+    }
+
+    /// Agent state with expired keys purged, or an error while the agent is locked
+    fn unlocked_state(&self) -> Result<std::sync::MutexGuard<'_, AgentState>, CryptoError> {
+        let mut state = self.lock_state()?;
+        if state.lock.is_some() {
+            return Err(agent_error("Agent is locked"));
+        }
+        state.purge_expired();
+        Ok(state)
+    }
+
+    fn lock_state(&self) -> Result<std::sync::MutexGuard<'_, AgentState>, CryptoError> {
+        self.state.lock().map_err(|_| agent_error("Agent state poisoned"))
+    }
+
+    /// Bind the socket and serve clients until the future is dropped
+    pub async fn serve(self, socket_path: &Path) -> Result<(), CryptoError> {
+        let listener = bind_socket(socket_path)?;
+        info!("Agent listening on {}", socket_path.display());
+        self.serve_listener(listener).await
+    }
+
+    /// Serve clients on an already bound listener
+    pub async fn serve_listener(self, listener: UnixListener) -> Result<(), CryptoError> {
+        // Forget expired keys even when no client is talking to the agent. The
+        // reaper holds the key state, so it stops when serving stops, on error
+        // or when this future is dropped.
+        let reaper = self.clone();
+        let _reaper = AbortOnDrop(tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                if let Ok(mut state) = reaper.lock_state() {
+                    state.purge_expired();
+                }
+            }
+        }));
+
+        loop {
+            let (stream, _) = listener.accept().await.map_err(|e| agent_error(&format!("Accept failed: {}", e)))?;
+
+            match stream.peer_cred() {
+                Ok(cred) if cred.uid() == self.client_uid => {}
+                Ok(cred) => {
+                    warn!("Rejected agent connection from uid {} (pid {:?})", cred.uid(), cred.pid());
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Rejected agent connection without peer credentials: {}", e);
+                    continue;
+                }
+            }
+
+            let agent = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = agent.serve_connection(stream).await {
+                    debug!("Agent connection closed: {}", e);
+                }
+            });
+        }
+    }
+
+    async fn serve_connection(&self, mut stream: UnixStream) -> Result<(), CryptoError> {
+        while let Some(request) = read_frame::<AgentRequest>(&mut stream).await? {
+            // Requests touch the keystore files and may run Argon2
+            let agent = self.clone();
+            let response = tokio::task::spawn_blocking(move || agent.handle(request)).await
+                .map_err(|e| agent_error(&format!("Request handler failed: {}", e)))?;
+            write_frame(&mut stream, &response).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Client connection to a running agent
+pub struct AgentClient {
+    stream: UnixStream,
+}
+
+impl AgentClient {
+    /// Connect to the agent listening on `socket_path`
+    pub async fn connect(socket_path: &Path) -> Result<Self, CryptoError> {
+        let stream = UnixStream::connect(socket_path).await.map_err(|e| {
+            agent_error(&format!("Cannot connect to agent at {}: {}", socket_path.display(), e))
+        })?;
+        Ok(Self { stream })
+    }
+
+    /// Connect to the agent at the default socket path
+    pub async fn connect_default() -> Result<Self, CryptoError> {
+        Self::connect(&default_socket_path()?).await
+    }
+
+    /// Send a request and wait for its response
+    pub async fn request(&mut self, request: &AgentRequest) -> Result<AgentResponse, CryptoError> {
+        write_frame(&mut self.stream, request).await?;
+        match read_frame::<AgentResponse>(&mut self.stream).await? {
+            Some(AgentResponse::Failure { message }) => Err(CryptoError::Agent { details: message }),
+            Some(response) => Ok(response),
+            None => Err(agent_error("Agent closed the connection")),
+        }
+    }
+
+    /// List keys held by the agent
+    pub async fn list_keys(&mut self) -> Result<Vec<AgentKeyInfo>, CryptoError> {
+        match self.request(&AgentRequest::ListKeys).await? {
+            AgentResponse::Keys(keys) => Ok(keys),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Fetch the public key of a loaded key
+    pub async fn public_key(&mut self, name: &str) -> Result<PublicKey, CryptoError> {
+        match self.request(&AgentRequest::PublicKey { name: name.to_string() }).await? {
+            AgentResponse::PublicKey(public_key) => Ok(public_key),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Sign a message with a loaded key
+    pub async fn sign(&mut self, name: &str, message: &[u8]) -> Result<Signature, CryptoError> {
+        let request = AgentRequest::Sign { name: name.to_string(), message: message.to_vec() };
+        match self.request(&request).await? {
+            AgentResponse::Signature(signature) => Ok(signature),
+            other => Err(unexpected(other)),
+        }
+    }
+
+    /// Ask the agent to load a key from its keystore
+    pub async fn add_key(&mut self, name: &str, passphrase: &str, lifetime: Option<u64>) -> Result<(), CryptoError> {
+        let request = AgentRequest::AddKey {
+            name: name.to_string(),
+            passphrase: Zeroizing::new(passphrase.to_string()),
+            lifetime,
+        };
+        self.expect_success(&request).await
+    }
+
+    /// Ask the agent to forget a key
+    pub async fn remove_key(&mut self, name: &str) -> Result<(), CryptoError> {
+        self.expect_success(&AgentRequest::RemoveKey { name: name.to_string() }).await
+    }
+
+    /// Lock the agent with a passphrase
+    pub async fn lock(&mut self, passphrase: &str) -> Result<(), CryptoError> {
+        self.expect_success(&AgentRequest::Lock { passphrase: Zeroizing::new(passphrase.to_string()) }).await
+    }
+
+    /// Unlock the agent
+    pub async fn unlock(&mut self, passphrase: &str) -> Result<(), CryptoError> {
+        self.expect_success(&AgentRequest::Unlock { passphrase: Zeroizing::new(passphrase.to_string()) }).await
+    }
+
+    async fn expect_success(&mut self, request: &AgentRequest) -> Result<(), CryptoError> {
+        match self.request(request).await? {
+            AgentResponse::Success => Ok(()),
+            other => Err(unexpected(other)),
+        }
+    }
+}
+
+/// Bind the agent socket, replacing a stale one, with mode 0600
+fn bind_socket(socket_path: &Path) -> Result<UnixListener, CryptoError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    if socket_path.exists() {
+        if std::os::unix::net::UnixStream::connect(socket_path).is_ok() {
+            return Err(agent_error(&format!("An agent is already listening on {}", socket_path.display())));
+        }
+        std::fs::remove_file(socket_path)
+            .map_err(|e| agent_error(&format!("Failed to remove stale socket: {}", e)))?;
+    }
+
+    let listener = UnixListener::bind(socket_path)
+        .map_err(|e| agent_error(&format!("Failed to bind {}: {}", socket_path.display(), e)))?;
+    std::fs::set_permissions(socket_path, std::fs::Permissions::from_mode(0o600))
+        .map_err(|e| agent_error(&format!("Failed to restrict socket permissions: {}", e)))?;
+    Ok(listener)
+}
+
+fn frame_codec() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_limit(MAX_FRAME_LEN as u64)
+}
+
+async fn write_frame<T: Serialize>(stream: &mut UnixStream, value: &T) -> Result<(), CryptoError> {
+    // Frames may carry passphrases
+    let payload = Zeroizing::new(frame_codec().serialize(value)
+        .map_err(|e| agent_error(&format!("Failed to encode frame: {}", e)))?);
+    let len = u32::try_from(payload.len())
+        .ok()
+        .filter(|len| *len <= MAX_FRAME_LEN)
+        .ok_or_else(|| agent_error("Frame too large"))?;
+
+    let io_error = |e: std::io::Error| agent_error(&format!("Failed to write frame: {}", e));
+    stream.write_u32(len).await.map_err(io_error)?;
+    stream.write_all(&payload).await.map_err(io_error)?;
+    Ok(())
+}
+
+/// Read one frame; `None` on a clean end of stream
+async fn read_frame<T: serde::de::DeserializeOwned>(stream: &mut UnixStream) -> Result<Option<T>, CryptoError> {
+    let len = match stream.read_u32().await {
+        Ok(len) => len,
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(agent_error(&format!("Failed to read frame: {}", e))),
+    };
+    if len > MAX_FRAME_LEN {
+        return Err(agent_error(&format!("Frame of {} bytes exceeds limit", len)));
+    }
+
+    let mut payload = Zeroizing::new(vec![0u8; len as usize]);
+    stream.read_exact(&mut payload).await
+        .map_err(|e| agent_error(&format!("Failed to read frame: {}", e)))?;
+    frame_codec().deserialize(&payload)
+        .map(Some)
+        .map_err(|e| agent_error(&format!("Malformed frame: {}", e)))
+}
+
+/// Aborts a background task when dropped
+struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+fn current_uid() -> u32 {
+    // SAFETY: getuid has no preconditions and cannot fail
+    unsafe { libc::getuid() }
+}
+
+fn agent_error(details: &str) -> CryptoError {
+    CryptoError::Agent { details: details.to_string() }
+}
+
+fn unexpected(response: AgentResponse) -> CryptoError {
+    agent_error(&format!("Unexpected agent response: {:?}", response))
+}
@@ -1,6 +1,18 @@
 use clap::{Parser, Subcommand, CommandFactory};
 use clap_complete::{generate, Shell};
-use af_pqc::{PublicKey, Signature};
+use af_pqc::{Fingerprint, PublicKey, Signature};
+use af_pqc::bundle::{KeyCertificate, SignatureBundle};
+use af_pqc::hd::{DerivationPath, ExtendedPrivateKey};
+use af_pqc::kdf::{KeySchedule, KeyScheduleVersion};
+use af_pqc::multisig::{MultiSignature, Policy};
+use af_pqc::shamir::DealtShare;
+use af_pqc::timestamp::{LocalTimestampAuthority, TimestampAuthority, TimestampRequest, TimestampedSignature};
+use af_pqc::trust::{KeyUsage, TrustStore, TrustedKey};
+use af_pqc::keystore::{Keystore, KeyPolicy, DEFAULT_MAX_USAGE};
+use af_pqc::agent::{Agent, AgentClient};
+use af_pqc::server::{ServerConfig, SigningService};
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
 use std::fs;
 use std::io::{self, Read};
 use std::path::PathBuf;
@@ -8,6 +20,7 @@ use sha2::{Sha256, Digest};
 use anyhow::{Result, Context};
 use log::{info, warn, error, debug};
 use indicatif::{ProgressBar, ProgressStyle};
+use zeroize::Zeroizing;
 
 #[derive(Parser)]
 #[command(name = "af-cli")]
@@ -20,6 +33,15 @@ struct Cli {
     /// Enable verbose logging
     #[arg(short, long, global = true)]
     verbose: bool,
+    
+    /// Key schedule for seed-derived keys: v1, or legacy for keys created before v1
+    /// [default: legacy for plain hex seeds, v1 otherwise]
+    #[arg(long, global = true)]
+    key_schedule: Option<KeyScheduleVersion>,
+    
+    /// Combine seeds with a passphrase, read from $AF_SEED_PASSPHRASE or prompted
+    #[arg(long, global = true)]
+    seed_passphrase: bool,
 }
 
 #[derive(Subcommand)]
@@ -33,7 +55,7 @@ enum Commands {
     
     /// Generate cryptographically secure seed
     GenerateSeed {
-        /// Output format: hex or base64
+        /// Output format: hex, base64 or mnemonic (24 words)
         #[arg(short, long, default_value = "hex")]
         format: String,
     },
@@ -48,16 +70,28 @@ enum Commands {
         #[arg(short, long, default_value = "signing")]
         key_type: String,
         
-        /// Seed phrase for deterministic key generation (32 hex chars)
+        /// Seed for deterministic key generation (64 hex chars or 24-word mnemonic)
         #[arg(short, long)]
         seed: String,
     },
     
     /// Sign a file or message with hybrid post-quantum signature
     Sign {
-        /// Seed phrase for deterministic key generation (32 hex chars)
+        /// Seed for deterministic key generation (64 hex chars or 24-word mnemonic)
+        #[arg(short, long, conflicts_with = "key")]
+        seed: Option<String>,
+        
+        /// Sign with the key derived at this path below the seed (e.g. m/af/0/service-name)
+        #[arg(long, requires = "seed")]
+        path: Option<DerivationPath>,
+        
+        /// Keystore key to sign with (defaults to the keystore default key)
         #[arg(short, long)]
-        seed: String,
+        key: Option<String>,
+        
+        /// Sign through the running signing agent instead of decrypting the key here
+        #[arg(long, conflicts_with = "seed")]
+        agent: bool,
         
         /// Input file to sign (or stdin if not provided)
         #[arg(short, long)]
@@ -70,17 +104,103 @@ enum Commands {
         /// Message to sign directly (alternative to input file)
         #[arg(short, long)]
         message: Option<String>,
+        
+        /// Write a signature bundle carrying the public key, verifiable with verify --bundle
+        #[arg(long)]
+        bundle: bool,
+        
+        /// Certificate file to include in the bundle, from the signer towards the root (repeatable)
+        #[arg(long, requires = "bundle")]
+        certificate: Vec<PathBuf>,
     },
     
-    /// Verify a post-quantum hybrid signature
-    Verify {
-        /// Public key file (JSON format)
+    /// Certify another public key, e.g. a service key or the successor of this key
+    Certify {
+        /// Seed of the issuing key (64 hex chars or 24-word mnemonic)
+        #[arg(short, long, conflicts_with = "key")]
+        seed: Option<String>,
+        
+        /// Issue with the key derived at this path below the seed (e.g. m/af/0/service-name)
+        #[arg(long, requires = "seed")]
+        path: Option<DerivationPath>,
+        
+        /// Keystore key issuing the certificate (defaults to the keystore default key)
+        #[arg(short, long)]
+        key: Option<String>,
+        
+        /// Sign through the running signing agent instead of decrypting the key here
+        #[arg(long, conflicts_with = "seed")]
+        agent: bool,
+        
+        /// Public key to certify (JSON format)
         #[arg(short = 'P', long)]
         public_key: PathBuf,
         
-        /// Signature file (JSON format)
+        /// Output certificate file (JSON format)
         #[arg(short, long)]
-        signature: PathBuf,
+        output: PathBuf,
+    },
+    
+    /// Add a hybrid signature to a multi-signature file, creating it if needed
+    Cosign {
+        /// Seed for deterministic key generation (64 hex chars or 24-word mnemonic)
+        #[arg(short, long, conflicts_with = "key")]
+        seed: Option<String>,
+        
+        /// Sign with the key derived at this path below the seed (e.g. m/af/0/service-name)
+        #[arg(long, requires = "seed")]
+        path: Option<DerivationPath>,
+        
+        /// Keystore key to sign with (defaults to the keystore default key)
+        #[arg(short, long)]
+        key: Option<String>,
+        
+        /// Sign through the running signing agent instead of decrypting the key here
+        #[arg(long, conflicts_with = "seed")]
+        agent: bool,
+        
+        /// Input file to sign (or stdin if not provided)
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+        
+        /// Multi-signature file (JSON format), updated in place
+        #[arg(short = 'S', long)]
+        signatures: PathBuf,
+        
+        /// Message to sign directly (alternative to input file)
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    
+    /// Verify a post-quantum hybrid signature
+    Verify {
+        /// Public key file (JSON format); repeat with --policy to list the keys of a multi-signature
+        #[arg(short = 'P', long, required_unless_present = "bundle", conflicts_with = "bundle")]
+        public_key: Vec<PathBuf>,
+        
+        /// Signature file (JSON format), a multi-signature file with --policy
+        #[arg(short, long, required_unless_present = "bundle", conflicts_with = "bundle")]
+        signature: Option<PathBuf>,
+        
+        /// Signature bundle (JSON format), verified against the trust store or the pinned fingerprints
+        #[arg(short, long)]
+        bundle: Option<PathBuf>,
+        
+        /// Trust store file for --bundle (default: $AF_HOME/trust.json)
+        #[arg(long, requires = "bundle", conflicts_with = "trusted")]
+        trust_store: Option<PathBuf>,
+        
+        /// Pinned public key fingerprint trusted for --bundle instead of the trust store (repeatable)
+        #[arg(long, group = "trusted", requires = "bundle")]
+        pin: Vec<Fingerprint>,
+        
+        /// File of pinned fingerprints for --bundle instead of the trust store, one per line
+        #[arg(long, group = "trusted", requires = "bundle")]
+        pins: Option<PathBuf>,
+        
+        /// Verify a multi-signature: all, M-of-N or weighted:THRESHOLD:W1,W2,... over the listed keys
+        #[arg(long, conflicts_with = "bundle")]
+        policy: Option<Policy>,
         
         /// Input file that was signed (or stdin if not provided)
         #[arg(short, long)]
@@ -91,6 +211,25 @@ enum Commands {
         message: Option<String>,
     },
     
+    /// Derive the hybrid keypair at a path below a master seed
+    Derive {
+        /// Master seed (64 hex chars or 24-word mnemonic)
+        #[arg(short, long)]
+        seed: String,
+        
+        /// Derivation path, e.g. m/af/0/service-name (append ' to harden a segment)
+        #[arg(long)]
+        path: DerivationPath,
+        
+        /// Output file for the derived public key (JSON format)
+        #[arg(short = 'P', long)]
+        public_key: PathBuf,
+        
+        /// Also write the extended public key of the path (JSON format)
+        #[arg(long)]
+        xpub: Option<PathBuf>,
+    },
+    
     /// Generate cryptographic address from public key
     Address {
         /// Public key file (JSON format)
@@ -101,6 +240,269 @@ enum Commands {
         #[arg(short, long, default_value = "hex")]
         format: String,
     },
+    
+    /// Split a master seed into Shamir shares or restore it from them
+    Seed {
+        #[command(subcommand)]
+        command: SeedCommands,
+    },
+    
+    /// Issue or check RFC 3161-style timestamp tokens for signatures
+    Timestamp {
+        #[command(subcommand)]
+        command: TimestampCommands,
+    },
+    
+    /// Manage the public keys trusted by verify --bundle
+    Trust {
+        /// Trust store file (default: $AF_HOME/trust.json)
+        #[arg(long)]
+        store: Option<PathBuf>,
+        
+        #[command(subcommand)]
+        command: TrustCommands,
+    },
+    
+    /// Manage keys in the local keystore ($AF_HOME)
+    Key {
+        #[command(subcommand)]
+        command: KeyCommands,
+    },
+    
+    /// Run or control the signing agent
+    Agent {
+        /// Agent socket path (default: $AF_AGENT_SOCK or $AF_HOME/agent.sock)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+        
+        #[command(subcommand)]
+        command: AgentCommands,
+    },
+    
+    /// Serve the HTTP/JSON signing and verification API on localhost
+    Serve {
+        /// Address to listen on (must be a loopback address)
+        #[arg(short, long, default_value = "127.0.0.1:8787")]
+        bind: SocketAddr,
+        
+        /// Keystore keys to serve
+        #[arg(short, long)]
+        key: Vec<String>,
+        
+        /// JSON file mapping key names to SHA-256 hex hashes of their bearer tokens
+        #[arg(short, long)]
+        tokens: Option<PathBuf>,
+        
+        /// Largest accepted request body in bytes
+        #[arg(long, default_value_t = 2 * 1024 * 1024)]
+        max_body_bytes: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum AgentCommands {
+    /// Start the agent in the foreground
+    Start {
+        /// Keystore keys to load at startup
+        #[arg(short, long)]
+        key: Vec<String>,
+        
+        /// Seconds after which loaded keys are forgotten
+        #[arg(short, long)]
+        lifetime: Option<u64>,
+    },
+    
+    /// List keys held by the agent
+    List,
+    
+    /// Load a keystore key into the agent
+    Add {
+        /// Key name
+        name: String,
+        
+        /// Seconds after which the key is forgotten
+        #[arg(short, long)]
+        lifetime: Option<u64>,
+    },
+    
+    /// Remove a key from the agent
+    Remove {
+        /// Key name
+        name: String,
+    },
+    
+    /// Lock the agent with a passphrase
+    Lock,
+    
+    /// Unlock a locked agent
+    Unlock,
+}
+
+#[derive(Subcommand)]
+enum SeedCommands {
+    /// Split a seed into share files, any THRESHOLD of which restore it
+    Split {
+        /// Seed to split (64 hex chars or 24-word mnemonic)
+        #[arg(short, long)]
+        seed: String,
+        
+        /// Number of shares needed to restore the seed
+        #[arg(short, long)]
+        threshold: u8,
+        
+        /// Number of shares to create
+        #[arg(short = 'n', long)]
+        shares: u8,
+        
+        /// Directory for the share files (share-1.json, share-2.json, ...)
+        #[arg(short, long)]
+        output: PathBuf,
+        
+        /// Keystore key signing each share as dealer
+        #[arg(long)]
+        dealer_key: Option<String>,
+    },
+    
+    /// Restore a seed from share files
+    Combine {
+        /// Share files
+        #[arg(required = true)]
+        shares: Vec<PathBuf>,
+        
+        /// Require shares signed by the dealer with this public key (JSON format)
+        #[arg(long)]
+        dealer: Option<PathBuf>,
+        
+        /// Output format: hex or mnemonic
+        #[arg(short, long, default_value = "hex")]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TimestampCommands {
+    /// Act as timestamp authority: attach a token signed by a keystore key to a signature or bundle
+    Issue {
+        /// Keystore key of the authority (defaults to the keystore default key)
+        #[arg(short, long)]
+        key: Option<String>,
+        
+        /// Signature or signature bundle file (JSON format)
+        #[arg(short, long)]
+        signature: PathBuf,
+        
+        /// Output file (default: update the signature file in place)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    
+    /// Check the timestamp token of a signature or bundle against the authority's public key
+    Verify {
+        /// Public key of the timestamp authority (JSON format)
+        #[arg(short = 'P', long)]
+        public_key: PathBuf,
+        
+        /// Timestamped signature or signature bundle file (JSON format)
+        #[arg(short, long)]
+        signature: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum TrustCommands {
+    /// Trust a public key under a label
+    Add {
+        /// Label identifying the key holder
+        label: String,
+        
+        /// Public key to trust (JSON format)
+        #[arg(short = 'P', long)]
+        public_key: PathBuf,
+        
+        /// What the key is trusted for: sign, certify, timestamp, witness (comma-separated)
+        #[arg(short, long, value_delimiter = ',', default_value = "sign")]
+        usage: Vec<KeyUsage>,
+        
+        /// Unix timestamp before which the key is not trusted
+        #[arg(long)]
+        not_before: Option<u64>,
+        
+        /// Unix timestamp after which the key is not trusted
+        #[arg(long)]
+        not_after: Option<u64>,
+    },
+    
+    /// Stop trusting a key
+    Remove {
+        /// Label or fingerprint of the key
+        key: String,
+    },
+    
+    /// List trusted keys
+    List,
+}
+
+#[derive(Subcommand)]
+enum KeyCommands {
+    /// Generate a new key and store it encrypted in the keystore
+    Generate {
+        /// Name of the new key
+        name: String,
+        
+        /// Seed for deterministic key generation (64 hex chars or 24-word mnemonic)
+        #[arg(short, long)]
+        seed: Option<String>,
+        
+        /// Derive the key at this path below the seed (e.g. m/af/0/service-name)
+        #[arg(long, requires = "seed")]
+        path: Option<DerivationPath>,
+        
+        /// Maximum number of signatures before the key must be rotated
+        #[arg(long, default_value_t = DEFAULT_MAX_USAGE)]
+        max_usage: u64,
+        
+        /// Unix timestamp after which the key may no longer sign
+        #[arg(long)]
+        not_after: Option<u64>,
+        
+        /// Make the new key the default key
+        #[arg(long)]
+        default: bool,
+    },
+    
+    /// List stored keys
+    List,
+    
+    /// Show details of a stored key
+    Show {
+        /// Key name
+        name: String,
+        
+        /// Also write the public key to this file (JSON format)
+        #[arg(short = 'P', long)]
+        public_key: Option<PathBuf>,
+    },
+    
+    /// Delete a stored key
+    Delete {
+        /// Key name
+        name: String,
+    },
+    
+    /// Rename a stored key
+    Rename {
+        /// Current key name
+        from: String,
+        
+        /// New key name
+        to: String,
+    },
+    
+    /// Show or set the default key
+    Default {
+        /// Key name to make the default
+        name: Option<String>,
+    },
 }
 
 #[tokio::main]
@@ -115,6 +517,7 @@ async fn main() -> Result<()> {
     info!("Authority Fabric Cryptographic CLI v0.1.0");
     debug!("Post-quantum hybrid cryptography (Dilithium3 + Ed25519)");
     
+    let seed_options = SeedOptions { key_schedule: cli.key_schedule, passphrase: cli.seed_passphrase };
     let result = match cli.command {
         Commands::Completions { shell } => {
             cmd_completions(shell).await
@@ -123,17 +526,49 @@ async fn main() -> Result<()> {
             cmd_generate_seed(format).await
         }
         Commands::Keygen { public_key, key_type, seed } => {
-            cmd_keygen(public_key, key_type, seed).await
+            cmd_keygen(public_key, key_type, seed, seed_options).await
+        }
+        Commands::Sign { seed, path, key, agent, input, output, message, bundle, certificate } => {
+            let bundle = bundle.then_some(certificate);
+            cmd_sign(seed, path, seed_options, key, agent, input, output, message, bundle).await
         }
-        Commands::Sign { seed, input, output, message } => {
-            cmd_sign(seed, input, output, message).await
+        Commands::Certify { seed, path, key, agent, public_key, output } => {
+            cmd_certify(seed, path, seed_options, key, agent, public_key, output).await
         }
-        Commands::Verify { public_key, signature, input, message } => {
-            cmd_verify(public_key, signature, input, message).await
+        Commands::Cosign { seed, path, key, agent, input, signatures, message } => {
+            cmd_cosign(seed, path, seed_options, key, agent, input, signatures, message).await
+        }
+        Commands::Verify { public_key, signature, bundle, trust_store, pin, pins, policy, input, message } => {
+            match (bundle, signature) {
+                (Some(bundle), _) => cmd_verify_bundle(bundle, trust_store, pin, pins, input, message).await,
+                (None, Some(signature)) => cmd_verify(public_key, signature, policy, input, message).await,
+                (None, None) => unreachable!("clap requires --signature without --bundle"),
+            }
+        }
+        Commands::Derive { seed, path, public_key, xpub } => {
+            cmd_derive(seed, path, seed_options, public_key, xpub).await
         }
         Commands::Address { public_key, format } => {
             cmd_address(public_key, format).await
         }
+        Commands::Seed { command } => {
+            cmd_seed(command).await
+        }
+        Commands::Timestamp { command } => {
+            cmd_timestamp(command).await
+        }
+        Commands::Trust { store, command } => {
+            cmd_trust(store, command).await
+        }
+        Commands::Key { command } => {
+            cmd_key(command, seed_options).await
+        }
+        Commands::Agent { socket, command } => {
+            cmd_agent(socket, command).await
+        }
+        Commands::Serve { bind, key, tokens, max_body_bytes } => {
+            cmd_serve(bind, key, tokens, max_body_bytes).await
+        }
     };
     
     match result {
@@ -142,7 +577,7 @@ async fn main() -> Result<()> {
             Ok(())
         }
         Err(e) => {
-            error!("Operation failed: {}", e);
+            error!("Operation failed: {:#}", e);
             std::process::exit(1);
         }
     }
@@ -165,22 +600,23 @@ async fn cmd_generate_seed(format: String) -> Result<()> {
     
     info!("Generating cryptographically secure 32-byte seed");
     
-    let mut seed = [0u8; 32];
-    af_pqc::secure_random_bytes(&mut seed)
+    let mut seed = Zeroizing::new([0u8; 32]);
+    af_pqc::secure_random_bytes(seed.as_mut_slice())
         .with_context(|| "Failed to generate secure random bytes - insufficient system entropy")?;
     
     pb.finish_with_message("Secure seed generated");
     
-    let output = match format.as_str() {
-        "hex" => hex::encode(seed),
+    let output = Zeroizing::new(match format.as_str() {
+        "hex" => hex::encode(seed.as_slice()),
         "base64" => {
             use base64::{Engine, engine::general_purpose};
-            general_purpose::STANDARD.encode(seed)
+            general_purpose::STANDARD.encode(seed.as_slice())
         },
-        _ => return Err(anyhow::anyhow!("Invalid format '{}' - supported formats: hex, base64", format)),
-    };
+        "mnemonic" => af_pqc::mnemonic::encode(&seed).to_string(),
+        _ => return Err(anyhow::anyhow!("Invalid format '{}' - supported formats: hex, base64, mnemonic", format)),
+    });
     
-    println!("{}", output);
+    println!("{}", output.as_str());
     info!("Secure seed generated using OS entropy");
     warn!("CRITICAL: Store this seed securely - it is your master secret");
     warn!("Anyone with this seed can regenerate your private keys");
@@ -188,20 +624,11 @@ async fn cmd_generate_seed(format: String) -> Result<()> {
     Ok(())
 }
 
-async fn cmd_keygen(public_key_path: PathBuf, key_type_str: String, seed: String) -> Result<()> {
-    // Validate and parse seed
-    if seed.len() != 64 {
-        return Err(anyhow::anyhow!("Seed must be exactly 64 hex characters (32 bytes)"));
-    }
-    let seed_bytes: [u8; 32] = hex::decode(&seed)
-        .context("Invalid hex seed")?
-        .try_into()
-        .map_err(|_| anyhow::anyhow!("Seed must be exactly 32 bytes"))?;
-    
+async fn cmd_keygen(public_key_path: PathBuf, key_type_str: String, seed: String, seed_options: SeedOptions) -> Result<()> {
     info!("Generating deterministic post-quantum hybrid keypair");
     debug!("Key type: {}", key_type_str);
     
-    let (private_key, public_key) = af_pqc::generate_key_from_seed(&seed_bytes)
+    let (private_key, public_key) = keypair_from_seed(seed, None, seed_options)
         .context("Failed to generate deterministic keypair")?;
     
     // Save only public key - private key never touches disk
@@ -220,39 +647,10 @@ async fn cmd_keygen(public_key_path: PathBuf, key_type_str: String, seed: String
     Ok(())
 }
 
-async fn cmd_sign(seed: String, input_path: Option<PathBuf>, output_path: PathBuf, message: Option<String>) -> Result<()> {
-    // Validate and parse seed
-    if seed.len() != 64 {
-        return Err(anyhow::anyhow!("Seed must be exactly 64 hex characters (32 bytes)"));
-    }
-    let seed_bytes: [u8; 32] = hex::decode(&seed)
-        .context("Invalid hex seed")?
-        .try_into()
-        .map_err(|_| anyhow::anyhow!("Seed must be exactly 32 bytes"))?;
-    
-    info!("Regenerating private key from seed for signing operation");
-    
-    // Regenerate private key deterministically from seed
-    let (private_key, _public_key) = af_pqc::generate_key_from_seed(&seed_bytes)
-        .context("Failed to regenerate keypair from seed")?;
-    
-    info!("Using deterministic key: {}", private_key.key_id);
-    
-    // Get message to sign
-    let message_bytes = if let Some(msg) = message {
-        debug!("Signing direct message of {} bytes", msg.len());
-        msg.into_bytes()
-    } else if let Some(input) = input_path {
-        debug!("Reading input file: {}", input.display());
-        fs::read(&input)
-            .with_context(|| format!("Failed to read input file: {}", input.display()))?
-    } else {
-        debug!("Reading from stdin");
-        let mut buffer = Vec::new();
-        io::stdin().read_to_end(&mut buffer)
-            .context("Failed to read from stdin")?;
-        buffer
-    };
+#[allow(clippy::too_many_arguments)]
+async fn cmd_sign(seed: Option<String>, path: Option<DerivationPath>, seed_options: SeedOptions, key: Option<String>, use_agent: bool, input_path: Option<PathBuf>, output_path: PathBuf, message: Option<String>, bundle: Option<Vec<PathBuf>>) -> Result<()> {
+    let signing_key = SigningKeySource::resolve(seed, path, seed_options, key, use_agent)?;
+    let message_bytes = read_message(message, input_path)?;
     
     if message_bytes.is_empty() {
         warn!("Input message is empty");
@@ -260,9 +658,29 @@ async fn cmd_sign(seed: String, input_path: Option<PathBuf>, output_path: PathBu
     
     info!("Signing {} bytes with hybrid algorithm", message_bytes.len());
     
-    let signature = private_key.sign(&message_bytes)
+    let signature = signing_key.sign(&message_bytes).await
         .context("Hybrid signature generation failed")?;
     
+    if let Some(certificate_paths) = bundle {
+        let mut signature_bundle = SignatureBundle::new(signature, signing_key.public_key().await?)?;
+        for path in certificate_paths {
+            let certificate: KeyCertificate = serde_json::from_str(&fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read certificate file: {}", path.display()))?)
+                .with_context(|| format!("Failed to parse certificate JSON: {}", path.display()))?;
+            signature_bundle = signature_bundle.with_certificate(certificate)?;
+        }
+        
+        let bundle_json = serde_json::to_string_pretty(&signature_bundle)
+            .context("Failed to serialize signature bundle")?;
+        fs::write(&output_path, bundle_json)
+            .context("Failed to write signature bundle")?;
+        
+        info!("Signature bundle saved to: {}", output_path.display());
+        info!("Signer: {}", signature_bundle.public_key().fingerprint());
+        info!("Certificates: {}", signature_bundle.chain().len());
+        return Ok(());
+    }
+    
     // Save signature in JSON format (Signature implements Serialize/Deserialize)
     let signature_json = serde_json::to_string_pretty(&signature)
         .context("Failed to serialize signature")?;
@@ -277,38 +695,167 @@ async fn cmd_sign(seed: String, input_path: Option<PathBuf>, output_path: PathBu
     Ok(())
 }
 
-async fn cmd_verify(public_key_path: PathBuf, signature_path: PathBuf, input_path: Option<PathBuf>, message: Option<String>) -> Result<()> {
-    debug!("Loading public key from: {}", public_key_path.display());
+async fn cmd_certify(seed: Option<String>, path: Option<DerivationPath>, seed_options: SeedOptions, key: Option<String>, use_agent: bool, public_key_path: PathBuf, output_path: PathBuf) -> Result<()> {
+    let issuing_key = SigningKeySource::resolve(seed, path, seed_options, key, use_agent)?;
+    let subject = read_public_key(&public_key_path)?;
+    subject.hybrid().context("Invalid public key")?;
+    let issuer = issuing_key.public_key().await?;
     
-    // Load public key (JSON format)
-    let public_key_json = fs::read_to_string(&public_key_path)
-        .context("Failed to read public key file")?;
-    let public_key: PublicKey = serde_json::from_str(&public_key_json)
-        .context("Failed to parse public key JSON")?;
+    info!("Certifying key {} with {}", subject.fingerprint(), issuer.fingerprint());
+    let signature = issuing_key.sign(&KeyCertificate::message(&subject)).await
+        .context("Hybrid signature generation failed")?;
+    let certificate = KeyCertificate { issuer, signature };
+    certificate.verify(&subject)?;
     
-    debug!("Loading signature from: {}", signature_path.display());
+    let certificate_json = serde_json::to_string_pretty(&certificate)
+        .context("Failed to serialize certificate")?;
+    fs::write(&output_path, certificate_json)
+        .context("Failed to write certificate file")?;
+    info!("Certificate saved to: {}", output_path.display());
+    
+    Ok(())
+}
+
+async fn cmd_verify_bundle(bundle_path: PathBuf, trust_store_path: Option<PathBuf>, mut pinned: Vec<Fingerprint>, pins_path: Option<PathBuf>, input_path: Option<PathBuf>, message: Option<String>) -> Result<()> {
+    debug!("Loading signature bundle from: {}", bundle_path.display());
+    let bundle: SignatureBundle = serde_json::from_str(&fs::read_to_string(&bundle_path)
+        .context("Failed to read signature bundle")?)
+        .context("Failed to parse signature bundle JSON")?;
+    let message_bytes = read_message(message, input_path)?;
+    info!("Verifying signature bundle for {} bytes", message_bytes.len());
+    
+    if pinned.is_empty() && pins_path.is_none() {
+        let path = match trust_store_path {
+            Some(path) => path,
+            None => TrustStore::default_path()?,
+        };
+        debug!("Trust store: {}", path.display());
+        let trust_store = TrustStore::load(&path)?;
+        let now = af_pqc::clock::default_clock().now();
+        
+        return match trust_store.verify_bundle(&bundle, &message_bytes, now) {
+            Ok(trusted) => {
+                info!("✓ Signature verification PASSED");
+                if trusted.verification.chain_length == 0 {
+                    info!("Signed by trusted key '{}' ({})", trusted.identity.label, trusted.verification.signer);
+                } else {
+                    info!("Signed by {}, certified by trusted key '{}' through {} certificate(s)",
+                        trusted.verification.signer, trusted.identity.label, trusted.verification.chain_length);
+                }
+                info!("Signed at: {} (signer's clock)", bundle.signature().created_at);
+                if let (Some(time), Some(authority)) = (trusted.verification.timestamp, trusted.timestamp_authority) {
+                    info!("Timestamped at: {} by '{}'", time, authority.label);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                error!("✗ Signature verification FAILED: {}", e);
+                Err(anyhow::anyhow!("Signature verification failed: {}", e))
+            }
+        };
+    }
+    
+    if let Some(path) = pins_path {
+        let pins = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read pins file: {}", path.display()))?;
+        for line in pins.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+            pinned.push(line.parse().with_context(|| format!("Invalid pin in {}", path.display()))?);
+        }
+    }
+    debug!("{} pinned fingerprints", pinned.len());
+    
+    match bundle.verify(&message_bytes, &pinned) {
+        Ok(verification) => {
+            info!("✓ Signature verification PASSED");
+            info!("Signer: {} ({})", verification.signer, bundle.signature().signer_key_id);
+            if verification.chain_length > 0 {
+                info!("Certified by pinned key {} through {} certificate(s)", verification.anchor, verification.chain_length);
+            }
+            info!("Signed at: {} (signer's clock)", bundle.signature().created_at);
+            if let Some(time) = verification.timestamp {
+                info!("Timestamped at: {}", time);
+            }
+        }
+        Err(e) => {
+            error!("✗ Signature verification FAILED: {}", e);
+            return Err(anyhow::anyhow!("Signature verification failed: {}", e));
+        }
+    }
+    
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn cmd_cosign(seed: Option<String>, path: Option<DerivationPath>, seed_options: SeedOptions, key: Option<String>, use_agent: bool, input_path: Option<PathBuf>, signatures_path: PathBuf, message: Option<String>) -> Result<()> {
+    let signing_key = SigningKeySource::resolve(seed, path, seed_options, key, use_agent)?;
+    let message_bytes = read_message(message, input_path)?;
+    
+    let mut multisig = if signatures_path.exists() {
+        debug!("Loading multi-signature from: {}", signatures_path.display());
+        let multisig_json = fs::read_to_string(&signatures_path)
+            .context("Failed to read multi-signature file")?;
+        serde_json::from_str(&multisig_json)
+            .context("Failed to parse multi-signature JSON")?
+    } else {
+        info!("Starting new multi-signature: {}", signatures_path.display());
+        MultiSignature::new(&message_bytes)
+    };
+    
+    let public_key = signing_key.public_key().await?;
+    info!("Co-signing {} bytes as {}", message_bytes.len(), public_key.fingerprint());
+    let signature = signing_key.sign(&message_bytes).await
+        .context("Hybrid signature generation failed")?;
+    multisig.add(&message_bytes, &public_key, signature)
+        .context("Failed to add signature")?;
+    
+    let multisig_json = serde_json::to_string_pretty(&multisig)
+        .context("Failed to serialize multi-signature")?;
+    fs::write(&signatures_path, multisig_json)
+        .context("Failed to write multi-signature file")?;
+    
+    info!("Multi-signature saved to: {}", signatures_path.display());
+    for signer in multisig.signers() {
+        info!("Signed by: {}", signer);
+    }
+    
+    Ok(())
+}
+
+async fn cmd_verify(public_key_paths: Vec<PathBuf>, signature_path: PathBuf, policy: Option<Policy>, input_path: Option<PathBuf>, message: Option<String>) -> Result<()> {
+    let public_keys = public_key_paths.iter()
+        .map(|path| read_public_key(path))
+        .collect::<Result<Vec<_>>>()?;
     
-    // Load signature (JSON format)
+    debug!("Loading signature from: {}", signature_path.display());
     let signature_json = fs::read_to_string(&signature_path)
         .context("Failed to read signature file")?;
-    let signature: Signature = serde_json::from_str(&signature_json)
-        .context("Failed to parse signature JSON")?;
+    let message_bytes = read_message(message, input_path)?;
     
-    // Get message to verify
-    let message_bytes = if let Some(msg) = message {
-        debug!("Verifying direct message of {} bytes", msg.len());
-        msg.into_bytes()
-    } else if let Some(input) = input_path {
-        debug!("Reading input file: {}", input.display());
-        fs::read(&input)
-            .with_context(|| format!("Failed to read input file: {}", input.display()))?
-    } else {
-        debug!("Reading from stdin");
-        let mut buffer = Vec::new();
-        io::stdin().read_to_end(&mut buffer)
-            .context("Failed to read from stdin")?;
-        buffer
+    if let Some(policy) = policy {
+        let multisig: MultiSignature = serde_json::from_str(&signature_json)
+            .context("Failed to parse multi-signature JSON")?;
+        info!("Verifying multi-signature for {} bytes against policy {}", message_bytes.len(), policy);
+        
+        return match multisig.verify(&message_bytes, &public_keys, &policy) {
+            Ok(signers) => {
+                info!("✓ Multi-signature verification PASSED");
+                for signer in signers {
+                    info!("Signed by: {}", signer);
+                }
+                Ok(())
+            }
+            Err(e) => {
+                error!("✗ Multi-signature verification FAILED: {}", e);
+                Err(anyhow::anyhow!("Multi-signature verification failed: {}", e))
+            }
+        };
+    }
+    
+    let [public_key] = public_keys.as_slice() else {
+        return Err(anyhow::anyhow!("Verifying against several public keys requires --policy"));
     };
+    let signature: Signature = serde_json::from_str(&signature_json)
+        .context("Failed to parse signature JSON")?;
     
     info!("Verifying hybrid signature for {} bytes", message_bytes.len());
     
@@ -329,6 +876,35 @@ async fn cmd_verify(public_key_path: PathBuf, signature_path: PathBuf, input_pat
     Ok(())
 }
 
+async fn cmd_derive(seed: String, path: DerivationPath, seed_options: SeedOptions, public_key_path: PathBuf, xpub_path: Option<PathBuf>) -> Result<()> {
+    info!("Deriving post-quantum hybrid keypair at {}", path);
+    
+    let node = ExtendedPrivateKey::from_schedule(&seed_options.schedule(seed, true)?)
+        .and_then(|master| master.derive_path(&path))
+        .context("Failed to derive key")?;
+    let (private_key, public_key) = node.to_keypair(af_pqc::clock::default_clock())
+        .context("Failed to derive keypair")?;
+    
+    let public_key_json = serde_json::to_string_pretty(&public_key)
+        .context("Failed to serialize public key")?;
+    fs::write(&public_key_path, public_key_json)
+        .context("Failed to write public key file")?;
+    info!("Public key saved to: {}", public_key_path.display());
+    info!("Key ID: {}", private_key.key_id);
+    info!("Fingerprint: {}", public_key.fingerprint());
+    
+    if let Some(xpub_path) = xpub_path {
+        let xpub_json = serde_json::to_string_pretty(&node.extended_public_key())
+            .context("Failed to serialize extended public key")?;
+        fs::write(&xpub_path, xpub_json)
+            .context("Failed to write extended public key file")?;
+        info!("Extended public key saved to: {}", xpub_path.display());
+        warn!("The extended public key plus any non-hardened child private key reveals this key");
+    }
+    
+    Ok(())
+}
+
 async fn cmd_address(public_key_path: PathBuf, format: String) -> Result<()> {
     debug!("Loading public key from: {}", public_key_path.display());
     
@@ -343,8 +919,8 @@ async fn cmd_address(public_key_path: PathBuf, format: String) -> Result<()> {
     // Generate address from public key hash (using SHA-256)
     let mut hasher = Sha256::new();
     hasher.update(&public_key.bytes);
-    hasher.update(&public_key.created_at.to_le_bytes());
-    hasher.update(&public_key.operation_id.to_le_bytes());
+    hasher.update(public_key.created_at.to_le_bytes());
+    hasher.update(public_key.operation_id.to_le_bytes());
     let hash = hasher.finalize();
     
     let address = match format.as_str() {
@@ -368,3 +944,643 @@ async fn cmd_address(public_key_path: PathBuf, format: String) -> Result<()> {
     
     Ok(())
 }
+
+/// Where a signing key comes from
+enum SigningKeySource {
+    Seed(Box<af_pqc::PrivateKey>),
+    Keystore(Keystore, String),
+    Agent(String),
+}
+
+impl SigningKeySource {
+    /// Signing key from an explicit seed, or else a keystore key used directly or through the agent
+    fn resolve(seed: Option<String>, path: Option<DerivationPath>, seed_options: SeedOptions, key: Option<String>, use_agent: bool) -> Result<Self> {
+        if let Some(seed) = seed {
+            info!("Regenerating private key from seed for signing operation");
+            
+            // Regenerate private key deterministically from seed
+            let (private_key, _public_key) = keypair_from_seed(seed, path.as_ref(), seed_options)
+                .context("Failed to regenerate keypair from seed")?;
+            
+            info!("Using deterministic key: {}", private_key.key_id);
+            return Ok(SigningKeySource::Seed(Box::new(private_key)));
+        }
+        
+        let keystore = Keystore::open_default()
+            .context("Failed to open keystore")?;
+        let name = match key {
+            Some(name) => name,
+            None => keystore.default_key()?
+                .ok_or_else(|| anyhow::anyhow!("No --seed or --key given and no default key set"))?,
+        };
+        
+        if use_agent {
+            info!("Using agent key: {}", name);
+            Ok(SigningKeySource::Agent(name))
+        } else {
+            info!("Using keystore key: {}", name);
+            Ok(SigningKeySource::Keystore(keystore, name))
+        }
+    }
+    
+    /// Public key of the signing key
+    async fn public_key(&self) -> Result<PublicKey> {
+        Ok(match self {
+            SigningKeySource::Seed(private_key) => private_key.public_key()?,
+            SigningKeySource::Keystore(keystore, name) => keystore.public_key(name)?,
+            SigningKeySource::Agent(name) => AgentClient::connect_default().await?.public_key(name).await?,
+        })
+    }
+    
+    /// Sign `message`, asking for the keystore passphrase if needed
+    async fn sign(&self, message: &[u8]) -> Result<Signature> {
+        Ok(match self {
+            SigningKeySource::Seed(private_key) => private_key.sign(message)?,
+            SigningKeySource::Keystore(keystore, name) => {
+                let passphrase = read_passphrase(&format!("Passphrase for key '{}': ", name), false)?;
+                keystore.sign(name, passphrase.as_bytes(), message)?
+            }
+            SigningKeySource::Agent(name) => {
+                let mut client = AgentClient::connect_default().await?;
+                client.sign(name, message).await?
+            }
+        })
+    }
+}
+
+/// Message from the command line, an input file, or stdin
+fn read_message(message: Option<String>, input_path: Option<PathBuf>) -> Result<Vec<u8>> {
+    if let Some(msg) = message {
+        debug!("Using direct message of {} bytes", msg.len());
+        return Ok(msg.into_bytes());
+    }
+    if let Some(input) = input_path {
+        debug!("Reading input file: {}", input.display());
+        return fs::read(&input)
+            .with_context(|| format!("Failed to read input file: {}", input.display()));
+    }
+    debug!("Reading from stdin");
+    let mut buffer = Vec::new();
+    io::stdin().read_to_end(&mut buffer)
+        .context("Failed to read from stdin")?;
+    Ok(buffer)
+}
+
+/// Load a public key file (JSON format)
+fn read_public_key(path: &std::path::Path) -> Result<PublicKey> {
+    debug!("Loading public key from: {}", path.display());
+    let public_key_json = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read public key file: {}", path.display()))?;
+    serde_json::from_str(&public_key_json)
+        .with_context(|| format!("Failed to parse public key JSON: {}", path.display()))
+}
+
+/// How seeds given on the command line turn into keys
+#[derive(Clone, Copy)]
+struct SeedOptions {
+    key_schedule: Option<KeyScheduleVersion>,
+    passphrase: bool,
+}
+
+impl SeedOptions {
+    /// Key schedule for a hex or mnemonic seed, prompting for its passphrase if enabled
+    ///
+    /// `hierarchical` seeds feed a derivation path and always default to v1.
+    fn schedule(&self, seed: String, hierarchical: bool) -> Result<KeySchedule> {
+        let mnemonic = seed.trim().contains(char::is_whitespace);
+        let seed = parse_seed(seed)?;
+        let version = match self.key_schedule {
+            Some(version) => version,
+            // Mnemonics, passphrases and paths never existed under the legacy schedule
+            None if mnemonic || self.passphrase || hierarchical => KeyScheduleVersion::V1,
+            None => {
+                warn!("Using the legacy key schedule for a hex seed; pass --key-schedule v1 for new keys");
+                KeyScheduleVersion::Legacy
+            }
+        };
+        debug!("Key schedule: {}", version);
+        if !self.passphrase {
+            return Ok(KeySchedule::with_version(&seed, version));
+        }
+        let passphrase = read_seed_passphrase()?;
+        Ok(KeySchedule::with_passphrase(&seed, &passphrase, version))
+    }
+}
+
+/// Parse a seed given as 64 hex characters or a 24-word mnemonic, clearing the argument afterwards
+fn parse_seed(seed: String) -> Result<Zeroizing<[u8; 32]>> {
+    let seed = Zeroizing::new(seed);
+    if seed.trim().contains(char::is_whitespace) {
+        return af_pqc::mnemonic::decode(&seed).context("Invalid mnemonic seed");
+    }
+    if seed.len() != 64 {
+        return Err(anyhow::anyhow!("Seed must be exactly 64 hex characters (32 bytes) or a 24-word mnemonic"));
+    }
+    let mut bytes = Zeroizing::new([0u8; 32]);
+    hex::decode_to_slice(seed.as_str(), bytes.as_mut_slice())
+        .context("Invalid hex seed")?;
+    Ok(bytes)
+}
+
+/// Regenerate the keypair for a seed, at `path` if given
+fn keypair_from_seed(seed: String, path: Option<&DerivationPath>, seed_options: SeedOptions) -> Result<(af_pqc::PrivateKey, PublicKey)> {
+    let schedule = seed_options.schedule(seed, path.is_some())?;
+    let clock = af_pqc::clock::default_clock();
+    Ok(match path {
+        Some(path) => {
+            debug!("Derivation path: {}", path);
+            ExtendedPrivateKey::from_schedule(&schedule)?.derive_path(path)?.to_keypair(clock)?
+        }
+        None => af_pqc::generate_key_from_schedule(&schedule, clock)?,
+    })
+}
+
+/// Read a seed passphrase from $AF_SEED_PASSPHRASE or the terminal
+fn read_seed_passphrase() -> Result<Zeroizing<String>> {
+    if let Ok(passphrase) = std::env::var("AF_SEED_PASSPHRASE") {
+        debug!("Using seed passphrase from AF_SEED_PASSPHRASE");
+        return Ok(Zeroizing::new(passphrase));
+    }
+    
+    warn!("A mistyped seed passphrase silently gives different keys");
+    Ok(Zeroizing::new(rpassword::prompt_password("Seed passphrase: ")
+        .context("Failed to read seed passphrase")?))
+}
+
+/// Read a keystore passphrase from $AF_PASSPHRASE or the terminal
+fn read_passphrase(prompt: &str, confirm: bool) -> Result<Zeroizing<String>> {
+    if let Ok(passphrase) = std::env::var("AF_PASSPHRASE") {
+        debug!("Using passphrase from AF_PASSPHRASE");
+        return Ok(Zeroizing::new(passphrase));
+    }
+    
+    let passphrase = Zeroizing::new(rpassword::prompt_password(prompt)
+        .context("Failed to read passphrase")?);
+    if confirm {
+        let again = Zeroizing::new(rpassword::prompt_password("Confirm passphrase: ")
+            .context("Failed to read passphrase")?);
+        if again != passphrase {
+            return Err(anyhow::anyhow!("Passphrases do not match"));
+        }
+    }
+    Ok(passphrase)
+}
+
+async fn cmd_seed(command: SeedCommands) -> Result<()> {
+    match command {
+        SeedCommands::Split { seed, threshold, shares, output, dealer_key } => {
+            info!("Splitting seed into {}-of-{} shares", threshold, shares);
+            let seed = parse_seed(seed)?;
+            let split = af_pqc::shamir::split(&seed, threshold, shares)
+                .context("Failed to split seed")?;
+            
+            let dealer = match dealer_key {
+                Some(name) => {
+                    let keystore = Keystore::open_default()
+                        .context("Failed to open keystore")?;
+                    let public_key = keystore.public_key(&name)?;
+                    let passphrase = read_passphrase(&format!("Passphrase for key '{}': ", name), false)?;
+                    info!("Signing shares as dealer '{}' ({})", name, public_key.fingerprint());
+                    Some((keystore, name, public_key, passphrase))
+                }
+                None => None,
+            };
+            
+            if let Some(share) = split.first() {
+                info!("Share group: {}", hex::encode(share.group_id()));
+            }
+            fs::create_dir_all(&output)
+                .with_context(|| format!("Failed to create {}", output.display()))?;
+            for share in split {
+                let index = share.index();
+                let dealt = match &dealer {
+                    Some((keystore, name, public_key, passphrase)) => {
+                        let signature = keystore.sign(name, passphrase.as_bytes(), &share.dealer_message())
+                            .with_context(|| format!("Failed to sign share {}", index))?;
+                        DealtShare::signed(share, public_key.clone(), signature)?
+                    }
+                    None => DealtShare::unsigned(share),
+                };
+                let path = output.join(format!("share-{}.json", index));
+                let json = Zeroizing::new(serde_json::to_string_pretty(&dealt)
+                    .context("Failed to serialize share")?);
+                write_new_private_file(&path, json.as_bytes())?;
+                info!("Share {} saved to: {}", index, path.display());
+            }
+            
+            warn!("Give each share to a different custodian and delete the share files here");
+            warn!("Any {} shares restore the seed; fewer reveal nothing about it", threshold);
+        }
+        SeedCommands::Combine { shares, dealer, format } => {
+            let dealt: Vec<DealtShare> = shares.iter()
+                .map(|path| {
+                    let json = Zeroizing::new(fs::read_to_string(path)
+                        .with_context(|| format!("Failed to read share file: {}", path.display()))?);
+                    serde_json::from_str(&json)
+                        .with_context(|| format!("Invalid share file: {}", path.display()))
+                })
+                .collect::<Result<_>>()?;
+            
+            let expected_dealer = match dealer {
+                Some(path) => {
+                    let public_key: PublicKey = serde_json::from_str(&fs::read_to_string(&path)
+                        .context("Failed to read dealer public key file")?)
+                        .context("Invalid dealer public key format")?;
+                    Some(public_key.fingerprint())
+                }
+                None => None,
+            };
+            
+            info!("Combining {} shares", dealt.len());
+            let (seed, signer) = af_pqc::shamir::combine_dealt(&dealt, expected_dealer.as_ref())
+                .context("Failed to restore seed")?;
+            match (signer, expected_dealer) {
+                (Some(signer), Some(_)) => info!("All shares signed by dealer {}", signer),
+                (Some(signer), None) => warn!("Shares are signed by {}; pass --dealer to require this dealer", signer),
+                (None, _) => warn!("Shares carry no dealer signature"),
+            }
+            
+            let output = match format.as_str() {
+                "hex" => Zeroizing::new(hex::encode(seed.as_slice())),
+                "mnemonic" => af_pqc::mnemonic::encode(&seed),
+                _ => return Err(anyhow::anyhow!("Invalid format '{}' - supported formats: hex, mnemonic", format)),
+            };
+            println!("{}", output.as_str());
+            warn!("CRITICAL: Store this seed securely - it is your master secret");
+        }
+    }
+    
+    Ok(())
+}
+
+/// Write `data` to a new file readable only by the owner
+fn write_new_private_file(path: &std::path::Path, data: &[u8]) -> Result<()> {
+    use std::io::Write;
+    
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)
+        .and_then(|mut file| file.write_all(data))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// File a timestamp token is attached to
+enum TimestampTarget {
+    Signature(Signature),
+    Timestamped(TimestampedSignature),
+    Bundle(Box<SignatureBundle>),
+}
+
+impl TimestampTarget {
+    /// Parse a signature bundle, timestamped signature or plain signature file
+    fn read(path: &std::path::Path) -> Result<Self> {
+        debug!("Loading signature from: {}", path.display());
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read signature file: {}", path.display()))?;
+        if let Ok(bundle) = serde_json::from_str(&json) {
+            return Ok(TimestampTarget::Bundle(Box::new(bundle)));
+        }
+        if let Ok(timestamped) = serde_json::from_str(&json) {
+            return Ok(TimestampTarget::Timestamped(timestamped));
+        }
+        serde_json::from_str(&json)
+            .map(TimestampTarget::Signature)
+            .with_context(|| format!("Failed to parse signature JSON: {}", path.display()))
+    }
+    
+    fn signature(&self) -> &Signature {
+        match self {
+            TimestampTarget::Signature(signature) => signature,
+            TimestampTarget::Timestamped(timestamped) => timestamped.signature(),
+            TimestampTarget::Bundle(bundle) => bundle.signature(),
+        }
+    }
+}
+
+async fn cmd_timestamp(command: TimestampCommands) -> Result<()> {
+    match command {
+        TimestampCommands::Issue { key, signature, output } => {
+            let keystore = Keystore::open_default()
+                .context("Failed to open keystore")?;
+            let name = match key {
+                Some(name) => name,
+                None => keystore.default_key()?
+                    .ok_or_else(|| anyhow::anyhow!("No --key given and no default key set"))?,
+            };
+            let target = TimestampTarget::read(&signature)?;
+            let request = TimestampRequest::new(target.signature());
+            
+            info!("Issuing timestamp token with keystore key: {}", name);
+            let passphrase = read_passphrase(&format!("Passphrase for key '{}': ", name), false)?;
+            let private_key = keystore.load_private_key(&name, passphrase.as_bytes())?;
+            // The key's persisted usage counter numbers the tokens, so serials never repeat
+            let serial = keystore.record_usage(&name)?;
+            let authority = LocalTimestampAuthority::with_first_serial(private_key, af_pqc::clock::default_clock(), serial);
+            let token = authority.timestamp(&request)
+                .context("Timestamp token generation failed")?;
+            info!("Token {} at {} by {}", token.serial(), token.time(), token.authority().fingerprint());
+            
+            let stamped_json = match target {
+                TimestampTarget::Bundle(bundle) => serde_json::to_string_pretty(&bundle.with_timestamp(token)?),
+                TimestampTarget::Signature(signature) => serde_json::to_string_pretty(&TimestampedSignature::new(signature, token)?),
+                TimestampTarget::Timestamped(timestamped) => {
+                    warn!("Replacing the existing timestamp token {}", timestamped.token().serial());
+                    let (signature, _) = timestamped.into_parts();
+                    serde_json::to_string_pretty(&TimestampedSignature::new(signature, token)?)
+                }
+            }.context("Failed to serialize timestamped signature")?;
+            let output = output.unwrap_or(signature);
+            fs::write(&output, stamped_json)
+                .context("Failed to write timestamped signature")?;
+            info!("Timestamped signature saved to: {}", output.display());
+        }
+        TimestampCommands::Verify { public_key, signature } => {
+            let authority = read_public_key(&public_key)?;
+            let target = TimestampTarget::read(&signature)?;
+            let token = match &target {
+                TimestampTarget::Signature(_) => None,
+                TimestampTarget::Timestamped(timestamped) => Some(timestamped.token()),
+                TimestampTarget::Bundle(bundle) => bundle.timestamp(),
+            }.ok_or_else(|| anyhow::anyhow!("{} carries no timestamp token", signature.display()))?;
+            
+            match token.verify_with_authority(target.signature(), &authority) {
+                Ok(time) => {
+                    info!("✓ Timestamp verification PASSED");
+                    info!("Timestamped at: {} by {} (token {})", time, authority.fingerprint(), token.serial());
+                    info!("Signed at: {} (signer's clock)", target.signature().created_at);
+                }
+                Err(e) => {
+                    error!("✗ Timestamp verification FAILED: {}", e);
+                    return Err(anyhow::anyhow!("Timestamp verification failed: {}", e));
+                }
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+async fn cmd_trust(store: Option<PathBuf>, command: TrustCommands) -> Result<()> {
+    let path = match store {
+        Some(path) => path,
+        None => {
+            // Creates $AF_HOME with the keystore's permissions
+            Keystore::open_default().context("Failed to open keystore")?;
+            TrustStore::default_path()?
+        }
+    };
+    debug!("Trust store: {}", path.display());
+    let mut trust_store = TrustStore::load(&path)?;
+    
+    match command {
+        TrustCommands::Add { label, public_key, usage, not_before, not_after } => {
+            let key = TrustedKey::new(&label, read_public_key(&public_key)?, &usage)
+                .and_then(|key| key.with_validity(not_before, not_after))
+                .context("Invalid trusted key")?;
+            let fingerprint = key.fingerprint();
+            trust_store.add(key)?;
+            trust_store.save(&path)?;
+            info!("Trusting {} as '{}'", fingerprint, label);
+        }
+        TrustCommands::Remove { key } => {
+            let removed = trust_store.remove(&key)?;
+            trust_store.save(&path)?;
+            warn!("No longer trusting '{}' ({})", removed.label, removed.fingerprint());
+        }
+        TrustCommands::List => {
+            if trust_store.keys().is_empty() {
+                info!("Trust store is empty");
+            }
+            for key in trust_store.keys() {
+                let usages: Vec<String> = key.usages.iter().map(ToString::to_string).collect();
+                let validity = match (key.not_before, key.not_after) {
+                    (None, None) => "always".to_string(),
+                    (start, end) => format!(
+                        "{}..{}",
+                        start.map(|t| t.to_string()).unwrap_or_default(),
+                        end.map(|t| t.to_string()).unwrap_or_default()
+                    ),
+                };
+                println!("{}\t{}\t{}\t{}", key.label, key.fingerprint(), usages.join(","), validity);
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+async fn cmd_key(command: KeyCommands, seed_options: SeedOptions) -> Result<()> {
+    let keystore = Keystore::open_default()
+        .context("Failed to open keystore")?;
+    debug!("Keystore: {}", keystore.root().display());
+    
+    match command {
+        KeyCommands::Generate { name, seed, path, max_usage, not_after, default } => {
+            af_pqc::keystore::validate_key_name(&name)?;
+            if keystore.contains(&name) {
+                return Err(anyhow::anyhow!("Key '{}' already exists", name));
+            }
+            
+            let (private_key, _public_key) = match seed {
+                Some(seed) => {
+                    info!("Generating deterministic post-quantum hybrid keypair");
+                    keypair_from_seed(seed, path.as_ref(), seed_options)
+                }
+                None => {
+                    info!("Generating random post-quantum hybrid keypair");
+                    Ok(af_pqc::PrivateKey::generate()?)
+                }
+            }.context("Failed to generate keypair")?;
+            
+            let passphrase = read_passphrase(&format!("New passphrase for key '{}': ", name), true)?;
+            let policy = KeyPolicy { max_usage, not_after };
+            let record = keystore.insert(&name, &private_key, policy, passphrase.as_bytes())
+                .context("Failed to store key")?;
+            
+            if default {
+                keystore.set_default_key(&name)?;
+            }
+            
+            info!("Key '{}' stored in {}", record.name, keystore.root().display());
+            info!("Key ID: {}", record.key_id);
+        }
+        KeyCommands::List => {
+            let default_key = keystore.default_key()?;
+            let records = keystore.list()?;
+            if records.is_empty() {
+                info!("Keystore is empty");
+            }
+            for record in records {
+                let marker = if default_key.as_deref() == Some(record.name.as_str()) { "*" } else { " " };
+                println!("{} {}\t{}\t{:?}", marker, record.name, record.key_id, record.algorithm);
+            }
+        }
+        KeyCommands::Show { name, public_key } => {
+            let record = keystore.record(&name)?;
+            let usage = keystore.usage(&name)?;
+            let stored_public_key = keystore.public_key(&name)?;
+            
+            println!("Name:        {}", record.name);
+            println!("Key ID:      {}", record.key_id);
+            println!("Algorithm:   {:?}", record.algorithm);
+            println!("Created at:  {}", record.created_at);
+            println!("Usage:       {} / {}", usage, record.policy.max_usage);
+            match record.policy.not_after {
+                Some(not_after) => println!("Not after:   {}", not_after),
+                None => println!("Not after:   never"),
+            }
+            println!("Public key:  {} bytes", stored_public_key.bytes.len());
+            
+            if let Some(path) = public_key {
+                let public_key_json = serde_json::to_string_pretty(&stored_public_key)
+                    .context("Failed to serialize public key")?;
+                fs::write(&path, public_key_json)
+                    .context("Failed to write public key file")?;
+                info!("Public key saved to: {}", path.display());
+            }
+        }
+        KeyCommands::Delete { name } => {
+            keystore.delete(&name)?;
+            warn!("Key '{}' permanently deleted", name);
+        }
+        KeyCommands::Rename { from, to } => {
+            keystore.rename(&from, &to)?;
+            info!("Key '{}' renamed to '{}'", from, to);
+        }
+        KeyCommands::Default { name } => {
+            match name {
+                Some(name) => {
+                    keystore.set_default_key(&name)?;
+                    info!("Default key set to '{}'", name);
+                }
+                None => match keystore.default_key()? {
+                    Some(name) => println!("{}", name),
+                    None => info!("No default key set"),
+                },
+            }
+        }
+    }
+    
+    Ok(())
+}
+
+async fn cmd_agent(socket: Option<PathBuf>, command: AgentCommands) -> Result<()> {
+    let socket_path = match socket {
+        Some(path) => path,
+        None => af_pqc::agent::default_socket_path()?,
+    };
+    
+    if let AgentCommands::Start { key, lifetime } = command {
+        let keystore = Keystore::open_default()
+            .context("Failed to open keystore")?;
+        let agent = Agent::new(keystore);
+        for name in key {
+            let passphrase = read_passphrase(&format!("Passphrase for key '{}': ", name), false)?;
+            agent.add_key(&name, passphrase.as_bytes(), lifetime.map(std::time::Duration::from_secs))
+                .with_context(|| format!("Failed to load key '{}'", name))?;
+        }
+        
+        warn!("Agent holds decrypted keys in memory - stop it with Ctrl-C");
+        tokio::select! {
+            result = agent.serve(&socket_path) => result?,
+            _ = tokio::signal::ctrl_c() => info!("Agent shutting down"),
+        }
+        let _ = fs::remove_file(&socket_path);
+        return Ok(());
+    }
+    
+    let mut client = AgentClient::connect(&socket_path).await?;
+    match command {
+        AgentCommands::Start { .. } => unreachable!("handled above"),
+        AgentCommands::List => {
+            let keys = client.list_keys().await?;
+            if keys.is_empty() {
+                info!("Agent holds no keys (or is locked)");
+            }
+            for key in keys {
+                match key.expires_in {
+                    Some(secs) => println!("{}\t{}\texpires in {}s", key.name, key.key_id, secs),
+                    None => println!("{}\t{}", key.name, key.key_id),
+                }
+            }
+        }
+        AgentCommands::Add { name, lifetime } => {
+            let passphrase = read_passphrase(&format!("Passphrase for key '{}': ", name), false)?;
+            client.add_key(&name, &passphrase, lifetime).await?;
+            info!("Key '{}' added to agent", name);
+        }
+        AgentCommands::Remove { name } => {
+            client.remove_key(&name).await?;
+            info!("Key '{}' removed from agent", name);
+        }
+        AgentCommands::Lock => {
+            let passphrase = Zeroizing::new(rpassword::prompt_password("Lock passphrase: ")
+                .context("Failed to read passphrase")?);
+            client.lock(&passphrase).await?;
+            info!("Agent locked");
+        }
+        AgentCommands::Unlock => {
+            let passphrase = Zeroizing::new(rpassword::prompt_password("Unlock passphrase: ")
+                .context("Failed to read passphrase")?);
+            client.unlock(&passphrase).await?;
+            info!("Agent unlocked");
+        }
+    }
+    
+    Ok(())
+}
+
+async fn cmd_serve(bind: SocketAddr, keys: Vec<String>, tokens_path: Option<PathBuf>, max_body_bytes: usize) -> Result<()> {
+    if !bind.ip().is_loopback() {
+        return Err(anyhow::anyhow!("Refusing to listen on non-loopback address {}", bind));
+    }
+    
+    // Token file: { "<key name>": ["<sha256 hex of token>", ...] }
+    let token_map: BTreeMap<String, Vec<String>> = match &tokens_path {
+        Some(path) => {
+            let tokens_json = fs::read_to_string(path)
+                .context("Failed to read token file")?;
+            serde_json::from_str(&tokens_json)
+                .context("Failed to parse token file JSON")?
+        }
+        None if keys.is_empty() => BTreeMap::new(),
+        None => return Err(anyhow::anyhow!("Serving keys requires --tokens")),
+    };
+    
+    let keystore = Keystore::open_default()
+        .context("Failed to open keystore")?;
+    let config = ServerConfig { max_body_bytes, ..ServerConfig::default() };
+    let mut service = SigningService::new(keystore, config);
+    
+    for name in keys {
+        let token_hashes = token_map.get(&name)
+            .ok_or_else(|| anyhow::anyhow!("No tokens configured for key '{}'", name))?
+            .iter()
+            .map(|hash| {
+                hex::decode(hash).ok()
+                    .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                    .ok_or_else(|| anyhow::anyhow!("Invalid token hash for key '{}'", name))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        
+        let passphrase = read_passphrase(&format!("Passphrase for key '{}': ", name), false)?;
+        service.add_key(&name, passphrase.as_bytes(), token_hashes)
+            .with_context(|| format!("Failed to load key '{}'", name))?;
+        info!("Serving key '{}'", name);
+    }
+    
+    let listener = tokio::net::TcpListener::bind(bind).await
+        .with_context(|| format!("Failed to bind {}", bind))?;
+    
+    tokio::select! {
+        result = service.serve(listener) => result?,
+        _ = tokio::signal::ctrl_c() => info!("Signing service shutting down"),
+    }
+    
+    Ok(())
+}
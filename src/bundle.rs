@@ -0,0 +1,194 @@
+//! # Signature Bundles
+//!
+//! A [`SignatureBundle`] is a self-describing detached signature: the hybrid
+//! signature, the signer's public key, an optional chain of
+//! [`KeyCertificate`]s and an optional [`TimestampToken`]. Verifiers need no
+//! key file, only the fingerprints of the keys they trust.
+//!
+//! Certificate `i` of the chain is issued by a key vouching for the key
+//! certified by certificate `i - 1`, or for the signer's key if `i = 0`. This
+//! covers both certification (a root key certifies service keys) and
+//! succession (a retired key certifies its replacement).
+//! [`SignatureBundle::verify`] walks the chain from the signer towards the
+//! root and stops at the first pinned key, so certificates beyond it are not
+//! needed. [`SignatureBundle::verify_with`] takes a predicate instead of a
+//! pinned set, so callers such as [`crate::trust::TrustStore`] can trust a
+//! key for signing but not for certifying, or the other way round.
+
+use crate::clock::Clock;
+use crate::timestamp::TimestampToken;
+use crate::trust::KeyUsage;
+use crate::{CryptoError, Fingerprint, PrivateKey, PublicKey, Signature};
+use alloc::format;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Current bundle format version
+pub const BUNDLE_VERSION: u8 = 1;
+
+/// Longest certificate chain a bundle may carry
+pub const MAX_CHAIN_LENGTH: usize = 8;
+
+/// A key's statement that it vouches for another key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyCertificate {
+    pub issuer: PublicKey,
+    pub signature: Signature,
+}
+
+/// Detached signature with everything needed to verify it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureBundle {
+    version: u8,
+    signature: Signature,
+    public_key: PublicKey,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    chain: Vec<KeyCertificate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timestamp: Option<TimestampToken>,
+}
+
+/// Outcome of verifying a [`SignatureBundle`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleVerification {
+    /// Fingerprint of the signing key
+    pub signer: Fingerprint,
+    /// Pinned key the signer was reached from, the signer itself if pinned
+    pub anchor: Fingerprint,
+    /// Certificates checked between the signer and the anchor
+    pub chain_length: usize,
+    /// Time vouched for by a pinned timestamp authority
+    pub timestamp: Option<u64>,
+}
+
+impl KeyCertificate {
+    /// Certificate by `issuer` for `subject`, timestamped by `clock`
+    pub fn issue(issuer: &PrivateKey, subject: &PublicKey, clock: &(impl Clock + ?Sized)) -> Result<Self, CryptoError> {
+        let signature = issuer.sign_with_clock(&Self::message(subject), clock)?;
+        Ok(Self { issuer: issuer.public_key()?, signature })
+    }
+
+    /// Message an issuer signs to certify `subject`
+    pub fn message(subject: &PublicKey) -> Vec<u8> {
+        [b"af-pqc/certificate/v1".as_slice(), &subject.bytes].concat()
+    }
+
+    /// Check that the issuer vouches for `subject`, returning the issuer's fingerprint
+    pub fn verify(&self, subject: &PublicKey) -> Result<Fingerprint, CryptoError> {
+        self.issuer.verify(&Self::message(subject), &self.signature)
+            .map_err(|e| CryptoError::SignatureVerification {
+                details: format!("Certificate for key {} by {} is invalid: {}", subject.fingerprint(), self.issuer.fingerprint(), e),
+            })?;
+        Ok(self.issuer.fingerprint())
+    }
+}
+
+impl SignatureBundle {
+    /// Bundle of `signature` made by `public_key`, checking the signature's algorithm
+    pub fn new(signature: Signature, public_key: PublicKey) -> Result<Self, CryptoError> {
+        signature.hybrid()?;
+        public_key.hybrid()?;
+        Ok(Self { version: BUNDLE_VERSION, signature, public_key, chain: Vec::new(), timestamp: None })
+    }
+
+    /// Sign `message` with `key`, timestamped by `clock`, and bundle the signature
+    pub fn sign(message: &[u8], key: &PrivateKey, clock: &(impl Clock + ?Sized)) -> Result<Self, CryptoError> {
+        Self::new(key.sign_with_clock(message, clock)?, key.public_key()?)
+    }
+
+    /// Append the next certificate towards the root of the chain
+    pub fn with_certificate(mut self, certificate: KeyCertificate) -> Result<Self, CryptoError> {
+        if self.chain.len() >= MAX_CHAIN_LENGTH {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Certificate chains are limited to {} certificates", MAX_CHAIN_LENGTH),
+            });
+        }
+        self.chain.push(certificate);
+        Ok(self)
+    }
+
+    /// Attach a timestamp token for the signature
+    pub fn with_timestamp(mut self, token: TimestampToken) -> Result<Self, CryptoError> {
+        token.verify(&self.signature)?;
+        self.timestamp = Some(token);
+        Ok(self)
+    }
+
+    /// The bundled signature
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Public key of the signer
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Certificates from the signer towards the root
+    pub fn chain(&self) -> &[KeyCertificate] {
+        &self.chain
+    }
+
+    /// Attached timestamp token
+    pub fn timestamp(&self) -> Option<&TimestampToken> {
+        self.timestamp.as_ref()
+    }
+
+    /// Verify the bundle over `message`, trusting only keys in `pinned`
+    ///
+    /// The signer must be pinned or certified through the chain by a pinned
+    /// key. A timestamp token, if present, must come from a pinned authority.
+    pub fn verify(&self, message: &[u8], pinned: &[Fingerprint]) -> Result<BundleVerification, CryptoError> {
+        self.verify_with(message, |fingerprint, _| pinned.contains(fingerprint))
+    }
+
+    /// Verify the bundle over `message`, asking `trusted` whether a key may be relied on
+    ///
+    /// `trusted` is asked about the signer for [`KeyUsage::Sign`], then about
+    /// each certificate issuer for [`KeyUsage::Certify`] until it agrees, and
+    /// about the timestamp authority for [`KeyUsage::Timestamp`].
+    pub fn verify_with(&self, message: &[u8], trusted: impl Fn(&Fingerprint, KeyUsage) -> bool) -> Result<BundleVerification, CryptoError> {
+        if self.version != BUNDLE_VERSION {
+            return Err(CryptoError::MalformedEncoding {
+                details: format!("Unsupported signature bundle version {}", self.version),
+            });
+        }
+        if self.chain.len() > MAX_CHAIN_LENGTH {
+            return Err(CryptoError::MalformedEncoding {
+                details: format!("Certificate chain has {} certificates, at most {} allowed", self.chain.len(), MAX_CHAIN_LENGTH),
+            });
+        }
+
+        self.public_key.verify(message, &self.signature)?;
+        let signer = self.public_key.fingerprint();
+
+        let mut subject = &self.public_key;
+        let mut anchor = signer;
+        let mut chain_length = 0;
+        let mut usage = KeyUsage::Sign;
+        while !trusted(&anchor, usage) {
+            let certificate = self.chain.get(chain_length).ok_or_else(|| CryptoError::SignatureVerification {
+                details: format!("Signer {} is not trusted and its certificate chain reaches no trusted key", signer),
+            })?;
+            anchor = certificate.verify(subject)?;
+            subject = &certificate.issuer;
+            chain_length += 1;
+            usage = KeyUsage::Certify;
+        }
+
+        let timestamp = match &self.timestamp {
+            Some(token) => {
+                let authority = token.verify(&self.signature)?;
+                if !trusted(&authority, KeyUsage::Timestamp) {
+                    return Err(CryptoError::SignatureVerification {
+                        details: format!("Timestamp authority {} is not trusted", authority),
+                    });
+                }
+                Some(token.time())
+            }
+            None => None,
+        };
+
+        Ok(BundleVerification { signer, anchor, chain_length, timestamp })
+    }
+}
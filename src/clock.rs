@@ -0,0 +1,119 @@
+//! # Clocks
+//!
+//! Key generation and signing stamp their output with Unix timestamps in
+//! seconds. The time source is a [`Clock`] so callers can pin it in tests or
+//! bind it to a witness service.
+//!
+//! The functions without a clock argument use [`default_clock`]: the system
+//! clock wrapped in a [`MonotonicClock`], or the time of the local witness
+//! log with the `witness-integration` feature.
+
+use core::sync::atomic::{AtomicU64, Ordering};
+
+/// Source of Unix timestamps in seconds
+pub trait Clock: Send + Sync {
+    /// Current time in seconds since the Unix epoch
+    fn now(&self) -> u64;
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> u64 {
+        (**self).now()
+    }
+}
+
+/// Operating system wall clock
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        use std::time::{SystemTime, UNIX_EPOCH};
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        use web_time::{SystemTime, UNIX_EPOCH};
+
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs()
+    }
+}
+
+/// Clock that only moves when told to, for tests and reproducible output
+#[derive(Debug, Default)]
+pub struct FixedClock {
+    time: AtomicU64,
+}
+
+impl FixedClock {
+    /// Clock reading `time`
+    pub const fn new(time: u64) -> Self {
+        Self { time: AtomicU64::new(time) }
+    }
+
+    /// Set the current reading
+    pub fn set(&self, time: u64) {
+        self.time.store(time, Ordering::SeqCst);
+    }
+
+    /// Move the reading forward by `seconds`
+    pub fn advance(&self, seconds: u64) {
+        self.time.fetch_add(seconds, Ordering::SeqCst);
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> u64 {
+        self.time.load(Ordering::SeqCst)
+    }
+}
+
+/// Wrapper that never reports an earlier time than it already has
+///
+/// Protects timestamps from wall-clock steps backwards (NTP corrections,
+/// manual changes) within one process.
+#[derive(Debug)]
+pub struct MonotonicClock<C> {
+    inner: C,
+    last: AtomicU64,
+}
+
+impl<C> MonotonicClock<C> {
+    /// Wrap `inner`
+    pub const fn new(inner: C) -> Self {
+        Self { inner, last: AtomicU64::new(0) }
+    }
+}
+
+impl<C: Clock> Clock for MonotonicClock<C> {
+    fn now(&self) -> u64 {
+        let now = self.inner.now();
+        let previous = self.last.fetch_max(now, Ordering::SeqCst);
+        now.max(previous)
+    }
+}
+
+/// Time of a witness backend, for timestamps that witness commitments can vouch for
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WitnessClock<W>(pub W);
+
+impl<W: crate::witness::WitnessBackend> Clock for WitnessClock<W> {
+    fn now(&self) -> u64 {
+        self.0.current_timestamp()
+    }
+}
+
+/// Clock used by key generation and signing when none is given
+#[cfg(feature = "std")]
+pub fn default_clock() -> &'static dyn Clock {
+    #[cfg(feature = "witness-integration")]
+    static CLOCK: MonotonicClock<WitnessClock<crate::witness::LocalWitness>> =
+        MonotonicClock::new(WitnessClock(crate::witness::LocalWitness));
+    #[cfg(not(feature = "witness-integration"))]
+    static CLOCK: MonotonicClock<SystemClock> = MonotonicClock::new(SystemClock);
+
+    &CLOCK
+}
@@ -0,0 +1,113 @@
+//! # Constant-Time Comparisons
+//!
+//! Anything derived from secret or attacker-guessable material — key
+//! fingerprints, token and passphrase hashes, MACs, key bytes — is compared
+//! through [`subtle`], so the time taken does not depend on where the first
+//! differing byte is. Lengths are treated as public.
+//!
+//! `tests/timing.rs` checks these paths with a statistical timing test built
+//! on the hidden `timing` module of the `fuzzing` feature.
+
+use crate::CryptoError;
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+use subtle::{Choice, ConstantTimeEq};
+
+/// Length of a [`Fingerprint`]
+pub const FINGERPRINT_BYTES: usize = 32;
+
+/// Whether `a` and `b` are equal, in time independent of their contents
+pub fn eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// Whether `needle` equals any entry of `set`, checking every entry
+pub fn contains<T: AsRef<[u8]>>(set: &[T], needle: &[u8]) -> bool {
+    set.iter()
+        .fold(Choice::from(0), |found, entry| found | entry.as_ref().ct_eq(needle))
+        .into()
+}
+
+/// SHA-256 fingerprint identifying a public key
+///
+/// Equality is constant-time. Displays, parses and serializes as lowercase hex.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Fingerprint([u8; FINGERPRINT_BYTES]);
+
+impl Fingerprint {
+    /// Fingerprint of an encoded public key
+    pub fn of(public_key_bytes: &[u8]) -> Self {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"AF_KEY_FINGERPRINT_V1");
+        hasher.update(public_key_bytes);
+        Self(hasher.finalize().into())
+    }
+
+    /// Wrap raw fingerprint bytes
+    pub const fn from_bytes(bytes: [u8; FINGERPRINT_BYTES]) -> Self {
+        Self(bytes)
+    }
+
+    /// Raw fingerprint bytes
+    pub fn as_bytes(&self) -> &[u8; FINGERPRINT_BYTES] {
+        &self.0
+    }
+}
+
+impl ConstantTimeEq for Fingerprint {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl PartialEq for Fingerprint {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl Eq for Fingerprint {}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{:02x}", byte))
+    }
+}
+
+impl fmt::Debug for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fingerprint({})", self)
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = [0u8; FINGERPRINT_BYTES];
+        hex::decode_to_slice(s, &mut bytes).map_err(|e| CryptoError::MalformedEncoding {
+            details: format!("Fingerprint must be {} hex characters: {}", FINGERPRINT_BYTES * 2, e),
+        })?;
+        Ok(Self(bytes))
+    }
+}
+
+impl TryFrom<String> for Fingerprint {
+    type Error = CryptoError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+impl From<Fingerprint> for String {
+    fn from(fingerprint: Fingerprint) -> Self {
+        fingerprint.to_string()
+    }
+}
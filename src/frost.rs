@@ -0,0 +1,575 @@
+//! # Threshold Ed25519 Signing (FROST)
+//!
+//! FROST(Ed25519, SHA-512) from RFC 9591 lets any `min_signers` of
+//! `max_signers` guardians produce the Ed25519 half of a hybrid signature
+//! without the Ed25519 secret key ever existing in one place. The result is
+//! an ordinary Ed25519 signature under the group public key.
+//!
+//! Guardian keys come from the distributed key generation in [`dkg`].
+//! Signing then takes two rounds:
+//!
+//! 1. each participating guardian calls [`commit`], publishes its
+//!    [`SigningCommitments`] and keeps its [`SigningNonces`] secret;
+//! 2. the coordinator collects the commitments and the message into a
+//!    [`SigningPackage`], and each guardian answers with [`sign`], which
+//!    consumes its nonces so they cannot be used twice.
+//!
+//! [`aggregate`] checks every [`SignatureShare`] against the guardian's
+//! verifying share, naming any guardian whose share is invalid, and combines
+//! them.
+//!
+//! The Dilithium3 half is not threshold-shared. A [`DilithiumCustodian`]
+//! holds it under its own [`DilithiumPolicy`] and co-signs only messages the
+//! guardians have already signed, completing a regular hybrid [`Signature`].
+
+pub mod dkg;
+
+use crate::clock::Clock;
+use crate::hybrid::HybridPublicKey;
+use crate::secret::SecretBytes;
+use crate::{AlgorithmVersion, CryptoError, PublicKey, Signature};
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+use crystals_dilithium::dilithium3::{Keypair as DilithiumKeypair, SecretKey as DilithiumSecretKey, SECRETKEYBYTES};
+use curve25519_dalek::edwards::CompressedEdwardsY;
+use curve25519_dalek::traits::IsIdentity;
+use curve25519_dalek::{EdwardsPoint, Scalar};
+use ed25519_dalek::VerifyingKey;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha512};
+use zeroize::{Zeroize, Zeroizing};
+
+/// RFC 9591 context string of FROST(Ed25519, SHA-512)
+const CONTEXT: &[u8] = b"FROST-ED25519-SHA512-v1";
+
+/// Guardian identifier, a non-zero field element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u16", into = "u16")]
+pub struct Identifier(u16);
+
+impl Identifier {
+    /// Identifier `value`, which must not be zero
+    pub fn new(value: u16) -> Result<Self, CryptoError> {
+        if value == 0 {
+            return Err(CryptoError::InvalidOperation {
+                details: "FROST identifiers start at 1".into(),
+            });
+        }
+        Ok(Self(value))
+    }
+
+    /// Numeric value
+    pub fn get(self) -> u16 {
+        self.0
+    }
+
+    fn scalar(self) -> Scalar {
+        Scalar::from(self.0)
+    }
+}
+
+impl TryFrom<u16> for Identifier {
+    type Error = CryptoError;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Identifier> for u16 {
+    fn from(identifier: Identifier) -> Self {
+        identifier.0
+    }
+}
+
+impl fmt::Display for Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Group element, serialized as compressed hex and validated on input
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Element(EdwardsPoint);
+
+impl Element {
+    fn to_bytes(self) -> [u8; 32] {
+        self.0.compress().to_bytes()
+    }
+
+    /// Canonical, non-identity point of the prime-order subgroup
+    fn from_bytes(bytes: &[u8; 32]) -> Result<Self, CryptoError> {
+        let point = CompressedEdwardsY(*bytes).decompress()
+            .filter(|point| point.compress().as_bytes() == bytes)
+            .filter(|point| !point.is_identity() && point.is_torsion_free())
+            .ok_or_else(|| CryptoError::MalformedEncoding {
+                details: "Invalid FROST group element".into(),
+            })?;
+        Ok(Self(point))
+    }
+}
+
+impl Serialize for Element {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(self.to_bytes()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Element {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(&text, &mut bytes).map_err(serde::de::Error::custom)?;
+        Element::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Debug for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Element({})", hex::encode(self.to_bytes()))
+    }
+}
+
+/// Field element, serialized as canonical hex; `Debug` is redacted
+#[derive(Clone, Copy, PartialEq, Eq, Zeroize)]
+struct FieldScalar(Scalar);
+
+impl FieldScalar {
+    fn from_bytes(bytes: [u8; 32]) -> Result<Self, CryptoError> {
+        Option::<Scalar>::from(Scalar::from_canonical_bytes(bytes))
+            .map(Self)
+            .ok_or_else(|| CryptoError::MalformedEncoding {
+                details: "Non-canonical FROST scalar".into(),
+            })
+    }
+}
+
+impl Serialize for FieldScalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&Zeroizing::new(hex::encode(self.0.as_bytes())))
+    }
+}
+
+impl<'de> Deserialize<'de> for FieldScalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = Zeroizing::new(String::deserialize(deserializer)?);
+        let mut bytes = Zeroizing::new([0u8; 32]);
+        hex::decode_to_slice(text.as_str(), bytes.as_mut_slice()).map_err(serde::de::Error::custom)?;
+        FieldScalar::from_bytes(*bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Debug for FieldScalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FieldScalar([REDACTED])")
+    }
+}
+
+/// A guardian's long-term signing share and the group public key
+#[derive(Clone, Serialize, Deserialize)]
+pub struct KeyPackage {
+    identifier: Identifier,
+    signing_share: Zeroizing<FieldScalar>,
+    group_public: Element,
+    min_signers: u16,
+}
+
+impl KeyPackage {
+    /// This guardian's identifier
+    pub fn identifier(&self) -> Identifier {
+        self.identifier
+    }
+
+    /// Number of guardians needed to sign
+    pub fn min_signers(&self) -> u16 {
+        self.min_signers
+    }
+
+    /// Group Ed25519 public key
+    pub fn group_public_key(&self) -> VerifyingKey {
+        VerifyingKey::from(self.group_public.0)
+    }
+
+    /// Public key of this guardian's signing share
+    pub fn verifying_share(&self) -> [u8; 32] {
+        EdwardsPoint::mul_base(&self.signing_share.0).compress().to_bytes()
+    }
+}
+
+impl fmt::Debug for KeyPackage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyPackage")
+            .field("identifier", &self.identifier)
+            .field("group_public", &self.group_public)
+            .field("min_signers", &self.min_signers)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Group public key and every guardian's verifying share
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicKeyPackage {
+    verifying_shares: BTreeMap<Identifier, Element>,
+    group_public: Element,
+    min_signers: u16,
+}
+
+impl PublicKeyPackage {
+    /// Group Ed25519 public key
+    pub fn group_public_key(&self) -> VerifyingKey {
+        VerifyingKey::from(self.group_public.0)
+    }
+
+    /// Number of guardians needed to sign
+    pub fn min_signers(&self) -> u16 {
+        self.min_signers
+    }
+
+    /// Identifiers of all guardians
+    pub fn identifiers(&self) -> impl Iterator<Item = Identifier> + '_ {
+        self.verifying_shares.keys().copied()
+    }
+
+    /// Verifying share of guardian `identifier`
+    pub fn verifying_share(&self, identifier: Identifier) -> Option<[u8; 32]> {
+        self.verifying_shares.get(&identifier).map(|share| share.to_bytes())
+    }
+}
+
+/// Round-one nonces of one guardian, consumed by [`sign`]
+pub struct SigningNonces {
+    hiding: Zeroizing<Scalar>,
+    binding: Zeroizing<Scalar>,
+    commitments: SigningCommitments,
+}
+
+impl SigningNonces {
+    /// Commitments published for these nonces
+    pub fn commitments(&self) -> &SigningCommitments {
+        &self.commitments
+    }
+}
+
+impl fmt::Debug for SigningNonces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SigningNonces").field("commitments", &self.commitments).finish_non_exhaustive()
+    }
+}
+
+/// Round-one commitments to a guardian's hiding and binding nonces
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningCommitments {
+    hiding: Element,
+    binding: Element,
+}
+
+impl SigningCommitments {
+    /// Compressed hiding and binding nonce commitments
+    pub fn to_bytes(&self) -> ([u8; 32], [u8; 32]) {
+        (self.hiding.to_bytes(), self.binding.to_bytes())
+    }
+}
+
+/// Message and the commitments of the guardians taking part in signing it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningPackage {
+    commitments: BTreeMap<Identifier, SigningCommitments>,
+    #[serde(with = "serde_bytes")]
+    message: Vec<u8>,
+}
+
+impl SigningPackage {
+    /// Package for `message` signed by the guardians in `commitments`
+    pub fn new(commitments: BTreeMap<Identifier, SigningCommitments>, message: &[u8]) -> Self {
+        Self { commitments, message: message.to_vec() }
+    }
+
+    /// Message to sign
+    pub fn message(&self) -> &[u8] {
+        &self.message
+    }
+
+    /// Identifiers of the participating guardians
+    pub fn signers(&self) -> impl Iterator<Item = Identifier> + '_ {
+        self.commitments.keys().copied()
+    }
+}
+
+/// Round-two response of one guardian
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignatureShare(FieldScalar);
+
+impl SignatureShare {
+    /// Canonical scalar encoding
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0 .0.to_bytes()
+    }
+}
+
+/// Round one: draw fresh nonces for signing with `key`
+pub fn commit<R: CryptoRngCore + ?Sized>(key: &KeyPackage, rng: &mut R) -> (SigningNonces, SigningCommitments) {
+    let hiding = nonce_generate(&key.signing_share.0, rng);
+    let binding = nonce_generate(&key.signing_share.0, rng);
+    let commitments = SigningCommitments {
+        hiding: Element(EdwardsPoint::mul_base(&hiding)),
+        binding: Element(EdwardsPoint::mul_base(&binding)),
+    };
+    (SigningNonces { hiding, binding, commitments }, commitments)
+}
+
+/// Round two: this guardian's share of the signature over `package`
+pub fn sign(package: &SigningPackage, nonces: SigningNonces, key: &KeyPackage) -> Result<SignatureShare, CryptoError> {
+    if package.commitments.get(&key.identifier) != Some(&nonces.commitments) {
+        return Err(CryptoError::InvalidOperation {
+            details: format!("Signing package does not hold the commitments of guardian {}", key.identifier),
+        });
+    }
+    check_signer_count(package, key.min_signers)?;
+
+    let binding_factors = binding_factors(&key.group_public, package);
+    let group_commitment = group_commitment(package, &binding_factors);
+    let lambda = lagrange_at_zero(key.identifier, package.commitments.keys());
+    let challenge = challenge(&group_commitment, &key.group_public, &package.message);
+
+    let share = *nonces.hiding
+        + *nonces.binding * binding_factors[&key.identifier]
+        + lambda * key.signing_share.0 * challenge;
+    Ok(SignatureShare(FieldScalar(share)))
+}
+
+/// Check every share and combine them into an Ed25519 signature under the group key
+pub fn aggregate(
+    package: &SigningPackage,
+    shares: &BTreeMap<Identifier, SignatureShare>,
+    public: &PublicKeyPackage,
+) -> Result<ed25519_dalek::Signature, CryptoError> {
+    check_signer_count(package, public.min_signers)?;
+    if !shares.keys().eq(package.commitments.keys()) {
+        return Err(CryptoError::InvalidOperation {
+            details: "Signature shares do not match the guardians in the signing package".into(),
+        });
+    }
+
+    let binding_factors = binding_factors(&public.group_public, package);
+    let group_commitment = group_commitment(package, &binding_factors);
+    let challenge = challenge(&group_commitment, &public.group_public, &package.message);
+
+    let mut z = Scalar::ZERO;
+    for (identifier, share) in shares {
+        let verifying_share = public.verifying_shares.get(identifier).ok_or_else(|| CryptoError::InvalidOperation {
+            details: format!("Guardian {} is not part of this group", identifier),
+        })?;
+        let commitments = &package.commitments[identifier];
+        let lambda = lagrange_at_zero(*identifier, package.commitments.keys());
+        let expected = commitments.hiding.0 + commitments.binding.0 * binding_factors[identifier]
+            + verifying_share.0 * (challenge * lambda);
+        if EdwardsPoint::mul_base(&share.0 .0) != expected {
+            return Err(CryptoError::SignatureVerification {
+                details: format!("Signature share of guardian {} is invalid", identifier),
+            });
+        }
+        z += share.0 .0;
+    }
+
+    let mut bytes = [0u8; 64];
+    bytes[..32].copy_from_slice(group_commitment.compress().as_bytes());
+    bytes[32..].copy_from_slice(z.as_bytes());
+    let signature = ed25519_dalek::Signature::from_bytes(&bytes);
+    public.group_public_key().verify_strict(&package.message, &signature).map_err(|_| CryptoError::SignatureVerification {
+        details: "Aggregated FROST signature does not verify".into(),
+    })?;
+    Ok(signature)
+}
+
+fn check_signer_count(package: &SigningPackage, min_signers: u16) -> Result<(), CryptoError> {
+    if package.commitments.len() < usize::from(min_signers) {
+        return Err(CryptoError::InsufficientGuardianApproval);
+    }
+    Ok(())
+}
+
+/// RFC 9591 `nonce_generate`: H3(32 random bytes || secret)
+fn nonce_generate<R: CryptoRngCore + ?Sized>(secret: &Scalar, rng: &mut R) -> Zeroizing<Scalar> {
+    let mut random = Zeroizing::new([0u8; 32]);
+    rng.fill_bytes(random.as_mut_slice());
+    Zeroizing::new(hash_to_scalar(&[CONTEXT, b"nonce", random.as_slice(), secret.as_bytes()]))
+}
+
+/// RFC 9591 `compute_binding_factors`
+fn binding_factors(group_public: &Element, package: &SigningPackage) -> BTreeMap<Identifier, Scalar> {
+    let mut encoded_commitments = Vec::with_capacity(package.commitments.len() * 96);
+    for (identifier, commitments) in &package.commitments {
+        encoded_commitments.extend_from_slice(identifier.scalar().as_bytes());
+        encoded_commitments.extend_from_slice(&commitments.hiding.to_bytes());
+        encoded_commitments.extend_from_slice(&commitments.binding.to_bytes());
+    }
+    let message_hash = Sha512::new().chain_update(CONTEXT).chain_update(b"msg").chain_update(&package.message).finalize();
+    let commitment_hash = Sha512::new().chain_update(CONTEXT).chain_update(b"com").chain_update(&encoded_commitments).finalize();
+    let group_public = group_public.to_bytes();
+
+    package.commitments.keys()
+        .map(|identifier| {
+            let rho = hash_to_scalar(&[
+                CONTEXT, b"rho", &group_public, &message_hash, &commitment_hash, identifier.scalar().as_bytes(),
+            ]);
+            (*identifier, rho)
+        })
+        .collect()
+}
+
+/// RFC 9591 `compute_group_commitment`
+fn group_commitment(package: &SigningPackage, binding_factors: &BTreeMap<Identifier, Scalar>) -> EdwardsPoint {
+    package.commitments.iter()
+        .map(|(identifier, commitments)| commitments.hiding.0 + commitments.binding.0 * binding_factors[identifier])
+        .sum()
+}
+
+/// RFC 9591 `compute_challenge`, identical to the Ed25519 challenge
+fn challenge(group_commitment: &EdwardsPoint, group_public: &Element, message: &[u8]) -> Scalar {
+    hash_to_scalar(&[group_commitment.compress().as_bytes(), &group_public.to_bytes(), message])
+}
+
+/// Lagrange coefficient of `identifier` at zero over `signers`
+fn lagrange_at_zero<'a>(identifier: Identifier, signers: impl Iterator<Item = &'a Identifier>) -> Scalar {
+    let (mut numerator, mut denominator) = (Scalar::ONE, Scalar::ONE);
+    for other in signers.filter(|other| **other != identifier) {
+        numerator *= other.scalar();
+        denominator *= other.scalar() - identifier.scalar();
+    }
+    numerator * denominator.invert()
+}
+
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let digest = parts.iter().fold(Sha512::new(), |hash, part| hash.chain_update(part)).finalize();
+    Scalar::from_bytes_mod_order_wide(&digest.into())
+}
+
+fn random_scalar<R: CryptoRngCore + ?Sized>(rng: &mut R) -> Zeroizing<Scalar> {
+    let mut wide = Zeroizing::new([0u8; 64]);
+    rng.fill_bytes(wide.as_mut_slice());
+    Zeroizing::new(Scalar::from_bytes_mod_order_wide(&wide))
+}
+
+/// Limits on the Dilithium3 half of a threshold hybrid key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DilithiumPolicy {
+    /// Maximum number of co-signatures
+    pub max_usage: u64,
+    /// Unix timestamp after which the custodian no longer co-signs
+    pub not_after: Option<u64>,
+}
+
+impl Default for DilithiumPolicy {
+    fn default() -> Self {
+        Self { max_usage: 1_000_000, not_after: None }
+    }
+}
+
+/// Holder of the Dilithium3 half of a hybrid key whose Ed25519 half is FROST-shared
+///
+/// Co-signs a message only once the guardians' Ed25519 signature over it
+/// verifies under the group key, and only within its [`DilithiumPolicy`].
+pub struct DilithiumCustodian {
+    public: HybridPublicKey,
+    dilithium: SecretBytes<SECRETKEYBYTES>,
+    policy: DilithiumPolicy,
+    usage_count: AtomicU64,
+    created_at: u64,
+    key_id: String,
+}
+
+impl DilithiumCustodian {
+    /// Take ownership of `dilithium` for the guardian group `group`, scrubbing the original
+    pub fn new(mut dilithium: DilithiumKeypair, group: &PublicKeyPackage, policy: DilithiumPolicy, clock: &(impl Clock + ?Sized)) -> Self {
+        let public = HybridPublicKey::from_components(dilithium.public.to_bytes(), group.group_public_key());
+        let fingerprint = crate::Fingerprint::of(&public.to_bytes());
+        let custodian = Self {
+            public,
+            dilithium: SecretBytes::from_bytes(&dilithium.secret.bytes),
+            policy,
+            usage_count: AtomicU64::new(0),
+            created_at: clock.now(),
+            key_id: format!("frost-hybrid-{}", hex::encode(&fingerprint.as_bytes()[..8])),
+        };
+        dilithium.secret.bytes.zeroize();
+        custodian
+    }
+
+    /// Hybrid public key: the Dilithium3 key and the guardians' group key
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey {
+            algorithm: AlgorithmVersion::MandatoryHybrid,
+            bytes: self.public.to_bytes(),
+            created_at: self.created_at,
+            operation_id: self.created_at,
+        }
+    }
+
+    /// Identifier recorded as the signer of co-signed messages
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Policy this custodian enforces
+    pub fn policy(&self) -> DilithiumPolicy {
+        self.policy
+    }
+
+    /// Number of co-signatures made so far
+    pub fn usage(&self) -> u64 {
+        self.usage_count.load(Ordering::SeqCst)
+    }
+
+    /// Complete the hybrid signature over `message` the guardians signed as `guardian_signature`
+    pub fn cosign(&self, message: &[u8], guardian_signature: &ed25519_dalek::Signature, clock: &(impl Clock + ?Sized)) -> Result<Signature, CryptoError> {
+        if message.is_empty() {
+            return Err(CryptoError::InvalidOperation {
+                details: "Cannot sign empty message".into(),
+            });
+        }
+        self.public.verify_ed25519(message, guardian_signature).map_err(|_| CryptoError::InsufficientGuardianApproval)?;
+
+        let now = clock.now();
+        if let Some(not_after) = self.policy.not_after {
+            if now > not_after {
+                return Err(CryptoError::InvalidOperation {
+                    details: format!("Dilithium policy of {} expired at {}", self.key_id, not_after),
+                });
+            }
+        }
+        let count = self.usage_count.fetch_add(1, Ordering::SeqCst);
+        if count >= self.policy.max_usage {
+            self.usage_count.fetch_sub(1, Ordering::SeqCst);
+            return Err(CryptoError::KeyUsageExceeded { count, max: self.policy.max_usage });
+        }
+
+        let dilithium = self.dilithium.expose(|secret| {
+            let mut key = DilithiumSecretKey { bytes: *secret };
+            let signature = key.sign(message);
+            key.bytes.zeroize();
+            signature
+        });
+        let signature = crate::hybrid::HybridSignature::from_components(dilithium, *guardian_signature);
+        Ok(Signature {
+            algorithm: AlgorithmVersion::MandatoryHybrid,
+            bytes: signature.to_bytes(),
+            created_at: now,
+            operation_id: now,
+            signer_key_id: self.key_id.clone(),
+        })
+    }
+}
+
+impl fmt::Debug for DilithiumCustodian {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DilithiumCustodian")
+            .field("key_id", &self.key_id)
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
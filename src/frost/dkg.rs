@@ -0,0 +1,235 @@
+//! Distributed key generation for FROST guardians
+//!
+//! Pedersen DKG with Schnorr proofs of knowledge (the FROST paper, figure 1),
+//! using the encodings of the Zcash Foundation implementation so its test
+//! vectors apply:
+//!
+//! 1. [`part1`]: each guardian draws a random polynomial of degree
+//!    `min_signers - 1` and broadcasts a [`Round1Package`] committing to its
+//!    coefficients, with a proof of knowledge of the constant term;
+//! 2. [`part2`]: after checking every other guardian's proof, each guardian
+//!    sends every other guardian a [`Round2Package`] with its polynomial
+//!    evaluated at the recipient's identifier, over a confidential channel;
+//! 3. [`part3`]: each guardian checks the shares it received against the
+//!    commitments and sums them into its [`KeyPackage`].
+//!
+//! The group secret key is the sum of all constant terms and is never
+//! computed by anyone.
+
+use super::{random_scalar, Element, FieldScalar, Identifier, KeyPackage, PublicKeyPackage, CONTEXT};
+use crate::CryptoError;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::vec::Vec;
+use core::fmt;
+use curve25519_dalek::{EdwardsPoint, Scalar};
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+/// State a guardian keeps between [`part1`] and [`part2`]
+pub struct Round1Secret {
+    identifier: Identifier,
+    coefficients: Vec<Zeroizing<Scalar>>,
+    commitment: Vec<Element>,
+    max_signers: u16,
+    min_signers: u16,
+}
+
+/// Broadcast of round one: coefficient commitments and proof of knowledge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Round1Package {
+    commitment: Vec<Element>,
+    proof_r: Element,
+    proof_z: FieldScalar,
+}
+
+/// State a guardian keeps between [`part2`] and [`part3`]
+pub struct Round2Secret {
+    identifier: Identifier,
+    commitment: Vec<Element>,
+    own_share: Zeroizing<Scalar>,
+    max_signers: u16,
+    min_signers: u16,
+}
+
+/// Secret share sent from one guardian to another in round two
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Round2Package {
+    signing_share: Zeroizing<FieldScalar>,
+}
+
+impl fmt::Debug for Round1Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Round1Secret").field("identifier", &self.identifier).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for Round2Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Round2Secret").field("identifier", &self.identifier).finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for Round2Package {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Round2Package([REDACTED])")
+    }
+}
+
+/// Round one for guardian `identifier` of a `min_signers`-of-`max_signers` group
+pub fn part1<R: CryptoRngCore + ?Sized>(
+    identifier: Identifier,
+    max_signers: u16,
+    min_signers: u16,
+    rng: &mut R,
+) -> Result<(Round1Secret, Round1Package), CryptoError> {
+    if min_signers < 2 || min_signers > max_signers {
+        return Err(CryptoError::InvalidOperation {
+            details: format!("FROST needs 2 <= min_signers <= max_signers, got {}-of-{}", min_signers, max_signers),
+        });
+    }
+
+    let coefficients: Vec<Zeroizing<Scalar>> = (0..min_signers).map(|_| random_scalar(rng)).collect();
+    let commitment: Vec<Element> = coefficients.iter()
+        .map(|coefficient| Element(EdwardsPoint::mul_base(coefficient)))
+        .collect();
+
+    let nonce = random_scalar(rng);
+    let proof_r = Element(EdwardsPoint::mul_base(&nonce));
+    let proof_z = *nonce + *coefficients[0] * proof_challenge(identifier, &commitment[0], &proof_r);
+
+    let package = Round1Package { commitment: commitment.clone(), proof_r, proof_z: FieldScalar(proof_z) };
+    let secret = Round1Secret { identifier, coefficients, commitment, max_signers, min_signers };
+    Ok((secret, package))
+}
+
+/// Round two: check the other guardians' round-one packages and deal them shares
+///
+/// `round1` holds the packages of all other guardians. The returned packages
+/// go to the guardian they are keyed by, and to nobody else.
+pub fn part2(
+    secret: Round1Secret,
+    round1: &BTreeMap<Identifier, Round1Package>,
+) -> Result<(Round2Secret, BTreeMap<Identifier, Round2Package>), CryptoError> {
+    check_participants(secret.identifier, secret.max_signers, round1.keys())?;
+    for (identifier, package) in round1 {
+        if package.commitment.len() != usize::from(secret.min_signers) {
+            return Err(CryptoError::InvalidOperation {
+                details: format!(
+                    "Guardian {} committed to {} coefficients, expected {}",
+                    identifier, package.commitment.len(), secret.min_signers
+                ),
+            });
+        }
+        let challenge = proof_challenge(*identifier, &package.commitment[0], &package.proof_r);
+        if EdwardsPoint::mul_base(&package.proof_z.0) != package.proof_r.0 + package.commitment[0].0 * challenge {
+            return Err(CryptoError::SignatureVerification {
+                details: format!("Proof of knowledge of guardian {} is invalid", identifier),
+            });
+        }
+    }
+
+    let shares = round1.keys()
+        .map(|identifier| {
+            let share = evaluate(&secret.coefficients, *identifier);
+            (*identifier, Round2Package { signing_share: Zeroizing::new(FieldScalar(*share)) })
+        })
+        .collect();
+    let own_share = evaluate(&secret.coefficients, secret.identifier);
+    let round2 = Round2Secret {
+        identifier: secret.identifier,
+        commitment: secret.commitment,
+        own_share,
+        max_signers: secret.max_signers,
+        min_signers: secret.min_signers,
+    };
+    Ok((round2, shares))
+}
+
+/// Round three: check the received shares and compute this guardian's keys
+///
+/// `round1` is the same map given to [`part2`]; `round2` holds the packages
+/// the other guardians sent to this one.
+pub fn part3(
+    secret: &Round2Secret,
+    round1: &BTreeMap<Identifier, Round1Package>,
+    round2: &BTreeMap<Identifier, Round2Package>,
+) -> Result<(KeyPackage, PublicKeyPackage), CryptoError> {
+    check_participants(secret.identifier, secret.max_signers, round1.keys())?;
+    if !round2.keys().eq(round1.keys()) {
+        return Err(CryptoError::InvalidOperation {
+            details: "Round-two packages do not come from the round-one guardians".into(),
+        });
+    }
+
+    let mut signing_share = Zeroizing::new(*secret.own_share);
+    for (identifier, package) in round2 {
+        let share = package.signing_share.0;
+        if EdwardsPoint::mul_base(&share) != evaluate_commitment(&round1[identifier].commitment, secret.identifier) {
+            return Err(CryptoError::SignatureVerification {
+                details: format!("Share from guardian {} does not match its commitment", identifier),
+            });
+        }
+        *signing_share += share;
+    }
+
+    // Commitment to the sum of all polynomials
+    let mut group_commitment = secret.commitment.clone();
+    for package in round1.values() {
+        for (sum, term) in group_commitment.iter_mut().zip(&package.commitment) {
+            sum.0 += term.0;
+        }
+    }
+    let verifying_shares: BTreeMap<Identifier, Element> = round1.keys()
+        .chain(core::iter::once(&secret.identifier))
+        .map(|identifier| (*identifier, Element(evaluate_commitment(&group_commitment, *identifier))))
+        .collect();
+    let group_public = group_commitment[0];
+    if verifying_shares.values().any(|share| share.0 == EdwardsPoint::default()) || group_public.0 == EdwardsPoint::default() {
+        return Err(CryptoError::InvalidKey {
+            details: "Distributed key generation produced an identity key".into(),
+        });
+    }
+
+    let key = KeyPackage {
+        identifier: secret.identifier,
+        signing_share: Zeroizing::new(FieldScalar(*signing_share)),
+        group_public,
+        min_signers: secret.min_signers,
+    };
+    let public = PublicKeyPackage { verifying_shares, group_public, min_signers: secret.min_signers };
+    Ok((key, public))
+}
+
+fn check_participants<'a>(own: Identifier, max_signers: u16, others: impl ExactSizeIterator<Item = &'a Identifier>) -> Result<(), CryptoError> {
+    let count = others.len();
+    let mut others = others;
+    if count + 1 != usize::from(max_signers) || others.any(|identifier| *identifier == own) {
+        return Err(CryptoError::InvalidOperation {
+            details: format!("Expected packages from the {} other guardians", max_signers - 1),
+        });
+    }
+    Ok(())
+}
+
+/// Challenge of the proof of knowledge: HDKG(identifier || commitment || R)
+fn proof_challenge(identifier: Identifier, commitment: &Element, proof_r: &Element) -> Scalar {
+    super::hash_to_scalar(&[CONTEXT, b"dkg", identifier.scalar().as_bytes(), &commitment.to_bytes(), &proof_r.to_bytes()])
+}
+
+/// Secret polynomial at `identifier`
+fn evaluate(coefficients: &[Zeroizing<Scalar>], identifier: Identifier) -> Zeroizing<Scalar> {
+    let x = identifier.scalar();
+    let mut value = Zeroizing::new(Scalar::ZERO);
+    for coefficient in coefficients.iter().rev() {
+        *value = *value * x + **coefficient;
+    }
+    value
+}
+
+/// Committed polynomial at `identifier`, in the exponent
+fn evaluate_commitment(commitment: &[Element], identifier: Identifier) -> EdwardsPoint {
+    let x = identifier.scalar();
+    commitment.iter().rev().fold(EdwardsPoint::default(), |value, term| value * x + term.0)
+}
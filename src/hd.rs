@@ -0,0 +1,431 @@
+//! # Hierarchical Derivation
+//!
+//! One 32-byte master seed yields an independent hybrid keypair for every
+//! derivation path, so backing up the seed backs up every key below it.
+//! Paths are written `m/af/0/service-name`: `m` is the master node and each
+//! segment is 1 to [`MAX_SEGMENT_LEN`] ASCII letters, digits, `-`, `_` or
+//! `.`. A trailing `'` marks a hardened segment, as in BIP32.
+//!
+//! The master node comes from the [`crate::kdf`] key schedule (subkey label
+//! `hd/master`, 96 bytes: Ed25519 seed, chain code, Dilithium chain). Each
+//! child is derived with HMAC-SHA-512 keyed by its parent's chain values:
+//!
+//! - **Dilithium3** is always derived from the private Dilithium chain, so
+//!   no public information reveals anything about it.
+//! - **Ed25519** follows BIP32-Ed25519: the child scalar is the parent scalar
+//!   plus a tweak. For non-hardened segments the tweak depends only on the
+//!   parent public key and chain code, so an [`ExtendedPublicKey`] can
+//!   compute child Ed25519 public keys without any secret. Hardened
+//!   segments mix in the parent scalar instead.
+//!
+//! As with BIP32, an extended public key together with any non-hardened
+//! child's private key reveals the parent private key; share extended
+//! public keys only below a hardened segment.
+
+use crate::clock::Clock;
+use crate::kdf::KeySchedule;
+use crate::secret::{HybridSecretKey, EXPANDED_SECRET_KEY_BYTES};
+use crate::{AlgorithmVersion, CryptoError, KeyMaterialInner, PrivateKey, PublicKey};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use core::sync::atomic::AtomicU64;
+use crystals_dilithium::dilithium3::Keypair as DilithiumKeypair;
+use curve25519_dalek::{EdwardsPoint, Scalar};
+use ed25519_dalek::hazmat::ExpandedSecretKey;
+use ed25519_dalek::VerifyingKey;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+/// Longest path segment in bytes
+pub const MAX_SEGMENT_LEN: usize = 64;
+
+/// Key schedule label of the master node
+const MASTER_LABEL: &str = "hd/master";
+
+// Domain bytes of the child derivation HMAC inputs
+const HARDENED_TWEAK: u8 = 0x00;
+const HARDENED_CHAIN: u8 = 0x01;
+const NORMAL_TWEAK: u8 = 0x02;
+const NORMAL_CHAIN: u8 = 0x03;
+const NONCE_PREFIX: u8 = 0x04;
+const DILITHIUM_CHAIN: u8 = 0x05;
+const DILITHIUM_SEED: u8 = 0x06;
+
+/// One segment of a [`DerivationPath`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Segment {
+    name: String,
+    hardened: bool,
+}
+
+impl Segment {
+    /// Segment called `name`, hardened or not
+    pub fn new(name: &str, hardened: bool) -> Result<Self, CryptoError> {
+        let valid = (1..=MAX_SEGMENT_LEN).contains(&name.len())
+            && name.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'));
+        if !valid {
+            return Err(CryptoError::InvalidOperation {
+                details: format!(
+                    "Invalid path segment '{}': use 1 to {} letters, digits, '-', '_' or '.'",
+                    name, MAX_SEGMENT_LEN
+                ),
+            });
+        }
+        Ok(Self { name: name.to_string(), hardened })
+    }
+
+    /// Segment name without the hardened marker
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether this segment is hardened
+    pub fn is_hardened(&self) -> bool {
+        self.hardened
+    }
+
+    /// `hardened flag || name length || name`, the HMAC input identifying this segment
+    fn encode(&self) -> Vec<u8> {
+        let mut encoded = Vec::with_capacity(2 + self.name.len());
+        encoded.push(u8::from(self.hardened));
+        encoded.push(self.name.len() as u8);
+        encoded.extend_from_slice(self.name.as_bytes());
+        encoded
+    }
+}
+
+impl fmt::Display for Segment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.name, if self.hardened { "'" } else { "" })
+    }
+}
+
+impl FromStr for Segment {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.strip_suffix('\'') {
+            Some(name) => Self::new(name, true),
+            None => Self::new(s, false),
+        }
+    }
+}
+
+/// Path from the master node, such as `m/af/0/service-name`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct DerivationPath {
+    segments: Vec<Segment>,
+}
+
+impl DerivationPath {
+    /// The master node `m`
+    pub fn master() -> Self {
+        Self::default()
+    }
+
+    /// Segments below the master node
+    pub fn segments(&self) -> &[Segment] {
+        &self.segments
+    }
+
+    /// This path extended by `segment`
+    pub fn child(&self, segment: Segment) -> Self {
+        let mut segments = self.segments.clone();
+        segments.push(segment);
+        Self { segments }
+    }
+
+    /// This path extended by every segment of `relative`
+    pub fn join(&self, relative: &DerivationPath) -> Self {
+        Self { segments: [self.segments.as_slice(), relative.segments.as_slice()].concat() }
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("m")?;
+        self.segments.iter().try_for_each(|segment| write!(f, "/{}", segment))
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('/');
+        if parts.next() != Some("m") {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Derivation path '{}' must start with 'm'", s),
+            });
+        }
+        let segments = parts.map(Segment::from_str).collect::<Result<_, _>>()?;
+        Ok(Self { segments })
+    }
+}
+
+/// Private node of the derivation tree
+pub struct ExtendedPrivateKey {
+    path: DerivationPath,
+    /// Ed25519 scalar followed by nonce prefix
+    ed25519: Zeroizing<[u8; EXPANDED_SECRET_KEY_BYTES]>,
+    ed25519_public: VerifyingKey,
+    chain_code: [u8; 32],
+    dilithium_chain: Zeroizing<[u8; 32]>,
+}
+
+impl ExtendedPrivateKey {
+    /// Master node for `seed` under the current key schedule
+    pub fn master(seed: &[u8; 32]) -> Self {
+        Self::from_schedule(&KeySchedule::new(seed)).expect("the current key schedule supports labeled subkeys")
+    }
+
+    /// Master node of `schedule`; the legacy schedule has none
+    pub fn from_schedule(schedule: &KeySchedule) -> Result<Self, CryptoError> {
+        let mut master = Zeroizing::new([0u8; 96]);
+        schedule.derive_labeled(MASTER_LABEL, master.as_mut_slice())?;
+
+        let mut ed25519_seed = Zeroizing::new([0u8; 32]);
+        ed25519_seed.copy_from_slice(&master[..32]);
+        let expanded = ExpandedSecretKey::from(&*ed25519_seed);
+        let mut ed25519 = Zeroizing::new([0u8; EXPANDED_SECRET_KEY_BYTES]);
+        ed25519[..32].copy_from_slice(expanded.scalar.as_bytes());
+        ed25519[32..].copy_from_slice(&expanded.hash_prefix);
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&master[32..64]);
+        let mut dilithium_chain = Zeroizing::new([0u8; 32]);
+        dilithium_chain.copy_from_slice(&master[64..]);
+
+        Ok(Self {
+            path: DerivationPath::master(),
+            ed25519_public: VerifyingKey::from(EdwardsPoint::mul_base(&expanded.scalar)),
+            ed25519,
+            chain_code,
+            dilithium_chain,
+        })
+    }
+
+    /// Path of this node
+    pub fn path(&self) -> &DerivationPath {
+        &self.path
+    }
+
+    /// Child node for `segment`
+    pub fn derive_child(&self, segment: &Segment) -> Result<Self, CryptoError> {
+        let data = segment.encode();
+        let (tweak, chain_code) = if segment.is_hardened() {
+            let secret = &self.ed25519[..];
+            (
+                hmac(&self.chain_code, &[&[HARDENED_TWEAK], secret, &data]),
+                hmac(&self.chain_code, &[&[HARDENED_CHAIN], secret, &data]),
+            )
+        } else {
+            let public = self.ed25519_public.as_bytes();
+            (
+                hmac(&self.chain_code, &[&[NORMAL_TWEAK], public, &data]),
+                hmac(&self.chain_code, &[&[NORMAL_CHAIN], public, &data]),
+            )
+        };
+        let tweak = Scalar::from_bytes_mod_order_wide(&tweak);
+
+        let mut scalar_bytes = Zeroizing::new([0u8; 32]);
+        scalar_bytes.copy_from_slice(&self.ed25519[..32]);
+        let parent = Option::<Scalar>::from(Scalar::from_canonical_bytes(*scalar_bytes)).ok_or_else(|| CryptoError::InvalidKey {
+            details: "Invalid extended Ed25519 scalar".into(),
+        })?;
+        let scalar = parent + tweak;
+        if scalar == Scalar::ZERO {
+            return Err(underivable(&self.path.child(segment.clone())));
+        }
+
+        let prefix = hmac(&self.ed25519[32..], &[&[NONCE_PREFIX], &data]);
+        let mut ed25519 = Zeroizing::new([0u8; EXPANDED_SECRET_KEY_BYTES]);
+        ed25519[..32].copy_from_slice(scalar.as_bytes());
+        ed25519[32..].copy_from_slice(&prefix[..32]);
+
+        let dilithium = hmac(&self.dilithium_chain[..], &[&[DILITHIUM_CHAIN], &data]);
+        let mut dilithium_chain = Zeroizing::new([0u8; 32]);
+        dilithium_chain.copy_from_slice(&dilithium[..32]);
+
+        Ok(Self {
+            path: self.path.child(segment.clone()),
+            ed25519,
+            ed25519_public: VerifyingKey::from(EdwardsPoint::mul_base(&scalar)),
+            chain_code: chain_code[32..].try_into().expect("HMAC-SHA-512 output is 64 bytes"),
+            dilithium_chain,
+        })
+    }
+
+    /// Descendant at `relative` below this node
+    pub fn derive_path(&self, relative: &DerivationPath) -> Result<Self, CryptoError> {
+        relative.segments().iter().try_fold(self.clone_node(), |node, segment| node.derive_child(segment))
+    }
+
+    /// Extended public key of this node, for public derivation of Ed25519 children
+    pub fn extended_public_key(&self) -> ExtendedPublicKey {
+        ExtendedPublicKey {
+            path: self.path.clone(),
+            ed25519: self.ed25519_public,
+            chain_code: self.chain_code,
+        }
+    }
+
+    /// Hybrid keypair of this node, timestamped by `clock`
+    pub fn to_keypair(&self, clock: &(impl Clock + ?Sized)) -> Result<(PrivateKey, PublicKey), CryptoError> {
+        let seed = hmac(&self.dilithium_chain[..], &[&[DILITHIUM_SEED]]);
+        let dilithium = DilithiumKeypair::generate(Some(&seed[..32]));
+        let secret_key = HybridSecretKey::from_expanded(dilithium, &self.ed25519)?;
+
+        let public_bytes = secret_key.public().to_bytes();
+        let fingerprint = crate::Fingerprint::of(&public_bytes);
+        let id = fingerprint.as_bytes();
+        let operation_id = u64::from_be_bytes([id[0], id[1], id[2], id[3], id[4], id[5], id[6], id[7]]);
+        let now = clock.now();
+
+        let private_key = PrivateKey {
+            algorithm: AlgorithmVersion::MandatoryHybrid,
+            inner: KeyMaterialInner::MandatoryHybrid(secret_key),
+            created_at: now,
+            operation_id,
+            usage_count: AtomicU64::new(0),
+            key_id: format!("hd-hybrid-{}", hex::encode(&id[..8])),
+        };
+        let public_key = PublicKey {
+            algorithm: AlgorithmVersion::MandatoryHybrid,
+            bytes: public_bytes,
+            created_at: now,
+            operation_id,
+        };
+        Ok((private_key, public_key))
+    }
+
+    fn clone_node(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            ed25519: self.ed25519.clone(),
+            ed25519_public: self.ed25519_public,
+            chain_code: self.chain_code,
+            dilithium_chain: self.dilithium_chain.clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ExtendedPrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtendedPrivateKey").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
+/// Public node of the derivation tree: Ed25519 public key and chain code
+///
+/// Derives the Ed25519 public keys of non-hardened descendants. The
+/// Dilithium3 half of a descendant always needs the private tree.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "ExtendedPublicKeyJson", into = "ExtendedPublicKeyJson")]
+pub struct ExtendedPublicKey {
+    path: DerivationPath,
+    ed25519: VerifyingKey,
+    chain_code: [u8; 32],
+}
+
+impl ExtendedPublicKey {
+    /// Path of this node
+    pub fn path(&self) -> &DerivationPath {
+        &self.path
+    }
+
+    /// Ed25519 public key of this node
+    pub fn ed25519_public(&self) -> &VerifyingKey {
+        &self.ed25519
+    }
+
+    /// Child node for a non-hardened `segment`
+    pub fn derive_child(&self, segment: &Segment) -> Result<Self, CryptoError> {
+        if segment.is_hardened() {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Hardened segment '{}' needs the extended private key", segment),
+            });
+        }
+        let data = segment.encode();
+        let public = self.ed25519.as_bytes();
+        let tweak = Scalar::from_bytes_mod_order_wide(&hmac(&self.chain_code, &[&[NORMAL_TWEAK], public, &data]));
+        let chain_code = hmac(&self.chain_code, &[&[NORMAL_CHAIN], public, &data]);
+
+        let point = self.ed25519.to_edwards() + EdwardsPoint::mul_base(&tweak);
+        let path = self.path.child(segment.clone());
+        if point == EdwardsPoint::default() {
+            return Err(underivable(&path));
+        }
+        Ok(Self {
+            path,
+            ed25519: VerifyingKey::from(point),
+            chain_code: chain_code[32..].try_into().expect("HMAC-SHA-512 output is 64 bytes"),
+        })
+    }
+
+    /// Descendant at `relative` below this node; every segment must be non-hardened
+    pub fn derive_path(&self, relative: &DerivationPath) -> Result<Self, CryptoError> {
+        relative.segments().iter().try_fold(self.clone(), |node, segment| node.derive_child(segment))
+    }
+}
+
+/// Serialized form of [`ExtendedPublicKey`]
+#[derive(Serialize, Deserialize)]
+struct ExtendedPublicKeyJson {
+    path: String,
+    ed25519: String,
+    chain_code: String,
+}
+
+impl From<ExtendedPublicKey> for ExtendedPublicKeyJson {
+    fn from(key: ExtendedPublicKey) -> Self {
+        Self {
+            path: key.path.to_string(),
+            ed25519: hex::encode(key.ed25519.as_bytes()),
+            chain_code: hex::encode(key.chain_code),
+        }
+    }
+}
+
+impl TryFrom<ExtendedPublicKeyJson> for ExtendedPublicKey {
+    type Error = CryptoError;
+
+    fn try_from(json: ExtendedPublicKeyJson) -> Result<Self, Self::Error> {
+        let malformed = |what: &str| CryptoError::MalformedEncoding {
+            details: format!("Extended public key {} must be 32 hex-encoded bytes", what),
+        };
+        let mut ed25519 = [0u8; 32];
+        hex::decode_to_slice(&json.ed25519, &mut ed25519).map_err(|_| malformed("ed25519"))?;
+        let mut chain_code = [0u8; 32];
+        hex::decode_to_slice(&json.chain_code, &mut chain_code).map_err(|_| malformed("chain_code"))?;
+        Ok(Self {
+            path: json.path.parse()?,
+            ed25519: VerifyingKey::from_bytes(&ed25519).map_err(|_| CryptoError::InvalidKey {
+                details: "Invalid extended Ed25519 public key".into(),
+            })?,
+            chain_code,
+        })
+    }
+}
+
+/// Hybrid keypair at `path` below the master node of `seed`
+pub fn derive_keypair(seed: &[u8; 32], path: &DerivationPath, clock: &(impl Clock + ?Sized)) -> Result<(PrivateKey, PublicKey), CryptoError> {
+    ExtendedPrivateKey::master(seed).derive_path(path)?.to_keypair(clock)
+}
+
+fn hmac(key: &[u8], parts: &[&[u8]]) -> Zeroizing<[u8; 64]> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    parts.iter().for_each(|part| mac.update(part));
+    Zeroizing::new(mac.finalize().into_bytes().into())
+}
+
+fn underivable(path: &DerivationPath) -> CryptoError {
+    CryptoError::InvalidKey {
+        details: format!("No valid key at {}; use another path", path),
+    }
+}
@@ -0,0 +1,161 @@
+//! # Validated Hybrid Encodings
+//!
+//! [`PublicKey`](crate::PublicKey) and [`Signature`](crate::Signature) carry
+//! raw bytes straight from JSON or bincode. [`HybridPublicKey`] and
+//! [`HybridSignature`] can only be built from exactly-sized encodings of the
+//! Dilithium3 half followed by the Ed25519 half, so trailing or missing bytes
+//! are rejected as [`CryptoError::MalformedEncoding`] before any
+//! cryptographic check runs.
+//!
+//! Both types expose their halves as typed components and can be assembled
+//! from them, so each half can be inspected or verified on its own without
+//! slicing at `PUBLICKEYBYTES` or `SIGNBYTES` by hand.
+
+use crate::CryptoError;
+use alloc::format;
+use alloc::vec::Vec;
+use crystals_dilithium::dilithium3::{PublicKey as DilithiumPublicKey, PUBLICKEYBYTES, SIGNBYTES};
+use ed25519_dalek::{Verifier, VerifyingKey};
+use subtle::ConstantTimeEq;
+
+/// Length of an encoded hybrid public key
+pub const HYBRID_PUBLIC_KEY_BYTES: usize = PUBLICKEYBYTES + ed25519_dalek::PUBLIC_KEY_LENGTH;
+
+/// Length of an encoded hybrid signature
+pub const HYBRID_SIGNATURE_BYTES: usize = SIGNBYTES + ed25519_dalek::SIGNATURE_LENGTH;
+
+/// Hybrid public key with an exact-length encoding and a valid Ed25519 point
+///
+/// Equality is constant-time.
+#[derive(Debug, Clone)]
+pub struct HybridPublicKey {
+    dilithium: [u8; PUBLICKEYBYTES],
+    ed25519: VerifyingKey,
+}
+
+/// Hybrid signature with an exact-length encoding
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HybridSignature {
+    dilithium: [u8; SIGNBYTES],
+    ed25519: ed25519_dalek::Signature,
+}
+
+impl HybridPublicKey {
+    /// Parse `Dilithium3 public key || Ed25519 public key`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let (dilithium, ed25519) = split_exact::<PUBLICKEYBYTES, { ed25519_dalek::PUBLIC_KEY_LENGTH }>(bytes, "public key")?;
+        let ed25519 = VerifyingKey::from_bytes(&ed25519).map_err(|_| CryptoError::InvalidKey {
+            details: "Invalid mandatory hybrid Ed25519 public key".into(),
+        })?;
+        Ok(Self { dilithium, ed25519 })
+    }
+
+    /// Assemble from the Dilithium3 and Ed25519 public keys
+    pub fn from_components(dilithium: [u8; PUBLICKEYBYTES], ed25519: VerifyingKey) -> Self {
+        Self { dilithium, ed25519 }
+    }
+
+    /// Dilithium3 public key
+    pub fn dilithium_public(&self) -> &[u8; PUBLICKEYBYTES] {
+        &self.dilithium
+    }
+
+    /// Ed25519 public key
+    pub fn ed25519_public(&self) -> &VerifyingKey {
+        &self.ed25519
+    }
+
+    /// Encode as `Dilithium3 public key || Ed25519 public key`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.dilithium.as_slice(), self.ed25519.as_bytes()].concat()
+    }
+
+    /// Verify both halves of `signature` over `message`
+    pub fn verify(&self, message: &[u8], signature: &HybridSignature) -> Result<(), CryptoError> {
+        self.verify_dilithium(message, &signature.dilithium)?;
+        self.verify_ed25519(message, &signature.ed25519)
+    }
+
+    /// Verify only the Dilithium3 half; never sufficient on its own
+    pub fn verify_dilithium(&self, message: &[u8], signature: &[u8; SIGNBYTES]) -> Result<(), CryptoError> {
+        let dilithium = DilithiumPublicKey { bytes: self.dilithium };
+        if dilithium.verify(message, signature) {
+            Ok(())
+        } else {
+            Err(CryptoError::SignatureVerification {
+                details: "Mandatory hybrid Dilithium verification failed".into(),
+            })
+        }
+    }
+
+    /// Verify only the Ed25519 half; never sufficient on its own
+    pub fn verify_ed25519(&self, message: &[u8], signature: &ed25519_dalek::Signature) -> Result<(), CryptoError> {
+        self.ed25519.verify(message, signature).map_err(|_| CryptoError::SignatureVerification {
+            details: "Mandatory hybrid Ed25519 verification failed".into(),
+        })
+    }
+}
+
+impl PartialEq for HybridPublicKey {
+    fn eq(&self, other: &Self) -> bool {
+        let dilithium = self.dilithium.ct_eq(&other.dilithium);
+        let ed25519 = self.ed25519.as_bytes().ct_eq(other.ed25519.as_bytes());
+        (dilithium & ed25519).into()
+    }
+}
+
+impl Eq for HybridPublicKey {}
+
+impl HybridSignature {
+    /// Parse `Dilithium3 signature || Ed25519 signature`
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let (dilithium, ed25519) = split_exact::<SIGNBYTES, { ed25519_dalek::SIGNATURE_LENGTH }>(bytes, "signature")?;
+        Ok(Self { dilithium, ed25519: ed25519_dalek::Signature::from_bytes(&ed25519) })
+    }
+
+    /// Assemble from the Dilithium3 and Ed25519 signatures
+    pub fn from_components(dilithium: [u8; SIGNBYTES], ed25519: ed25519_dalek::Signature) -> Self {
+        Self { dilithium, ed25519 }
+    }
+
+    /// Dilithium3 signature
+    pub fn dilithium_signature(&self) -> &[u8; SIGNBYTES] {
+        &self.dilithium
+    }
+
+    /// Ed25519 signature
+    pub fn ed25519_signature(&self) -> &ed25519_dalek::Signature {
+        &self.ed25519
+    }
+
+    /// Encode as `Dilithium3 signature || Ed25519 signature`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        [self.dilithium.as_slice(), &self.ed25519.to_bytes()].concat()
+    }
+}
+
+impl TryFrom<&[u8]> for HybridPublicKey {
+    type Error = CryptoError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for HybridSignature {
+    type Error = CryptoError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        Self::from_bytes(bytes)
+    }
+}
+
+/// Split `bytes` into exactly `A` then `B` bytes
+fn split_exact<const A: usize, const B: usize>(bytes: &[u8], what: &str) -> Result<([u8; A], [u8; B]), CryptoError> {
+    let malformed = || CryptoError::MalformedEncoding {
+        details: format!("Mandatory hybrid {} must be {} bytes, got {}", what, A + B, bytes.len()),
+    };
+    let (first, second) = bytes.split_first_chunk::<A>().ok_or_else(malformed)?;
+    let second = second.try_into().map_err(|_| malformed())?;
+    Ok((*first, second))
+}
@@ -0,0 +1,224 @@
+//! # Key Schedule
+//!
+//! Every key derived from a 32-byte master seed comes from a [`KeySchedule`].
+//! The current schedule, [`KeyScheduleVersion::V1`], is HKDF-SHA-512
+//! (RFC 5869):
+//!
+//! ```text
+//! PRK     = HKDF-Extract(salt = "af-pqc/key-schedule", IKM = master seed)
+//! subkey  = HKDF-Expand(PRK, info = "af-pqc/key-schedule/v1/" || label, L)
+//! ```
+//!
+//! | Subkey                   | Label            | Bytes | Used for                          |
+//! |--------------------------|------------------|-------|-----------------------------------|
+//! | [`Subkey::DilithiumSeed`] | `dilithium-seed` | 32    | Dilithium3 key generation seed    |
+//! | [`Subkey::Ed25519Seed`]   | `ed25519-seed`   | 32    | Ed25519 secret key                |
+//! | [`Subkey::StorageKey`]    | `storage-key`    | 32    | AES-256-GCM key of the keypair cache |
+//! | [`Subkey::CacheName`]     | `cache-name`     | 32    | Keypair cache file name (first 16 bytes) |
+//! | [`Subkey::KeyId`]         | `key-id`         | 32    | Key id and operation id (first 8 bytes) |
+//!
+//! Further subkeys use [`KeySchedule::derive_labeled`], whose labels live
+//! under `ext/` so they can never collide with the table above.
+//!
+//! [`KeySchedule::with_passphrase`] first stretches the seed with an optional
+//! passphrase, as BIP39 does for mnemonics:
+//! `seed' = PBKDF2-HMAC-SHA-512(seed, "af-pqc/passphrase/" || NFKD(passphrase), 2048)[..32]`.
+//! An empty passphrase leaves the seed unchanged.
+//!
+//! [`KeyScheduleVersion::Legacy`] reproduces the derivations used before the
+//! key schedule existed (single SHA-256 hashes over fixed labels, and the
+//! Ed25519 key drawn from a ChaCha20 stream keyed by the raw seed) so keys
+//! and caches created by earlier versions can still be regenerated. A new
+//! version only ever adds a variant; existing versions never change.
+
+use crate::CryptoError;
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use core::str::FromStr;
+use hkdf::Hkdf;
+use sha2::Sha512;
+use zeroize::Zeroizing;
+
+/// HKDF-Extract salt shared by all versions
+const SALT: &[u8] = b"af-pqc/key-schedule";
+
+/// PBKDF2 salt prefix for passphrase-protected seeds
+const PASSPHRASE_SALT: &str = "af-pqc/passphrase/";
+
+/// PBKDF2 iterations for passphrase-protected seeds, as in BIP39
+pub const PASSPHRASE_ROUNDS: u32 = 2048;
+
+/// Key schedule version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyScheduleVersion {
+    /// Pre-HKDF derivations, kept to regenerate existing keys
+    Legacy,
+    /// HKDF-SHA-512 with labeled subkeys
+    #[default]
+    V1,
+}
+
+impl KeyScheduleVersion {
+    /// Name used on the command line and in documentation
+    pub const fn name(self) -> &'static str {
+        match self {
+            KeyScheduleVersion::Legacy => "legacy",
+            KeyScheduleVersion::V1 => "v1",
+        }
+    }
+}
+
+impl fmt::Display for KeyScheduleVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for KeyScheduleVersion {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "legacy" => Ok(KeyScheduleVersion::Legacy),
+            "v1" => Ok(KeyScheduleVersion::V1),
+            other => Err(CryptoError::InvalidOperation {
+                details: format!("Unknown key schedule '{}' (expected v1 or legacy)", other),
+            }),
+        }
+    }
+}
+
+/// Subkeys every key schedule version provides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subkey {
+    /// Seed for Dilithium3 key generation
+    DilithiumSeed,
+    /// Ed25519 secret key
+    Ed25519Seed,
+    /// AES-256-GCM key for the encrypted keypair cache
+    StorageKey,
+    /// Name of the keypair cache file
+    CacheName,
+    /// Public key identifier
+    KeyId,
+}
+
+impl Subkey {
+    /// HKDF label of this subkey
+    pub const fn label(self) -> &'static str {
+        match self {
+            Subkey::DilithiumSeed => "dilithium-seed",
+            Subkey::Ed25519Seed => "ed25519-seed",
+            Subkey::StorageKey => "storage-key",
+            Subkey::CacheName => "cache-name",
+            Subkey::KeyId => "key-id",
+        }
+    }
+}
+
+/// Derives all keys belonging to one master seed
+pub struct KeySchedule {
+    seed: Zeroizing<[u8; 32]>,
+    version: KeyScheduleVersion,
+}
+
+impl KeySchedule {
+    /// Current key schedule for `master_seed`
+    pub fn new(master_seed: &[u8; 32]) -> Self {
+        Self::with_version(master_seed, KeyScheduleVersion::default())
+    }
+
+    /// Key schedule of a specific version for `master_seed`
+    pub fn with_version(master_seed: &[u8; 32], version: KeyScheduleVersion) -> Self {
+        Self { seed: Zeroizing::new(*master_seed), version }
+    }
+
+    /// Key schedule of `version` for `master_seed` protected by `passphrase`
+    ///
+    /// The passphrase is NFKD-normalized. An empty passphrase gives the same
+    /// keys as [`KeySchedule::with_version`]; any other passphrase gives
+    /// unrelated keys, and a mistyped one is not detected.
+    pub fn with_passphrase(master_seed: &[u8; 32], passphrase: &str, version: KeyScheduleVersion) -> Self {
+        use unicode_normalization::UnicodeNormalization;
+
+        if passphrase.is_empty() {
+            return Self::with_version(master_seed, version);
+        }
+        let salt: Zeroizing<String> = Zeroizing::new(PASSPHRASE_SALT.chars().chain(passphrase.nfkd()).collect());
+        let mut stretched = Zeroizing::new([0u8; 64]);
+        pbkdf2::pbkdf2_hmac::<Sha512>(master_seed, salt.as_bytes(), PASSPHRASE_ROUNDS, stretched.as_mut_slice());
+        let mut seed = Zeroizing::new([0u8; 32]);
+        seed.copy_from_slice(&stretched[..32]);
+        Self { seed, version }
+    }
+
+    /// Version of this schedule
+    pub fn version(&self) -> KeyScheduleVersion {
+        self.version
+    }
+
+    /// Derive one of the built-in subkeys
+    pub fn derive(&self, subkey: Subkey) -> Zeroizing<[u8; 32]> {
+        let mut output = Zeroizing::new([0u8; 32]);
+        match self.version {
+            KeyScheduleVersion::Legacy => self.derive_legacy(subkey, &mut output),
+            KeyScheduleVersion::V1 => self.expand(&["af-pqc/key-schedule/v1/", subkey.label()], output.as_mut_slice())
+                .expect("32 bytes is within the HKDF-SHA-512 output limit"),
+        }
+        output
+    }
+
+    /// Derive `output.len()` bytes for a caller-defined `label`
+    ///
+    /// Only available from [`KeyScheduleVersion::V1`] on.
+    pub fn derive_labeled(&self, label: &str, output: &mut [u8]) -> Result<(), CryptoError> {
+        match self.version {
+            KeyScheduleVersion::Legacy => Err(CryptoError::InvalidOperation {
+                details: "The legacy key schedule has no labeled subkeys".into(),
+            }),
+            KeyScheduleVersion::V1 => self.expand(&["af-pqc/key-schedule/v1/ext/", label], output),
+        }
+    }
+
+    fn expand(&self, info: &[&str], output: &mut [u8]) -> Result<(), CryptoError> {
+        let info: String = info.concat();
+        Hkdf::<Sha512>::new(Some(SALT), self.seed.as_slice())
+            .expand(info.as_bytes(), output)
+            .map_err(|_| CryptoError::InvalidOperation {
+                details: format!("Cannot derive {} bytes with HKDF-SHA-512", output.len()),
+            })
+    }
+
+    fn derive_legacy(&self, subkey: Subkey, output: &mut [u8; 32]) {
+        use rand_chacha::ChaCha20Rng;
+        use rand_core::{RngCore, SeedableRng};
+        use sha2::{Digest, Sha256};
+
+        let seed = self.seed.as_slice();
+        match subkey {
+            Subkey::DilithiumSeed => output.copy_from_slice(&Sha256::new()
+                .chain_update(b"AF_DILITHIUM_SEED_V1")
+                .chain_update(seed)
+                .finalize()),
+            Subkey::Ed25519Seed => ChaCha20Rng::from_seed(*self.seed).fill_bytes(output),
+            Subkey::StorageKey => output.copy_from_slice(&Sha256::new()
+                .chain_update(b"AF_ENCRYPTION_KEY_V1")
+                .chain_update(seed)
+                .chain_update(b"DILITHIUM_STORAGE")
+                .finalize()),
+            Subkey::CacheName => output.copy_from_slice(&Sha256::new()
+                .chain_update(b"AF_FILENAME_V1")
+                .chain_update(seed)
+                .finalize()),
+            // Earlier versions exposed the first 8 seed bytes as the key id
+            Subkey::KeyId => output.copy_from_slice(seed),
+        }
+    }
+}
+
+impl fmt::Debug for KeySchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeySchedule").field("version", &self.version).finish_non_exhaustive()
+    }
+}
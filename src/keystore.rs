@@ -0,0 +1,580 @@
+//! # Local Keystore
+//!
+//! Directory-backed storage for named hybrid keypairs.
+//!
+//! The keystore lives in `$AF_HOME` (default: `<config dir>/af-pqc`) and uses
+//! the following layout:
+//!
+//! ```text
+//! $AF_HOME/
+//!   config.json              default key selection
+//!   keys/<name>/
+//!     key.json               key metadata and usage policy
+//!     public.json            hybrid public key
+//!     secret.enc             passphrase-encrypted hybrid private material
+//!     usage.json             persistent signature counter
+//!     .lock                  held while the counter is updated
+//! ```
+//!
+//! Directories are created with mode `0700` and files with mode `0600`; a
+//! keystore whose permissions have been loosened is refused. Every file is
+//! replaced atomically (write to a temporary file, fsync, rename). Usage
+//! updates hold an exclusive lock on `.lock`, so signers in separate
+//! processes never lose increments.
+
+use crate::clock;
+use crate::{AlgorithmVersion, CryptoError, HybridSecretKey, KeyMaterialInner, PrivateKey, PublicKey, Signature};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicU64;
+use zeroize::Zeroizing;
+
+/// Environment variable overriding the keystore location
+pub const AF_HOME_ENV: &str = "AF_HOME";
+
+/// Default per-key signature limit, matching the in-memory limit of `PrivateKey::sign`
+pub const DEFAULT_MAX_USAGE: u64 = 1_000_000;
+
+const CONFIG_FILE: &str = "config.json";
+const KEYS_DIR: &str = "keys";
+const RECORD_FILE: &str = "key.json";
+const PUBLIC_FILE: &str = "public.json";
+const SECRET_FILE: &str = "secret.enc";
+const USAGE_FILE: &str = "usage.json";
+const LOCK_FILE: &str = ".lock";
+
+const SECRET_MAGIC: &[u8; 4] = b"AFKS";
+const SECRET_VERSION: u8 = 1;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = SECRET_MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+
+/// Usage restrictions attached to a stored key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyPolicy {
+    /// Maximum number of signatures before the key must be rotated
+    pub max_usage: u64,
+    /// Unix timestamp after which the key may no longer sign
+    pub not_after: Option<u64>,
+}
+
+impl Default for KeyPolicy {
+    fn default() -> Self {
+        Self {
+            max_usage: DEFAULT_MAX_USAGE,
+            not_after: None,
+        }
+    }
+}
+
+/// Metadata describing a stored key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRecord {
+    pub name: String,
+    pub key_id: String,
+    pub algorithm: AlgorithmVersion,
+    pub created_at: u64,
+    pub operation_id: u64,
+    pub policy: KeyPolicy,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct UsageCounter {
+    count: u64,
+    last_used_at: Option<u64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KeystoreConfig {
+    default_key: Option<String>,
+}
+
+/// Directory of named, passphrase-protected hybrid keys
+#[derive(Debug, Clone)]
+pub struct Keystore {
+    root: PathBuf,
+}
+
+impl Keystore {
+    /// Resolve the keystore root from `$AF_HOME` or the user config directory
+    pub fn default_root() -> Result<PathBuf, CryptoError> {
+        if let Some(home) = std::env::var_os(AF_HOME_ENV) {
+            return Ok(PathBuf::from(home));
+        }
+        dirs::config_dir()
+            .map(|dir| dir.join("af-pqc"))
+            .ok_or_else(|| CryptoError::Keystore {
+                details: format!("Cannot determine config directory - set {}", AF_HOME_ENV),
+            })
+    }
+
+    /// Open the keystore at the default location
+    pub fn open_default() -> Result<Self, CryptoError> {
+        Self::open(Self::default_root()?)
+    }
+
+    /// Open (creating if necessary) the keystore rooted at `root`
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, CryptoError> {
+        let keystore = Self { root: root.into() };
+        create_private_dir(&keystore.root)?;
+        create_private_dir(&keystore.keys_dir())?;
+        Ok(keystore)
+    }
+
+    /// Keystore root directory
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// List all stored keys, sorted by name
+    pub fn list(&self) -> Result<Vec<KeyRecord>, CryptoError> {
+        let entries = fs::read_dir(self.keys_dir()).map_err(|e| CryptoError::Keystore {
+            details: format!("Failed to read keystore: {}", e),
+        })?;
+
+        let mut records = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| CryptoError::Keystore {
+                details: format!("Failed to read keystore entry: {}", e),
+            })?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            // Skips keys still being inserted, or left over from an interrupted insert
+            if !self.contains(&name) {
+                continue;
+            }
+            records.push(self.record(&name)?);
+        }
+        records.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(records)
+    }
+
+    /// Check whether a key with this name exists
+    pub fn contains(&self, name: &str) -> bool {
+        validate_key_name(name).is_ok() && self.key_dir(name).join(RECORD_FILE).is_file()
+    }
+
+    /// Load the metadata of a stored key
+    pub fn record(&self, name: &str) -> Result<KeyRecord, CryptoError> {
+        let dir = self.existing_key_dir(name)?;
+        read_json(&dir.join(RECORD_FILE))
+    }
+
+    /// Load the public key of a stored key
+    pub fn public_key(&self, name: &str) -> Result<PublicKey, CryptoError> {
+        let dir = self.existing_key_dir(name)?;
+        read_json(&dir.join(PUBLIC_FILE))
+    }
+
+    /// Number of signatures produced by a stored key
+    pub fn usage(&self, name: &str) -> Result<u64, CryptoError> {
+        let dir = self.existing_key_dir(name)?;
+        Ok(read_json::<UsageCounter>(&dir.join(USAGE_FILE))?.count)
+    }
+
+    /// Store a private key under `name`, encrypted with `passphrase`
+    pub fn insert(
+        &self,
+        name: &str,
+        private_key: &PrivateKey,
+        policy: KeyPolicy,
+        passphrase: &[u8],
+    ) -> Result<KeyRecord, CryptoError> {
+        validate_key_name(name)?;
+        if passphrase.is_empty() {
+            return Err(CryptoError::Keystore {
+                details: "Passphrase must not be empty".to_string(),
+            });
+        }
+
+        let dir = self.key_dir(name);
+        if dir.exists() {
+            return Err(CryptoError::Keystore {
+                details: format!("Key '{}' already exists", name),
+            });
+        }
+
+        let record = KeyRecord {
+            name: name.to_string(),
+            key_id: private_key.key_id.clone(),
+            algorithm: private_key.algorithm,
+            created_at: private_key.created_at,
+            operation_id: private_key.operation_id,
+            policy,
+        };
+        let public_key = private_key.public_key()?;
+        let secret = encrypt_secret(&hybrid_secret_bytes(private_key)?, passphrase, &record.key_id)?;
+
+        create_private_dir(&dir)?;
+        let result = (|| {
+            write_atomic(&dir.join(SECRET_FILE), &secret)?;
+            write_json(&dir.join(PUBLIC_FILE), &public_key)?;
+            write_json(&dir.join(USAGE_FILE), &UsageCounter::default())?;
+            // Written last: a key only becomes visible once all its files exist
+            write_json(&dir.join(RECORD_FILE), &record)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_dir_all(&dir);
+        }
+        result?;
+
+        Ok(record)
+    }
+
+    /// Decrypt a stored private key
+    pub fn load_private_key(&self, name: &str, passphrase: &[u8]) -> Result<PrivateKey, CryptoError> {
+        let dir = self.existing_key_dir(name)?;
+        let record: KeyRecord = read_json(&dir.join(RECORD_FILE))?;
+        let usage: UsageCounter = read_json(&dir.join(USAGE_FILE))?;
+
+        let secret_path = dir.join(SECRET_FILE);
+        check_private_permissions(&secret_path)?;
+        let data = fs::read(&secret_path).map_err(|e| CryptoError::Keystore {
+            details: format!("Failed to read private key for '{}': {}", name, e),
+        })?;
+        let plaintext = decrypt_secret(&data, passphrase, &record.key_id)?;
+
+        let secret_key = HybridSecretKey::from_bytes(&plaintext).map_err(|_| CryptoError::InvalidKey {
+            details: "Invalid stored hybrid key length".to_string(),
+        })?;
+        Ok(PrivateKey {
+            algorithm: record.algorithm,
+            inner: KeyMaterialInner::MandatoryHybrid(secret_key),
+            created_at: record.created_at,
+            operation_id: record.operation_id,
+            usage_count: AtomicU64::new(usage.count),
+            key_id: record.key_id,
+        })
+    }
+
+    /// Sign a message with a stored key, enforcing its policy and persisting the usage counter
+    pub fn sign(&self, name: &str, passphrase: &[u8], message: &[u8]) -> Result<Signature, CryptoError> {
+        let private_key = self.load_private_key(name, passphrase)?;
+        self.record_usage(name)?;
+        private_key.sign(message)
+    }
+
+    /// Check the policy of a stored key and count one more signature against it
+    ///
+    /// The counter is persisted before any signature is released so that a
+    /// crash can never make the stored count lower than the real one.
+    pub fn record_usage(&self, name: &str) -> Result<u64, CryptoError> {
+        let dir = self.existing_key_dir(name)?;
+        let _lock = lock_file(&dir.join(LOCK_FILE))?;
+        let record: KeyRecord = read_json(&dir.join(RECORD_FILE))?;
+        let usage_path = dir.join(USAGE_FILE);
+        let mut usage: UsageCounter = read_json(&usage_path)?;
+
+        let now = clock::default_clock().now();
+
+        if let Some(not_after) = record.policy.not_after {
+            if now > not_after {
+                return Err(CryptoError::InvalidOperation {
+                    details: format!("Key '{}' expired at {}", name, not_after),
+                });
+            }
+        }
+        if usage.count >= record.policy.max_usage {
+            return Err(CryptoError::KeyUsageExceeded {
+                count: usage.count,
+                max: record.policy.max_usage,
+            });
+        }
+
+        usage.count += 1;
+        usage.last_used_at = Some(now);
+        write_json(&usage_path, &usage)?;
+        Ok(usage.count)
+    }
+
+    /// Permanently remove a stored key
+    pub fn delete(&self, name: &str) -> Result<(), CryptoError> {
+        let dir = self.existing_key_dir(name)?;
+        fs::remove_dir_all(&dir).map_err(|e| CryptoError::Keystore {
+            details: format!("Failed to delete key '{}': {}", name, e),
+        })?;
+
+        if self.default_key()?.as_deref() == Some(name) {
+            self.write_config(&KeystoreConfig { default_key: None })?;
+        }
+        Ok(())
+    }
+
+    /// Rename a stored key
+    pub fn rename(&self, from: &str, to: &str) -> Result<(), CryptoError> {
+        let from_dir = self.existing_key_dir(from)?;
+        validate_key_name(to)?;
+        let to_dir = self.key_dir(to);
+        if to_dir.exists() {
+            return Err(CryptoError::Keystore {
+                details: format!("Key '{}' already exists", to),
+            });
+        }
+
+        let mut record: KeyRecord = read_json(&from_dir.join(RECORD_FILE))?;
+        fs::rename(&from_dir, &to_dir).map_err(|e| CryptoError::Keystore {
+            details: format!("Failed to rename key '{}': {}", from, e),
+        })?;
+
+        // The record follows the directory, which moves back if it cannot be rewritten
+        record.name = to.to_string();
+        if let Err(e) = write_json(&to_dir.join(RECORD_FILE), &record) {
+            let _ = fs::rename(&to_dir, &from_dir);
+            return Err(e);
+        }
+        sync_dir(&self.keys_dir())?;
+
+        if self.default_key()?.as_deref() == Some(from) {
+            self.write_config(&KeystoreConfig { default_key: Some(to.to_string()) })?;
+        }
+        Ok(())
+    }
+
+    /// Name of the default key, if one is set
+    pub fn default_key(&self) -> Result<Option<String>, CryptoError> {
+        let path = self.root.join(CONFIG_FILE);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(read_json::<KeystoreConfig>(&path)?.default_key)
+    }
+
+    /// Select the default key
+    pub fn set_default_key(&self, name: &str) -> Result<(), CryptoError> {
+        self.existing_key_dir(name)?;
+        self.write_config(&KeystoreConfig { default_key: Some(name.to_string()) })
+    }
+
+    fn write_config(&self, config: &KeystoreConfig) -> Result<(), CryptoError> {
+        write_json(&self.root.join(CONFIG_FILE), config)
+    }
+
+    fn keys_dir(&self) -> PathBuf {
+        self.root.join(KEYS_DIR)
+    }
+
+    fn key_dir(&self, name: &str) -> PathBuf {
+        self.keys_dir().join(name)
+    }
+
+    fn existing_key_dir(&self, name: &str) -> Result<PathBuf, CryptoError> {
+        validate_key_name(name)?;
+        let dir = self.key_dir(name);
+        if !dir.join(RECORD_FILE).is_file() {
+            return Err(CryptoError::KeyNotFound { name: name.to_string() });
+        }
+        check_private_permissions(&dir)?;
+        Ok(dir)
+    }
+}
+
+/// Validate a key name for use as a directory component
+pub fn validate_key_name(name: &str) -> Result<(), CryptoError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && !name.starts_with('.')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(CryptoError::Keystore {
+            details: format!(
+                "Invalid key name '{}': use 1-64 characters from [A-Za-z0-9._-], not starting with '.'",
+                name
+            ),
+        });
+    }
+    Ok(())
+}
+
+/// Serialize hybrid secret material: Dilithium public || Dilithium secret || Ed25519 secret
+fn hybrid_secret_bytes(private_key: &PrivateKey) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
+    match &private_key.inner {
+        KeyMaterialInner::MandatoryHybrid(secret_key) => Ok(secret_key.to_bytes()),
+        #[allow(deprecated)]
+        _ => Err(CryptoError::InvalidKey {
+            details: "Only mandatory hybrid keys can be stored".to_string(),
+        }),
+    }
+}
+
+/// Derive the file encryption key from a passphrase with Argon2id
+pub(crate) fn derive_passphrase_key(passphrase: &[u8], salt: &[u8]) -> Result<Zeroizing<[u8; 32]>, CryptoError> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    argon2::Argon2::default()
+        .hash_password_into(passphrase, salt, key.as_mut_slice())
+        .map_err(|e| CryptoError::Keystore {
+            details: format!("Passphrase key derivation failed: {}", e),
+        })?;
+    Ok(key)
+}
+
+/// Encrypt secret material: magic || version || salt || nonce || AES-256-GCM ciphertext
+///
+/// The key ID is bound as associated data so encrypted files cannot be swapped between keys.
+fn encrypt_secret(plaintext: &[u8], passphrase: &[u8], key_id: &str) -> Result<Vec<u8>, CryptoError> {
+    use aes_gcm::{Aes256Gcm, Key, KeyInit, AeadCore};
+    use aes_gcm::aead::{Aead, Payload};
+
+    let mut salt = [0u8; SALT_LEN];
+    crate::secure_random_bytes(&mut salt)?;
+    let key = derive_passphrase_key(passphrase, &salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()));
+    let nonce = Aes256Gcm::generate_nonce(&mut rand_core::OsRng);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext, aad: key_id.as_bytes() })
+        .map_err(|_| CryptoError::Keystore { details: "Encryption failed".to_string() })?;
+
+    let mut data = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+    data.extend_from_slice(SECRET_MAGIC);
+    data.push(SECRET_VERSION);
+    data.extend_from_slice(&salt);
+    data.extend_from_slice(&nonce);
+    data.extend_from_slice(&ciphertext);
+    Ok(data)
+}
+
+fn decrypt_secret(data: &[u8], passphrase: &[u8], key_id: &str) -> Result<Zeroizing<Vec<u8>>, CryptoError> {
+    use aes_gcm::{Aes256Gcm, Key, Nonce, KeyInit};
+    use aes_gcm::aead::{Aead, Payload};
+
+    if data.len() < HEADER_LEN || &data[..SECRET_MAGIC.len()] != SECRET_MAGIC {
+        return Err(CryptoError::Keystore { details: "Invalid encrypted key file".to_string() });
+    }
+    if data[SECRET_MAGIC.len()] != SECRET_VERSION {
+        return Err(CryptoError::Keystore {
+            details: format!("Unsupported encrypted key version {}", data[SECRET_MAGIC.len()]),
+        });
+    }
+
+    let salt = &data[SECRET_MAGIC.len() + 1..SECRET_MAGIC.len() + 1 + SALT_LEN];
+    let nonce_bytes: [u8; NONCE_LEN] = data[HEADER_LEN - NONCE_LEN..HEADER_LEN]
+        .try_into()
+        .map_err(|_| CryptoError::Keystore { details: "Invalid nonce size".to_string() })?;
+    let ciphertext = &data[HEADER_LEN..];
+
+    let key = derive_passphrase_key(passphrase, salt)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key.as_slice()));
+    cipher
+        .decrypt(&Nonce::from(nonce_bytes), Payload { msg: ciphertext, aad: key_id.as_bytes() })
+        .map(Zeroizing::new)
+        .map_err(|_| CryptoError::Keystore {
+            details: "Decryption failed - wrong passphrase or corrupted key file".to_string(),
+        })
+}
+
+pub(crate) fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, CryptoError> {
+    let data = fs::read(path).map_err(|e| CryptoError::Keystore {
+        details: format!("Failed to read {}: {}", path.display(), e),
+    })?;
+    serde_json::from_slice(&data).map_err(|e| CryptoError::Keystore {
+        details: format!("Failed to parse {}: {}", path.display(), e),
+    })
+}
+
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), CryptoError> {
+    let data = serde_json::to_vec_pretty(value).map_err(|e| CryptoError::Keystore {
+        details: format!("Failed to serialize {}: {}", path.display(), e),
+    })?;
+    write_atomic(path, &data)
+}
+
+/// Atomically replace `path` with `data` (mode 0600)
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), CryptoError> {
+    let dir = path.parent().ok_or_else(|| CryptoError::Keystore {
+        details: format!("Invalid keystore path {}", path.display()),
+    })?;
+    let file_name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    let tmp_path = dir.join(format!(".{}.tmp", file_name));
+    let io_error = |e: std::io::Error| CryptoError::Keystore {
+        details: format!("Failed to write {}: {}", path.display(), e),
+    };
+
+    let _ = fs::remove_file(&tmp_path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(&tmp_path).map_err(io_error)?;
+    file.write_all(data).and_then(|_| file.sync_all()).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        io_error(e)
+    })?;
+    fs::rename(&tmp_path, path).map_err(io_error)?;
+    sync_dir(dir)
+}
+
+/// Open (creating if necessary) `path` and hold an exclusive lock on it until the file is dropped
+pub(crate) fn lock_file(path: &Path) -> Result<fs::File, CryptoError> {
+    let mut options = fs::OpenOptions::new();
+    options.read(true).write(true).create(true).truncate(false);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let file = options.open(path).and_then(|file| file.lock().map(|_| file)).map_err(|e| CryptoError::Keystore {
+        details: format!("Failed to lock {}: {}", path.display(), e),
+    })?;
+    Ok(file)
+}
+
+fn sync_dir(dir: &Path) -> Result<(), CryptoError> {
+    #[cfg(unix)]
+    {
+        fs::File::open(dir).and_then(|d| d.sync_all()).map_err(|e| CryptoError::Keystore {
+            details: format!("Failed to sync {}: {}", dir.display(), e),
+        })?;
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Create a directory with mode 0700, or verify the mode of an existing one
+fn create_private_dir(dir: &Path) -> Result<(), CryptoError> {
+    if !dir.exists() {
+        let mut builder = fs::DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::DirBuilderExt;
+            builder.mode(0o700);
+        }
+        builder.create(dir).map_err(|e| CryptoError::Keystore {
+            details: format!("Failed to create {}: {}", dir.display(), e),
+        })?;
+    }
+    check_private_permissions(dir)
+}
+
+/// Refuse keystore paths readable or writable by group or others
+fn check_private_permissions(path: &Path) -> Result<(), CryptoError> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let metadata = fs::metadata(path).map_err(|e| CryptoError::Keystore {
+            details: format!("Failed to stat {}: {}", path.display(), e),
+        })?;
+        let mode = metadata.permissions().mode() & 0o777;
+        if mode & 0o077 != 0 {
+            return Err(CryptoError::Keystore {
+                details: format!(
+                    "Insecure permissions {:o} on {} (expected {:o})",
+                    mode,
+                    path.display(),
+                    if metadata.is_dir() { 0o700 } else { 0o600 }
+                ),
+            });
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
@@ -9,22 +9,73 @@
 //! - NIST-approved post-quantum algorithms (Dilithium3)
 //! - Hybrid approach for transition security
 //! - Memory-safe implementation with usage limits
-//! - Side-channel attack resistance
+//! - Side-channel attack resistance: constant-time comparisons ([`constant_time`])
+//!   checked by a statistical timing test harness (`tests/timing.rs`)
 //! - Production-grade error handling
+//!
+//! ## Cargo Features
+//! - `std` (default): the system [`clock`] behind [`PrivateKey::sign`] and
+//!   [`generate_key_from_seed`]. Without it the crate is `no_std` + `alloc`;
+//!   signing then takes an explicit clock via [`PrivateKey::sign_with_clock`]
+//! - `os-rng`: random key generation and [`secure_random_bytes`] from the OS
+//! - `fs-cache`: encrypted on-disk cache for legacy seed-derived Dilithium keypairs
+//! - `keystore`, `agent`, `server`: local key management services
+//! - `cli` (default): the `af-cli` binary and everything above
+//! - `witness-integration`: keys bound to policies committed to the local
+//!   [`witness`] log, whose time becomes the default clock; enables `keystore`
+//!
+//! With default features disabled the crate builds for `wasm32-unknown-unknown`.
+//! The `crystals-dilithium` backend itself still links `std`, so bare-metal
+//! targets additionally need a `no_std` Dilithium implementation.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
-#[cfg(feature = "witness-integration")]
-use witness_time::{EntryType, verify_witness_commitment, current_timestamp};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use crystals_dilithium::dilithium3::{
     Keypair as DilithiumKeypair,
-    PublicKey as DilithiumPublicKey,
     PUBLICKEYBYTES,
     SIGNBYTES,
 };
-use ed25519_dalek::{Signer, Verifier, SigningKey, VerifyingKey};
-use std::sync::atomic::AtomicU64;
+use ed25519_dalek::{SigningKey, VerifyingKey};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::sync::atomic::AtomicU64;
+use clock::Clock;
+use rand_core::CryptoRngCore;
+use zeroize::Zeroizing;
 
+pub use constant_time::Fingerprint;
+pub use hybrid::{HybridPublicKey, HybridSignature};
+pub use secret::HybridSecretKey;
+
+pub mod bundle;
+pub mod clock;
+pub mod constant_time;
+pub mod frost;
+pub mod hd;
+pub mod hybrid;
+pub mod kdf;
+pub mod mnemonic;
+pub mod multisig;
+pub mod rng;
+pub mod secret;
+pub mod shamir;
+pub mod timestamp;
+pub mod trust;
+pub mod witness;
+#[cfg(all(feature = "fuzzing", not(target_arch = "wasm32")))]
+#[doc(hidden)]
+pub mod timing;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+#[cfg(all(unix, feature = "agent"))]
+pub mod agent;
+#[cfg(feature = "server")]
+pub mod server;
 
 /// Cryptographic error types
 #[derive(Error, Debug)]
@@ -47,6 +98,9 @@ pub enum CryptoError {
     #[error("Signature verification failed: {details}")]
     SignatureVerification { details: String },
     
+    #[error("Malformed encoding: {details}")]
+    MalformedEncoding { details: String },
+    
     #[error("Unsupported algorithm: {0:?}")]
     UnsupportedAlgorithm(AlgorithmVersion),
     
@@ -64,6 +118,15 @@ pub enum CryptoError {
     
     #[error("Timing attack detected: operation took {duration_ms}ms (expected: {expected_ms}ms)")]
     TimingAttack { duration_ms: u64, expected_ms: u64 },
+    
+    #[error("Key not found: {name}")]
+    KeyNotFound { name: String },
+    
+    #[error("Keystore error: {details}")]
+    Keystore { details: String },
+    
+    #[error("Agent error: {details}")]
+    Agent { details: String },
 }
 
 /// Cryptographic key types
@@ -84,15 +147,26 @@ pub enum AlgorithmVersion {
 }
 
 /// Key material storage with hybrid enforcement
+///
+/// Secret material is zeroized on drop; hybrid keys additionally keep it in
+/// locked memory (see [`secret`]).
+#[allow(clippy::large_enum_variant)]
 pub enum KeyMaterialInner {
     #[deprecated(note = "Pure Dilithium forbidden - use MandatoryHybrid")]
     Dilithium(DilithiumKeypair),
     #[deprecated(note = "Pure Ed25519 forbidden - use MandatoryHybrid")]
     Ed25519(SigningKey),
-    MandatoryHybrid {
-        dilithium: DilithiumKeypair,
-        ed25519: SigningKey,
-    },
+    MandatoryHybrid(HybridSecretKey),
+}
+
+impl Drop for KeyMaterialInner {
+    fn drop(&mut self) {
+        // `SigningKey` and `HybridSecretKey` clear themselves; the Dilithium keypair does not
+        #[allow(deprecated)]
+        if let KeyMaterialInner::Dilithium(keypair) = self {
+            zeroize::Zeroize::zeroize(&mut keypair.secret.bytes);
+        }
+    }
 }
 
 /// Private key with usage tracking and metadata
@@ -126,62 +200,22 @@ pub struct Signature {
 
 impl PrivateKey {
     /// Generate a new hybrid keypair
+    #[cfg(feature = "os-rng")]
     pub fn generate() -> Result<(Self, PublicKey), CryptoError> {
         Self::generate_with_algorithm(AlgorithmVersion::MandatoryHybrid)
     }
     
     /// Generate keypair with specific algorithm version
+    #[cfg(feature = "os-rng")]
     pub fn generate_with_algorithm(algorithm: AlgorithmVersion) -> Result<(Self, PublicKey), CryptoError> {
+        Self::generate_with_clock(algorithm, clock::default_clock())
+    }
+
+    /// Generate keypair timestamped by `clock`
+    #[cfg(feature = "os-rng")]
+    pub fn generate_with_clock(algorithm: AlgorithmVersion, clock: &(impl Clock + ?Sized)) -> Result<(Self, PublicKey), CryptoError> {
         match algorithm {
-            AlgorithmVersion::MandatoryHybrid => {
-                #[cfg(feature = "witness-integration")]
-                let operation_id = current_timestamp();
-                #[cfg(not(feature = "witness-integration"))]
-                let operation_id = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs();
-                use rand_core::OsRng;
-                let mut rng = OsRng;
-                
-                let dilithium_keypair = DilithiumKeypair::generate(None);
-                let ed25519_key = SigningKey::generate(&mut rng);
-                
-                let mut public_bytes = Vec::new();
-                public_bytes.extend_from_slice(&dilithium_keypair.public.to_bytes());
-                public_bytes.extend_from_slice(&ed25519_key.verifying_key().to_bytes());
-                
-                let inner = KeyMaterialInner::MandatoryHybrid {
-                    dilithium: dilithium_keypair,
-                    ed25519: ed25519_key,
-                };
-                
-                #[cfg(feature = "witness-integration")]
-                let now = current_timestamp();
-                #[cfg(not(feature = "witness-integration"))]
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap_or_default()
-                    .as_secs();
-                
-                let private_key = Self {
-                    algorithm,
-                    inner,
-                    created_at: now,
-                    operation_id,
-                    usage_count: AtomicU64::new(0),
-                    key_id: format!("mandatory-hybrid-{}", operation_id),
-                };
-                
-                let public_key = PublicKey {
-                    algorithm,
-                    bytes: public_bytes,
-                    created_at: now,
-                    operation_id,
-                };
-                
-                Ok((private_key, public_key))
-            }
+            AlgorithmVersion::MandatoryHybrid => Self::generate_with_rng(&mut rand_core::OsRng, clock),
             #[allow(deprecated)]
             AlgorithmVersion::Dilithium3V1 => {
                 Err(CryptoError::UnsupportedAlgorithm(algorithm))
@@ -192,6 +226,48 @@ impl PrivateKey {
             }
         }
     }
+
+    /// Generate a hybrid keypair from `rng`, timestamped by `clock`
+    ///
+    /// The output of `rng` passes through a [`rng::HealthTestedRng`], which
+    /// discards [`rng::STARTUP_SAMPLES`] bytes before drawing key material.
+    /// Use [`rng::DeterministicRng`] for reproducible keys in tests.
+    pub fn generate_with_rng<R: CryptoRngCore + ?Sized>(rng: &mut R, clock: &(impl Clock + ?Sized)) -> Result<(Self, PublicKey), CryptoError> {
+        let algorithm = AlgorithmVersion::MandatoryHybrid;
+        let now = clock.now();
+        let operation_id = now;
+        
+        let mut source = rng::HealthTestedRng::new(rng);
+        let mut dilithium_seed = Zeroizing::new([0u8; 32]);
+        let mut ed25519_secret = Zeroizing::new([0u8; 32]);
+        source.fill(dilithium_seed.as_mut_slice())?;
+        source.fill(ed25519_secret.as_mut_slice())?;
+        
+        let secret_key = HybridSecretKey::from_keypairs(
+            DilithiumKeypair::generate(Some(dilithium_seed.as_slice())),
+            SigningKey::from_bytes(&ed25519_secret),
+        );
+        let public_bytes = secret_key.public().to_bytes();
+        let inner = KeyMaterialInner::MandatoryHybrid(secret_key);
+        
+        let private_key = Self {
+            algorithm,
+            inner,
+            created_at: now,
+            operation_id,
+            usage_count: AtomicU64::new(0),
+            key_id: format!("mandatory-hybrid-{}", operation_id),
+        };
+        
+        let public_key = PublicKey {
+            algorithm,
+            bytes: public_bytes,
+            created_at: now,
+            operation_id,
+        };
+        
+        Ok((private_key, public_key))
+    }
     
     /// Extract the corresponding public key
     pub fn public_key(&self) -> Result<PublicKey, CryptoError> {
@@ -208,16 +284,11 @@ impl PrivateKey {
                     details: "Pure Ed25519 forbidden - use MandatoryHybrid".to_string() 
                 });
             }
-            KeyMaterialInner::MandatoryHybrid { dilithium, ed25519 } => {
-                let mut bytes = Vec::new();
-                bytes.extend_from_slice(&dilithium.public.to_bytes());
-                bytes.extend_from_slice(&ed25519.verifying_key().to_bytes());
-                bytes
-            }
+            KeyMaterialInner::MandatoryHybrid(secret_key) => secret_key.public().to_bytes(),
         };
         
         Ok(PublicKey {
-            algorithm: self.algorithm.clone(),
+            algorithm: self.algorithm,
             bytes,
             created_at: self.created_at,
             operation_id: self.operation_id,
@@ -225,7 +296,13 @@ impl PrivateKey {
     }
 
     /// Create a hybrid digital signature
+    #[cfg(feature = "std")]
     pub fn sign(&self, message: &[u8]) -> Result<Signature, CryptoError> {
+        self.sign_with_clock(message, clock::default_clock())
+    }
+
+    /// Create a hybrid digital signature timestamped by `clock`
+    pub fn sign_with_clock(&self, message: &[u8], clock: &(impl Clock + ?Sized)) -> Result<Signature, CryptoError> {
         // Input validation
         if message.is_empty() {
             return Err(CryptoError::InvalidOperation {
@@ -240,20 +317,15 @@ impl PrivateKey {
         }
         
         // Usage tracking
-        let current_usage = self.usage_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        let current_usage = self.usage_count.fetch_add(1, core::sync::atomic::Ordering::SeqCst);
         if current_usage >= 1_000_000 {
             return Err(CryptoError::InvalidOperation {
                 details: "Key usage limit exceeded".to_string()
             });
         }
         
-        #[cfg(feature = "witness-integration")]
-        let operation_id = current_timestamp();
-        #[cfg(not(feature = "witness-integration"))]
-        let operation_id = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let now = clock.now();
+        let operation_id = now;
         
         let signature_bytes = match &self.inner {
             #[allow(deprecated)]
@@ -268,27 +340,13 @@ impl PrivateKey {
                     details: "Pure Ed25519 signing forbidden".to_string()
                 });
             }
-            KeyMaterialInner::MandatoryHybrid { dilithium, ed25519 } => {
-                let dilithium_sig = dilithium.sign(message);
-                let ed25519_sig = ed25519.sign(message);
-                
-                let mut combined = Vec::new();
-                combined.extend_from_slice(&dilithium_sig);
-                combined.extend_from_slice(&ed25519_sig.to_bytes());
-                combined
-            }
+            KeyMaterialInner::MandatoryHybrid(secret_key) => secret_key.sign(message).to_bytes(),
         };
         
         Ok(Signature {
             algorithm: self.algorithm,
             bytes: signature_bytes,
-            #[cfg(feature = "witness-integration")]
-            created_at: current_timestamp(),
-            #[cfg(not(feature = "witness-integration"))]
-            created_at: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_secs(),
+            created_at: now,
             operation_id,
             signer_key_id: self.key_id.clone(),
         })
@@ -312,90 +370,132 @@ impl PublicKey {
                 })
             }
             AlgorithmVersion::MandatoryHybrid => {
-                // Memory safety: validate key length
-                if self.bytes.len() < PUBLICKEYBYTES + 32 {
-                    return Err(CryptoError::InvalidKey {
-                        details: "Invalid mandatory hybrid key length".to_string()
-                    });
-                }
-                
-                let dilithium_public = DilithiumPublicKey::from_bytes(&self.bytes[..PUBLICKEYBYTES]);
-                let ed25519_bytes: [u8; 32] = self.bytes[PUBLICKEYBYTES..PUBLICKEYBYTES + 32].try_into()
-                    .map_err(|_| CryptoError::InvalidKey {
-                        details: "Invalid mandatory hybrid Ed25519 key".to_string()
-                    })?;
-                
-                let ed25519_public = VerifyingKey::from_bytes(&ed25519_bytes)
-                    .map_err(|_| CryptoError::InvalidKey {
-                        details: "Invalid mandatory hybrid Ed25519 public key".to_string()
-                    })?;
-                
-                // Memory safety: validate signature length
-                if signature.bytes.len() < SIGNBYTES + 64 {
-                    return Err(CryptoError::SignatureVerification {
-                        details: "Invalid mandatory hybrid signature length".to_string()
-                    });
-                }
-                
-                if !dilithium_public.verify(message, &signature.bytes[..SIGNBYTES]) {
+                if signature.algorithm != self.algorithm {
                     return Err(CryptoError::SignatureVerification {
-                        details: "Mandatory hybrid Dilithium verification failed".to_string()
+                        details: format!("Signature algorithm {:?} does not match key algorithm {:?}", signature.algorithm, self.algorithm)
                     });
                 }
                 
-                let ed25519_sig_bytes: [u8; 64] = signature.bytes[SIGNBYTES..SIGNBYTES + 64].try_into()
-                    .map_err(|_| CryptoError::SignatureVerification {
-                        details: "Invalid mandatory hybrid Ed25519 signature length".to_string()
-                    })?;
-                
-                let ed25519_sig = ed25519_dalek::Signature::from_bytes(&ed25519_sig_bytes);
-                
-                ed25519_public.verify(message, &ed25519_sig)
-                    .map_err(|_| CryptoError::SignatureVerification {
-                        details: "Mandatory hybrid Ed25519 verification failed".to_string()
-                    })
+                self.hybrid()?.verify(message, &signature.hybrid()?)
             }
         }
     }
+    
+    /// Validated hybrid encoding of this key
+    pub fn hybrid(&self) -> Result<HybridPublicKey, CryptoError> {
+        if self.algorithm != AlgorithmVersion::MandatoryHybrid {
+            return Err(CryptoError::UnsupportedAlgorithm(self.algorithm));
+        }
+        HybridPublicKey::from_bytes(&self.bytes)
+    }
+    
+    /// Build a hybrid public key from its components and metadata
+    pub fn from_hybrid(key: &HybridPublicKey, created_at: u64, operation_id: u64) -> Self {
+        Self {
+            algorithm: AlgorithmVersion::MandatoryHybrid,
+            bytes: key.to_bytes(),
+            created_at,
+            operation_id,
+        }
+    }
+    
+    /// Dilithium3 half of this key
+    pub fn dilithium_public(&self) -> Result<[u8; PUBLICKEYBYTES], CryptoError> {
+        Ok(*self.hybrid()?.dilithium_public())
+    }
+    
+    /// Ed25519 half of this key
+    pub fn ed25519_public(&self) -> Result<VerifyingKey, CryptoError> {
+        Ok(*self.hybrid()?.ed25519_public())
+    }
+    
+    /// Fingerprint of the key bytes, independent of creation metadata
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::of(&self.bytes)
+    }
 }
 
-/// Generate deterministic keypair from seed
-pub fn generate_key_from_seed(seed: &[u8; 32]) -> Result<(PrivateKey, PublicKey), CryptoError> {
-    use rand::{SeedableRng};
-    use rand_chacha::ChaCha20Rng;
-    
-    let mut rng = ChaCha20Rng::from_seed(*seed);
-    let operation_id = u64::from_be_bytes([seed[0], seed[1], seed[2], seed[3], seed[4], seed[5], seed[6], seed[7]]);
-    
-    // Try to load encrypted Dilithium keypair first
-    let dilithium_keypair = match load_encrypted_dilithium_keypair(seed) {
-        Ok(keypair) => keypair,
-        Err(_) => {
-            // Generate new Dilithium keypair and save it encrypted
-            let keypair = DilithiumKeypair::generate(None);
-            save_encrypted_dilithium_keypair(seed, &keypair)?;
-            keypair
+impl Signature {
+    /// Validated hybrid encoding of this signature
+    pub fn hybrid(&self) -> Result<HybridSignature, CryptoError> {
+        if self.algorithm != AlgorithmVersion::MandatoryHybrid {
+            return Err(CryptoError::UnsupportedAlgorithm(self.algorithm));
         }
-    };
+        HybridSignature::from_bytes(&self.bytes)
+    }
     
-    let ed25519_key = SigningKey::generate(&mut rng);
+    /// Build a hybrid signature from its components and metadata
+    pub fn from_hybrid(signature: &HybridSignature, created_at: u64, operation_id: u64, signer_key_id: String) -> Self {
+        Self {
+            algorithm: AlgorithmVersion::MandatoryHybrid,
+            bytes: signature.to_bytes(),
+            created_at,
+            operation_id,
+            signer_key_id,
+        }
+    }
     
-    let mut public_bytes = Vec::new();
-    public_bytes.extend_from_slice(&dilithium_keypair.public.to_bytes());
-    public_bytes.extend_from_slice(&ed25519_key.verifying_key().to_bytes());
+    /// Dilithium3 half of this signature
+    pub fn dilithium_signature(&self) -> Result<[u8; SIGNBYTES], CryptoError> {
+        Ok(*self.hybrid()?.dilithium_signature())
+    }
     
-    let inner = KeyMaterialInner::MandatoryHybrid {
-        dilithium: dilithium_keypair,
-        ed25519: ed25519_key,
+    /// Ed25519 half of this signature
+    pub fn ed25519_signature(&self) -> Result<ed25519_dalek::Signature, CryptoError> {
+        Ok(*self.hybrid()?.ed25519_signature())
+    }
+}
+
+/// Generate deterministic keypair from seed
+///
+/// All subkeys, including the Dilithium half, come from the current
+/// [`kdf::KeySchedule`], so a seed gives the same key in every build and on
+/// every machine.
+#[cfg(feature = "std")]
+pub fn generate_key_from_seed(seed: &[u8; 32]) -> Result<(PrivateKey, PublicKey), CryptoError> {
+    generate_key_from_seed_with_clock(seed, clock::default_clock())
+}
+
+/// Generate deterministic keypair from seed, timestamped by `clock`
+pub fn generate_key_from_seed_with_clock(seed: &[u8; 32], clock: &(impl Clock + ?Sized)) -> Result<(PrivateKey, PublicKey), CryptoError> {
+    generate_key_from_schedule(&kdf::KeySchedule::new(seed), clock)
+}
+
+/// Generate deterministic keypair from the subkeys of `schedule`
+///
+/// Use a [`kdf::KeyScheduleVersion::Legacy`] schedule to regenerate keys
+/// created before the HKDF key schedule was introduced. With the `fs-cache`
+/// feature their Dilithium half is random and cached encrypted in the
+/// working directory, as it was then.
+pub fn generate_key_from_schedule(schedule: &kdf::KeySchedule, clock: &(impl Clock + ?Sized)) -> Result<(PrivateKey, PublicKey), CryptoError> {
+    use kdf::Subkey;
+    
+    let key_id = schedule.derive(Subkey::KeyId);
+    let operation_id = u64::from_be_bytes([key_id[0], key_id[1], key_id[2], key_id[3], key_id[4], key_id[5], key_id[6], key_id[7]]);
+    
+    let dilithium_keypair = match schedule.version() {
+        // Legacy keys were random and live only in the encrypted cache
+        #[cfg(feature = "fs-cache")]
+        kdf::KeyScheduleVersion::Legacy => match load_encrypted_dilithium_keypair(schedule) {
+            Ok(keypair) => keypair,
+            Err(_) => {
+                // Generate new Dilithium keypair and save it encrypted
+                let mut entropy = Zeroizing::new([0u8; 32]);
+                rng::fill_tested(&mut rand_core::OsRng, entropy.as_mut_slice())?;
+                let keypair = DilithiumKeypair::generate(Some(entropy.as_slice()));
+                save_encrypted_dilithium_keypair(schedule, &keypair)?;
+                keypair
+            }
+        },
+        _ => DilithiumKeypair::generate(Some(schedule.derive(Subkey::DilithiumSeed).as_slice())),
     };
     
-    #[cfg(feature = "witness-integration")]
-    let now = current_timestamp();
-    #[cfg(not(feature = "witness-integration"))]
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
+    let ed25519 = SigningKey::from_bytes(&schedule.derive(Subkey::Ed25519Seed));
+    let secret_key = HybridSecretKey::from_keypairs(dilithium_keypair, ed25519);
+    let public_bytes = secret_key.public().to_bytes();
+    let inner = KeyMaterialInner::MandatoryHybrid(secret_key);
+    
+    let now = clock.now();
     
     let private_key = PrivateKey {
         algorithm: AlgorithmVersion::MandatoryHybrid,
@@ -403,7 +503,7 @@ pub fn generate_key_from_seed(seed: &[u8; 32]) -> Result<(PrivateKey, PublicKey)
         created_at: now,
         operation_id,
         usage_count: AtomicU64::new(0),
-        key_id: format!("deterministic-hybrid-{}", hex::encode(&seed[..8])),
+        key_id: format!("deterministic-hybrid-{}", hex::encode(&key_id[..8])),
     };
     
     let public_key = PublicKey {
@@ -417,70 +517,53 @@ pub fn generate_key_from_seed(seed: &[u8; 32]) -> Result<(PrivateKey, PublicKey)
 }
 
 /// Generate keypair with policy binding
-pub fn generate_witness_bound_key(policy_hash: &[u8; 32]) -> Result<(PrivateKey, PublicKey), CryptoError> {
+///
+/// With `witness-integration` the policy hash must be committed to the
+/// [`witness::LocalWitness`] log, and the returned [`witness::WitnessBinding`]
+/// is the key's signature over that commitment, for the caller to persist
+/// next to the public key. Without the feature no binding is made. See
+/// [`witness::generate_bound_key_with_rng`] for other backends.
+#[cfg(feature = "os-rng")]
+pub fn generate_witness_bound_key(policy_hash: &[u8; 32]) -> Result<(PrivateKey, PublicKey, Option<witness::WitnessBinding>), CryptoError> {
     #[cfg(feature = "witness-integration")]
     {
-        let witness_proof = verify_witness_commitment(*policy_hash, EntryType::PolicyCreate)
-            .map_err(|_| CryptoError::InvalidOperation {
-                details: "Witness commitment verification failed".to_string()
-            })?;
-        
-        // Generate standard keypair
-        let (private_key, public_key) = PrivateKey::generate()?;
-        
-        // Create cryptographic binding to policy
-        let binding_data = [policy_hash.as_slice(), &witness_proof.commitment_hash].concat();
-        let binding_signature = private_key.sign(&binding_data)?;
-        
-        // Verify binding integrity
-        public_key.verify(&binding_data, &binding_signature)?;
-        
-        Ok((private_key, public_key))
+        let (private_key, public_key, binding) =
+            witness::generate_bound_key_with_rng(policy_hash, &witness::LocalWitness, &mut rand_core::OsRng)?;
+        Ok((private_key, public_key, Some(binding)))
     }
     
     #[cfg(not(feature = "witness-integration"))]
     {
         let _ = policy_hash; // Silence unused parameter warning
-        PrivateKey::generate()
+        let (private_key, public_key) = PrivateKey::generate()?;
+        Ok((private_key, public_key, None))
     }
 }
 
 /// Generate cryptographically secure random bytes
+///
+/// Fails with [`CryptoError::KeyGeneration`] if the operating system
+/// generator fails its health tests.
+#[cfg(feature = "os-rng")]
 pub fn secure_random_bytes(buffer: &mut [u8]) -> Result<(), CryptoError> {
-    use rand::RngCore;
-    let mut rng = rand::rngs::OsRng;
-    rng.fill_bytes(buffer);
-    Ok(())
+    rng::fill_tested(&mut rand_core::OsRng, buffer)
 }
 
-/// Derive secure encryption key from seed using HKDF
-fn derive_encryption_key(seed: &[u8; 32]) -> [u8; 32] {
-    use sha2::{Sha256, Digest};
-    
-    // Use HKDF-like derivation
-    let mut hasher = Sha256::new();
-    hasher.update(b"AF_ENCRYPTION_KEY_V1");
-    hasher.update(seed);
-    hasher.update(b"DILITHIUM_STORAGE");
-    hasher.finalize().into()
+#[cfg(feature = "fs-cache")]
+fn generate_secure_filename(schedule: &kdf::KeySchedule) -> String {
+    let hash = schedule.derive(kdf::Subkey::CacheName);
+    match schedule.version() {
+        kdf::KeyScheduleVersion::Legacy => format!(".af_dilithium_{}", hex::encode(&hash[..16])),
+        version => format!(".af_dilithium_{}_{}", version, hex::encode(&hash[..16])),
+    }
 }
 
-fn generate_secure_filename(seed: &[u8; 32]) -> String {
-    use sha2::{Sha256, Digest};
-    
-    // Hash the seed for filename
-    let mut hasher = Sha256::new();
-    hasher.update(b"AF_FILENAME_V1");
-    hasher.update(seed);
-    let hash = hasher.finalize();
-    
-    format!(".af_dilithium_{}", hex::encode(&hash[..16]))
-}
 /// Validate file path for security
-fn validate_encrypted_file_path(seed: &[u8; 32]) -> Result<std::path::PathBuf, CryptoError> {
+#[cfg(feature = "fs-cache")]
+fn validate_encrypted_file_path(schedule: &kdf::KeySchedule) -> Result<std::path::PathBuf, CryptoError> {
     use std::path::Path;
     
-    let filename = generate_secure_filename(seed);
+    let filename = generate_secure_filename(schedule);
     let path = Path::new(&filename);
     
     // Security validation
@@ -493,23 +576,24 @@ fn validate_encrypted_file_path(seed: &[u8; 32]) -> Result<std::path::PathBuf, C
     Ok(path.to_path_buf())
 }
 /// Save encrypted Dilithium keypair to disk
-fn save_encrypted_dilithium_keypair(seed: &[u8; 32], keypair: &DilithiumKeypair) -> Result<(), CryptoError> {
+#[cfg(feature = "fs-cache")]
+fn save_encrypted_dilithium_keypair(schedule: &kdf::KeySchedule, keypair: &DilithiumKeypair) -> Result<(), CryptoError> {
     use aes_gcm::{Aes256Gcm, Key, KeyInit, AeadCore};
     use aes_gcm::aead::Aead;
     
     // Validate file path for security
-    let file_path = validate_encrypted_file_path(seed)?;
+    let file_path = validate_encrypted_file_path(schedule)?;
     
     // Use derived encryption key (NOT the seed directly)
-    let encryption_key = derive_encryption_key(seed);
-    let key = Key::<Aes256Gcm>::from(encryption_key);
-    let cipher = Aes256Gcm::new(&key);
+    let encryption_key = schedule.derive(kdf::Subkey::StorageKey);
+    let key = Key::<Aes256Gcm>::from_slice(encryption_key.as_slice());
+    let cipher = Aes256Gcm::new(key);
     
     // Generate random nonce
-    let nonce = Aes256Gcm::generate_nonce(&mut rand::rngs::OsRng);
+    let nonce = Aes256Gcm::generate_nonce(&mut rand_core::OsRng);
     
     // Serialize keypair
-    let keypair_bytes = [keypair.public.to_bytes().as_slice(), keypair.secret.to_bytes().as_slice()].concat();
+    let keypair_bytes = Zeroizing::new([keypair.public.to_bytes().as_slice(), keypair.secret.bytes.as_slice()].concat());
     
     // Encrypt
     let ciphertext = cipher.encrypt(&nonce, keypair_bytes.as_ref())
@@ -527,16 +611,24 @@ fn save_encrypted_dilithium_keypair(seed: &[u8; 32], keypair: &DilithiumKeypair)
 }
 
 /// Load encrypted Dilithium keypair from disk
-fn load_encrypted_dilithium_keypair(seed: &[u8; 32]) -> Result<DilithiumKeypair, CryptoError> {
-    use aes_gcm::{Aes256Gcm, Key, Nonce, KeyInit};
-    use aes_gcm::aead::Aead;
-    
+#[cfg(feature = "fs-cache")]
+fn load_encrypted_dilithium_keypair(schedule: &kdf::KeySchedule) -> Result<DilithiumKeypair, CryptoError> {
     // Validate file path for security
-    let file_path = validate_encrypted_file_path(seed)?;
+    let file_path = validate_encrypted_file_path(schedule)?;
     
     let file_data = std::fs::read(&file_path)
         .map_err(|_| CryptoError::InvalidOperation { details: "Encrypted keypair not found".to_string() })?;
     
+    decrypt_dilithium_keypair(schedule, &file_data)
+}
+
+/// Parse and decrypt the contents of an encrypted keypair file
+#[cfg(feature = "fs-cache")]
+fn decrypt_dilithium_keypair(schedule: &kdf::KeySchedule, file_data: &[u8]) -> Result<DilithiumKeypair, CryptoError> {
+    use aes_gcm::{Aes256Gcm, Key, Nonce, KeyInit};
+    use aes_gcm::aead::Aead;
+    use crystals_dilithium::dilithium3::{PUBLICKEYBYTES, SECRETKEYBYTES};
+    
     if file_data.len() < 12 {
         return Err(CryptoError::InvalidOperation { details: "Invalid encrypted file".to_string() });
     }
@@ -548,16 +640,16 @@ fn load_encrypted_dilithium_keypair(seed: &[u8; 32]) -> Result<DilithiumKeypair,
     let nonce = Nonce::from(nonce_array);
     
     // Use derived encryption key
-    let encryption_key = derive_encryption_key(seed);
-    let key = Key::<Aes256Gcm>::from(encryption_key);
-    let cipher = Aes256Gcm::new(&key);
+    let encryption_key = schedule.derive(kdf::Subkey::StorageKey);
+    let key = Key::<Aes256Gcm>::from_slice(encryption_key.as_slice());
+    let cipher = Aes256Gcm::new(key);
     
     // Decrypt
-    let plaintext = cipher.decrypt(&nonce, ciphertext)
-        .map_err(|_| CryptoError::InvalidOperation { details: "Decryption failed".to_string() })?;
+    let plaintext = Zeroizing::new(cipher.decrypt(&nonce, ciphertext)
+        .map_err(|_| CryptoError::InvalidOperation { details: "Decryption failed".to_string() })?);
     
     // Reconstruct keypair
-    if plaintext.len() != PUBLICKEYBYTES + crystals_dilithium::dilithium3::SECRETKEYBYTES {
+    if plaintext.len() != PUBLICKEYBYTES + SECRETKEYBYTES {
         return Err(CryptoError::InvalidOperation { details: "Invalid keypair data".to_string() });
     }
     
@@ -570,5 +662,18 @@ fn load_encrypted_dilithium_keypair(seed: &[u8; 32]) -> Result<DilithiumKeypair,
     Ok(DilithiumKeypair { public: public_key, secret: secret_key })
 }
 
+/// Entry points for the fuzz targets in `fuzz/`
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing {
+    use super::*;
+
+    /// Parse an encrypted keypair cache file as read from disk
+    pub fn parse_cache_file(seed: &[u8; 32], file_data: &[u8]) -> Result<(), CryptoError> {
+        let schedule = kdf::KeySchedule::with_version(seed, kdf::KeyScheduleVersion::Legacy);
+        decrypt_dilithium_keypair(&schedule, file_data).map(|_| ())
+    }
+}
+
 // End of module
 
@@ -0,0 +1,179 @@
+//! # Mnemonic Seeds
+//!
+//! A 32-byte master seed written as 24 words from the BIP39 English word
+//! list. The encoding is BIP39's: the 256 seed bits followed by the first 8
+//! bits of their SHA-256 are split into 24 groups of 11 bits, each selecting
+//! one word. The last word therefore carries the checksum, and a mistyped or
+//! swapped word is detected with probability 255/256.
+//!
+//! The phrase decodes to the seed itself, not to BIP39's PBKDF2 output, so a
+//! seed and its mnemonic are interchangeable. An optional passphrase is
+//! applied by [`crate::kdf::KeySchedule::with_passphrase`].
+
+mod english;
+
+use crate::CryptoError;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use english::WORDS;
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// Number of words in a seed mnemonic
+pub const MNEMONIC_WORDS: usize = 24;
+
+/// Bits selected by each word
+const BITS_PER_WORD: usize = 11;
+
+/// Largest edit distance of the typos considered when locating a checksum failure
+const MAX_TYPO_DISTANCE: usize = 1;
+
+/// Encode a seed as a 24-word mnemonic
+pub fn encode(seed: &[u8; 32]) -> Zeroizing<String> {
+    let mut bits = Zeroizing::new([0u8; 33]);
+    bits[..32].copy_from_slice(seed);
+    bits[32] = Sha256::digest(seed)[0];
+
+    let mut phrase = Zeroizing::new(String::with_capacity(MNEMONIC_WORDS * 9));
+    for position in 0..MNEMONIC_WORDS {
+        if position > 0 {
+            phrase.push(' ');
+        }
+        let index = (0..BITS_PER_WORD).fold(0usize, |index, bit| {
+            let offset = position * BITS_PER_WORD + bit;
+            (index << 1) | usize::from((bits[offset / 8] >> (7 - offset % 8)) & 1)
+        });
+        phrase.push_str(WORDS[index]);
+    }
+    phrase
+}
+
+/// Decode a 24-word mnemonic into its seed
+///
+/// Words are separated by any whitespace and compared case-insensitively.
+/// Errors give the position of the first word that is not in the word list.
+/// Checksum errors give the
+/// positions of adjacent swaps and one-letter typos that would fix the
+/// checksum. Errors never repeat the words themselves, as they may be logged.
+pub fn decode(phrase: &str) -> Result<Zeroizing<[u8; 32]>, CryptoError> {
+    let count = phrase.split_whitespace().count();
+    if count != MNEMONIC_WORDS {
+        return Err(CryptoError::MalformedEncoding {
+            details: format!("Mnemonic must have {} words, found {}", MNEMONIC_WORDS, count),
+        });
+    }
+
+    let mut indices = Zeroizing::new([0usize; MNEMONIC_WORDS]);
+    for (position, word) in phrase.split_whitespace().enumerate() {
+        let word = Zeroizing::new(word.to_lowercase());
+        indices[position] = WORDS.binary_search(&word.as_str()).map_err(|_| unknown_word(position))?;
+    }
+
+    checksummed_seed(&indices).ok_or_else(|| checksum_mismatch(&indices))
+}
+
+/// Seed selected by the word indices, if its checksum is correct
+fn checksummed_seed(indices: &[usize; MNEMONIC_WORDS]) -> Option<Zeroizing<[u8; 32]>> {
+    let mut bits = Zeroizing::new([0u8; 33]);
+    for (position, index) in indices.iter().enumerate() {
+        for bit in 0..BITS_PER_WORD {
+            if (index >> (BITS_PER_WORD - 1 - bit)) & 1 == 1 {
+                let offset = position * BITS_PER_WORD + bit;
+                bits[offset / 8] |= 0x80 >> (offset % 8);
+            }
+        }
+    }
+
+    let mut seed = Zeroizing::new([0u8; 32]);
+    seed.copy_from_slice(&bits[..32]);
+    (Sha256::digest(seed.as_slice())[0] == bits[32]).then_some(seed)
+}
+
+/// Checksum error naming the positions whose correction would fix the checksum
+///
+/// An 8-bit checksum cannot identify the wrong word, so this only looks for
+/// the common mistakes: two adjacent words swapped, or a one-letter typo
+/// that still spells a list word. Either may also fix it by chance.
+fn checksum_mismatch(indices: &[usize; MNEMONIC_WORDS]) -> CryptoError {
+    let mut candidate = Zeroizing::new(*indices);
+    let mut hints = Vec::new();
+
+    for position in 0..MNEMONIC_WORDS - 1 {
+        if indices[position] == indices[position + 1] {
+            continue;
+        }
+        candidate.swap(position, position + 1);
+        if checksummed_seed(&candidate).is_some() {
+            hints.push(format!("words {} and {} may be swapped", position + 1, position + 2));
+        }
+        candidate.swap(position, position + 1);
+    }
+
+    let mut typos = Vec::new();
+    for position in 0..MNEMONIC_WORDS {
+        let word = WORDS[indices[position]];
+        let fixed = (0..WORDS.len()).any(|index| {
+            if index == indices[position] || edit_distance(word, WORDS[index]) > MAX_TYPO_DISTANCE {
+                return false;
+            }
+            candidate[position] = index;
+            checksummed_seed(&candidate).is_some()
+        });
+        candidate[position] = indices[position];
+        if fixed {
+            typos.push(format!("{}", position + 1));
+        }
+    }
+    match typos.len() {
+        0 => {}
+        1 => hints.push(format!("word {} may be mistyped", typos[0])),
+        _ => hints.push(format!("one of words {} may be mistyped", typos.join(", "))),
+    }
+
+    if hints.is_empty() {
+        hints.push("a word is wrong or out of order".into());
+    }
+    CryptoError::MalformedEncoding {
+        details: format!(
+            "Mnemonic checksum mismatch: {} (word {} carries the checksum)",
+            hints.join("; "), MNEMONIC_WORDS
+        ),
+    }
+}
+
+/// Whether `word` is in the mnemonic word list
+pub fn is_word(word: &str) -> bool {
+    WORDS.binary_search(&word).is_ok()
+}
+
+fn unknown_word(position: usize) -> CryptoError {
+    CryptoError::MalformedEncoding {
+        details: format!("Word {} is not in the BIP39 English word list", position + 1),
+    }
+}
+
+/// Edit distance between two short words, counting adjacent transpositions as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut distance = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in distance.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in distance[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = distance[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut best = substitution.min(distance[i - 1][j] + 1).min(distance[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(distance[i - 2][j - 2] + 1);
+            }
+            distance[i][j] = best;
+        }
+    }
+    distance[a.len()][b.len()]
+}
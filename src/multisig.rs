@@ -0,0 +1,266 @@
+//! # Multi-Signatures
+//!
+//! A [`MultiSignature`] collects hybrid signatures of several keys over one
+//! message, for documents that several parties must co-sign. It records the
+//! SHA-256 digest of the message and the fingerprint of each signing key, so
+//! parties can add their signatures one at a time, in any order.
+//!
+//! [`MultiSignature::verify`] checks the signatures of a given list of public
+//! keys against a [`Policy`]:
+//!
+//! - `all`: every listed key signed;
+//! - `M-of-N`: at least `M` of the `N` listed keys signed;
+//! - `weighted:T:w1,w2,...`: the weights of the listed keys that signed, in
+//!   list order, add up to at least `T`.
+//!
+//! Signatures by keys outside the list are ignored; an invalid signature by a
+//! listed key fails verification.
+
+use crate::clock::Clock;
+use crate::constant_time;
+use crate::{CryptoError, Fingerprint, PrivateKey, PublicKey, Signature};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Current multi-signature format version
+pub const MULTISIG_VERSION: u8 = 1;
+
+/// One party's signature in a [`MultiSignature`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Cosignature {
+    pub signer: Fingerprint,
+    pub signature: Signature,
+}
+
+/// Hybrid signatures of several keys over the same message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiSignature {
+    version: u8,
+    #[serde(with = "hex::serde")]
+    message_digest: [u8; 32],
+    signatures: Vec<Cosignature>,
+}
+
+/// Which signatures a [`MultiSignature`] needs to verify
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Policy {
+    /// Every listed key signed
+    AllOf,
+    /// At least `threshold` of the listed keys signed
+    Threshold { threshold: usize, keys: usize },
+    /// The weights of the listed keys that signed add up to `threshold`
+    Weighted { threshold: u64, weights: Vec<u64> },
+}
+
+impl MultiSignature {
+    /// Empty multi-signature over `message`
+    pub fn new(message: &[u8]) -> Self {
+        Self { version: MULTISIG_VERSION, message_digest: digest(message), signatures: Vec::new() }
+    }
+
+    /// SHA-256 digest of the signed message
+    pub fn message_digest(&self) -> &[u8; 32] {
+        &self.message_digest
+    }
+
+    /// Signatures collected so far, in the order they were added
+    pub fn signatures(&self) -> &[Cosignature] {
+        &self.signatures
+    }
+
+    /// Fingerprints of the keys that signed
+    pub fn signers(&self) -> impl Iterator<Item = Fingerprint> + '_ {
+        self.signatures.iter().map(|cosignature| cosignature.signer)
+    }
+
+    /// Sign `message` with `key`, timestamped by `clock`, and add the signature
+    pub fn sign(&mut self, message: &[u8], key: &PrivateKey, clock: &(impl Clock + ?Sized)) -> Result<Fingerprint, CryptoError> {
+        self.check_message(message)?;
+        let public_key = key.public_key()?;
+        self.check_new_signer(&public_key.fingerprint())?;
+        let signature = key.sign_with_clock(message, clock)?;
+        self.add(message, &public_key, signature)
+    }
+
+    /// Add a signature made elsewhere, checking it first
+    ///
+    /// Fails if the signature is invalid, `message` is not the message of
+    /// this multi-signature, or `public_key` has already signed.
+    pub fn add(&mut self, message: &[u8], public_key: &PublicKey, signature: Signature) -> Result<Fingerprint, CryptoError> {
+        self.check_message(message)?;
+        let signer = public_key.fingerprint();
+        self.check_new_signer(&signer)?;
+        public_key.verify(message, &signature)?;
+        self.signatures.push(Cosignature { signer, signature });
+        Ok(signer)
+    }
+
+    /// Check the signatures of `keys` over `message` against `policy`
+    ///
+    /// Returns the fingerprints of the listed keys that signed, in list order.
+    pub fn verify(&self, message: &[u8], keys: &[PublicKey], policy: &Policy) -> Result<Vec<Fingerprint>, CryptoError> {
+        if self.version != MULTISIG_VERSION {
+            return Err(CryptoError::MalformedEncoding {
+                details: format!("Unsupported multi-signature version {}", self.version),
+            });
+        }
+        policy.check_keys(keys.len())?;
+        if !constant_time::eq(&digest(message), &self.message_digest) {
+            return Err(CryptoError::SignatureVerification {
+                details: "Multi-signature is over a different message".into(),
+            });
+        }
+
+        let fingerprints: Vec<Fingerprint> = keys.iter().map(PublicKey::fingerprint).collect();
+        for (index, fingerprint) in fingerprints.iter().enumerate() {
+            if fingerprints[..index].contains(fingerprint) {
+                return Err(CryptoError::InvalidOperation {
+                    details: format!("Key {} is listed more than once", fingerprint),
+                });
+            }
+        }
+
+        let mut signed = Vec::with_capacity(keys.len());
+        for (key, fingerprint) in keys.iter().zip(&fingerprints) {
+            let cosignatures = self.signatures.iter().filter(|cosignature| cosignature.signer == *fingerprint);
+            let mut found = false;
+            for cosignature in cosignatures {
+                key.verify(message, &cosignature.signature).map_err(|e| CryptoError::SignatureVerification {
+                    details: format!("Signature of key {} is invalid: {}", fingerprint, e),
+                })?;
+                found = true;
+            }
+            signed.push(found);
+        }
+
+        policy.check(&signed)?;
+        Ok(fingerprints.into_iter().zip(signed).filter(|(_, signed)| *signed).map(|(fingerprint, _)| fingerprint).collect())
+    }
+
+    fn check_message(&self, message: &[u8]) -> Result<(), CryptoError> {
+        if !constant_time::eq(&digest(message), &self.message_digest) {
+            return Err(CryptoError::InvalidOperation {
+                details: "Message differs from the one this multi-signature is over".into(),
+            });
+        }
+        Ok(())
+    }
+
+    fn check_new_signer(&self, signer: &Fingerprint) -> Result<(), CryptoError> {
+        if self.signers().any(|existing| existing == *signer) {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Key {} has already signed", signer),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Policy {
+    /// Fail unless the policy applies to a list of `keys` public keys
+    fn check_keys(&self, keys: usize) -> Result<(), CryptoError> {
+        let problem = match self {
+            _ if keys == 0 => Some(format!("Policy {} needs at least one key", self)),
+            Policy::AllOf => None,
+            Policy::Threshold { threshold, keys: expected } if *threshold == 0 || threshold > expected => {
+                Some(format!("Policy {} needs a threshold between 1 and the number of keys", self))
+            }
+            Policy::Threshold { keys: expected, .. } if *expected != keys => {
+                Some(format!("Policy {} needs {} keys, got {}", self, expected, keys))
+            }
+            Policy::Weighted { threshold: 0, .. } => Some(format!("Policy {} needs a threshold above 0", self)),
+            Policy::Weighted { weights, .. } if weights.len() != keys => {
+                Some(format!("Policy {} has {} weights for {} keys", self, weights.len(), keys))
+            }
+            _ => None,
+        };
+        match problem {
+            Some(details) => Err(CryptoError::InvalidOperation { details }),
+            None => Ok(()),
+        }
+    }
+
+    /// Fail unless the keys marked in `signed` satisfy the policy
+    fn check(&self, signed: &[bool]) -> Result<(), CryptoError> {
+        let count = signed.iter().filter(|signed| **signed).count();
+        let progress = format!("{} of {} keys signed", count, signed.len());
+        let (met, progress) = match self {
+            Policy::AllOf => (count == signed.len(), progress),
+            Policy::Threshold { threshold, .. } => (count >= *threshold, progress),
+            Policy::Weighted { threshold, weights } => {
+                let weight = weights.iter().zip(signed)
+                    .filter(|(_, signed)| **signed)
+                    .fold(0u64, |sum, (weight, _)| sum.saturating_add(*weight));
+                (weight >= *threshold, format!("signed weight {} of {}", weight, threshold))
+            }
+        };
+        if !met {
+            return Err(CryptoError::SignatureVerification {
+                details: format!("Policy {} not met: {}", self, progress),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Policy::AllOf => f.write_str("all"),
+            Policy::Threshold { threshold, keys } => write!(f, "{}-of-{}", threshold, keys),
+            Policy::Weighted { threshold, weights } => {
+                write!(f, "weighted:{}:", threshold)?;
+                for (index, weight) in weights.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", weight)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for Policy {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |details: String| CryptoError::MalformedEncoding {
+            details: format!("Invalid policy '{}': {}", s, details),
+        };
+        let number = |text: &str| text.parse::<u64>().map_err(|e| invalid(format!("'{}': {}", text, e)));
+
+        if s == "all" {
+            return Ok(Policy::AllOf);
+        }
+        if let Some(rest) = s.strip_prefix("weighted:") {
+            let (threshold, weights) = rest.split_once(':')
+                .ok_or_else(|| invalid("expected weighted:THRESHOLD:W1,W2,...".into()))?;
+            let threshold = number(threshold)?;
+            let weights = weights.split(',').map(number).collect::<Result<Vec<_>, _>>()?;
+            if threshold == 0 || weights.iter().fold(0u64, |sum, weight| sum.saturating_add(*weight)) < threshold {
+                return Err(invalid("threshold must be between 1 and the total weight".into()));
+            }
+            return Ok(Policy::Weighted { threshold, weights });
+        }
+        if let Some((threshold, keys)) = s.split_once("-of-") {
+            let threshold = usize::try_from(number(threshold)?).map_err(|e| invalid(e.to_string()))?;
+            let keys = usize::try_from(number(keys)?).map_err(|e| invalid(e.to_string()))?;
+            if threshold == 0 || threshold > keys {
+                return Err(invalid("threshold must be between 1 and the number of keys".into()));
+            }
+            return Ok(Policy::Threshold { threshold, keys });
+        }
+        Err(invalid("expected all, M-of-N or weighted:THRESHOLD:W1,W2,...".into()))
+    }
+}
+
+fn digest(message: &[u8]) -> [u8; 32] {
+    Sha256::new().chain_update(b"af-pqc/multisig/message").chain_update(message).finalize().into()
+}
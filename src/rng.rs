@@ -0,0 +1,214 @@
+//! # Randomness
+//!
+//! Key generation draws from any [`CryptoRngCore`], always through a
+//! [`HealthTestedRng`] that runs the NIST SP 800-90B continuous health tests
+//! on every output byte:
+//!
+//! - **Repetition count test**: fails when one value repeats too many times
+//!   in a row (a stuck source).
+//! - **Adaptive proportion test**: fails when one value is too frequent
+//!   within a 512-sample window (a biased source).
+//!
+//! Cutoffs follow SP 800-90B §4.4 for a claimed min-entropy per byte and a
+//! false-positive probability of 2^-40 per test. Failures are reported as
+//! [`CryptoError::KeyGeneration`] with the claimed and the estimated
+//! min-entropy. [`DeterministicRng`] makes key generation reproducible for
+//! known-answer tests.
+
+use crate::CryptoError;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRng, CryptoRngCore, RngCore, SeedableRng};
+use zeroize::Zeroize;
+
+/// Claimed min-entropy of one byte from the operating system generator
+pub const DEFAULT_MIN_ENTROPY: f64 = 7.0;
+
+/// Adaptive proportion test window for non-binary sources
+pub const APT_WINDOW: usize = 512;
+
+/// Bytes tested and discarded before a new source's first output
+pub const STARTUP_SAMPLES: usize = 1024;
+
+/// False-positive probability of each test, as the exponent `n` in 2^-n
+const FALSE_POSITIVE_EXPONENT: f64 = 40.0;
+
+/// RNG wrapper applying SP 800-90B repetition count and adaptive proportion tests
+///
+/// The first fill runs the tests over [`STARTUP_SAMPLES`] discarded bytes.
+/// After a failure every further fill fails as well.
+pub struct HealthTestedRng<R> {
+    inner: R,
+    min_entropy: f64,
+    rct_cutoff: u32,
+    apt_cutoff: u32,
+    last_sample: Option<u8>,
+    run_length: u32,
+    apt_sample: u8,
+    apt_count: u32,
+    apt_position: usize,
+    started: bool,
+    failure: Option<f64>,
+}
+
+impl<R: RngCore> HealthTestedRng<R> {
+    /// Wrap `inner`, claiming [`DEFAULT_MIN_ENTROPY`] bits per byte
+    pub fn new(inner: R) -> Self {
+        Self::with_min_entropy(inner, DEFAULT_MIN_ENTROPY)
+    }
+
+    /// Wrap `inner`, claiming `min_entropy` bits per byte (clamped to 1..=8)
+    pub fn with_min_entropy(inner: R, min_entropy: f64) -> Self {
+        let min_entropy = min_entropy.clamp(1.0, 8.0);
+        Self {
+            inner,
+            min_entropy,
+            rct_cutoff: repetition_count_cutoff(min_entropy),
+            apt_cutoff: adaptive_proportion_cutoff(min_entropy),
+            last_sample: None,
+            run_length: 0,
+            apt_sample: 0,
+            apt_count: 0,
+            apt_position: 0,
+            started: false,
+            failure: None,
+        }
+    }
+
+    /// Claimed min-entropy in bits per byte
+    pub fn min_entropy(&self) -> f64 {
+        self.min_entropy
+    }
+
+    /// Fill `dest` with tested output; on failure `dest` is zeroed
+    pub fn fill(&mut self, dest: &mut [u8]) -> Result<(), CryptoError> {
+        let result = self.startup().and_then(|()| self.draw(dest));
+        if result.is_err() {
+            dest.zeroize();
+        }
+        result
+    }
+
+    fn startup(&mut self) -> Result<(), CryptoError> {
+        if self.started {
+            return Ok(());
+        }
+        let mut samples = [0u8; STARTUP_SAMPLES];
+        let result = self.draw(&mut samples);
+        samples.zeroize();
+        self.started = result.is_ok();
+        result
+    }
+
+    fn draw(&mut self, dest: &mut [u8]) -> Result<(), CryptoError> {
+        if let Some(actual) = self.failure {
+            return Err(self.error(actual));
+        }
+        if self.inner.try_fill_bytes(dest).is_err() {
+            self.failure = Some(0.0);
+            return Err(self.error(0.0));
+        }
+        for &sample in dest.iter() {
+            if let Err(actual) = self.test(sample) {
+                self.failure = Some(actual);
+                return Err(self.error(actual));
+            }
+        }
+        Ok(())
+    }
+
+    /// Feed one sample to both tests, returning the entropy estimate on failure
+    fn test(&mut self, sample: u8) -> Result<(), f64> {
+        // Repetition count test (SP 800-90B §4.4.1)
+        if self.last_sample == Some(sample) {
+            self.run_length += 1;
+            if self.run_length >= self.rct_cutoff {
+                // A run of B repeats is only likely if H <= n / (B - 1)
+                return Err(FALSE_POSITIVE_EXPONENT / f64::from(self.run_length - 1));
+            }
+        } else {
+            self.last_sample = Some(sample);
+            self.run_length = 1;
+        }
+
+        // Adaptive proportion test (SP 800-90B §4.4.2)
+        if self.apt_position == 0 {
+            self.apt_sample = sample;
+            self.apt_count = 1;
+        } else if sample == self.apt_sample {
+            self.apt_count += 1;
+            if self.apt_count >= self.apt_cutoff {
+                return Err(-libm::log2(f64::from(self.apt_count) / APT_WINDOW as f64));
+            }
+        }
+        self.apt_position = (self.apt_position + 1) % APT_WINDOW;
+        Ok(())
+    }
+
+    fn error(&self, actual: f64) -> CryptoError {
+        CryptoError::KeyGeneration { threshold: self.min_entropy, actual }
+    }
+}
+
+/// Repetition count cutoff `C = 1 + ceil(n / H)`
+fn repetition_count_cutoff(min_entropy: f64) -> u32 {
+    1 + libm::ceil(FALSE_POSITIVE_EXPONENT / min_entropy) as u32
+}
+
+/// Adaptive proportion cutoff `C = 1 + CRITBINOM(W, 2^-H, 1 - alpha)`
+fn adaptive_proportion_cutoff(min_entropy: f64) -> u32 {
+    let p = libm::exp2(-min_entropy);
+    let alpha = libm::exp2(-FALSE_POSITIVE_EXPONENT);
+
+    let mut pmf = [0f64; APT_WINDOW + 1];
+    pmf[0] = libm::pow(1.0 - p, APT_WINDOW as f64);
+    for k in 1..=APT_WINDOW {
+        pmf[k] = pmf[k - 1] * (APT_WINDOW - k + 1) as f64 / k as f64 * p / (1.0 - p);
+    }
+
+    // CRITBINOM: smallest k with P(X > k) <= alpha
+    let mut tail = 0.0;
+    for k in (0..=APT_WINDOW).rev() {
+        if tail + pmf[k] > alpha {
+            return k as u32 + 1;
+        }
+        tail += pmf[k];
+    }
+    1
+}
+
+/// ChaCha20 generator for known-answer tests
+///
+/// Produces the same stream for the same seed. Never use it for real keys.
+pub struct DeterministicRng(ChaCha20Rng);
+
+impl DeterministicRng {
+    /// Generator whose whole output is determined by `seed`
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        Self(ChaCha20Rng::from_seed(seed))
+    }
+}
+
+impl RngCore for DeterministicRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for DeterministicRng {}
+
+/// Fill `dest` from `rng` through a fresh [`HealthTestedRng`]
+pub fn fill_tested<R: CryptoRngCore + ?Sized>(rng: &mut R, dest: &mut [u8]) -> Result<(), CryptoError> {
+    HealthTestedRng::new(rng).fill(dest)
+}
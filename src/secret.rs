@@ -0,0 +1,238 @@
+//! # Secret Memory
+//!
+//! Long-lived secret key material is held in [`SecretBytes`]. With `std` on
+//! native targets it lives in a `secrets::SecretBox`: libsodium guarded pages
+//! that are `mlock`ed, surrounded by guard pages, `mprotect`ed to no access
+//! except while borrowed, and zeroized when freed. Elsewhere (`no_std`,
+//! wasm) it falls back to a heap allocation zeroized on drop.
+//!
+//! [`HybridSecretKey`] keeps both halves of a hybrid private key this way and
+//! only rebuilds the Dilithium3 and Ed25519 signing keys on the stack for the
+//! duration of one signature.
+
+use crate::hybrid::{HybridPublicKey, HybridSignature};
+use crate::CryptoError;
+use alloc::vec::Vec;
+use core::fmt;
+use crystals_dilithium::dilithium3::{
+    Keypair as DilithiumKeypair, SecretKey as DilithiumSecretKey, PUBLICKEYBYTES, SECRETKEYBYTES,
+};
+use curve25519_dalek::{EdwardsPoint, Scalar};
+use ed25519_dalek::hazmat::{raw_sign, ExpandedSecretKey};
+use ed25519_dalek::{Signer, SigningKey, VerifyingKey, SECRET_KEY_LENGTH};
+use sha2::Sha512;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Length of [`HybridSecretKey::to_bytes`] with an RFC 8032 Ed25519 secret key
+pub const HYBRID_SECRET_KEY_BYTES: usize = PUBLICKEYBYTES + SECRETKEYBYTES + SECRET_KEY_LENGTH;
+
+/// Length of an expanded Ed25519 secret: scalar followed by nonce prefix
+pub const EXPANDED_SECRET_KEY_BYTES: usize = 64;
+
+/// Length of [`HybridSecretKey::to_bytes`] with an expanded Ed25519 secret
+pub const HYBRID_EXPANDED_SECRET_KEY_BYTES: usize = PUBLICKEYBYTES + SECRETKEYBYTES + EXPANDED_SECRET_KEY_BYTES;
+
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+type Storage<const N: usize> = std::sync::Mutex<secrets::SecretBox<[u8; N]>>;
+
+#[cfg(not(all(feature = "std", not(target_arch = "wasm32"))))]
+type Storage<const N: usize> = alloc::boxed::Box<[u8; N]>;
+
+/// Fixed-size secret in locked memory where available, zeroized on drop
+pub struct SecretBytes<const N: usize> {
+    storage: Storage<N>,
+}
+
+impl<const N: usize> SecretBytes<N> {
+    /// Allocate and fill the secret in place, without an intermediate copy
+    pub fn new(init: impl FnOnce(&mut [u8; N])) -> Self {
+        #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+        let storage = std::sync::Mutex::new(secrets::SecretBox::new(init));
+        #[cfg(not(all(feature = "std", not(target_arch = "wasm32"))))]
+        let storage = {
+            let mut bytes = alloc::boxed::Box::new([0u8; N]);
+            init(&mut bytes);
+            bytes
+        };
+        Self { storage }
+    }
+
+    /// Copy `bytes` into a new secret
+    pub fn from_bytes(bytes: &[u8; N]) -> Self {
+        Self::new(|secret| secret.copy_from_slice(bytes))
+    }
+
+    /// Run `f` with read access to the secret
+    pub fn expose<R>(&self, f: impl FnOnce(&[u8; N]) -> R) -> R {
+        #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+        {
+            let guard = self.storage.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+            let secret = guard.borrow();
+            f(&secret)
+        }
+        #[cfg(not(all(feature = "std", not(target_arch = "wasm32"))))]
+        f(&self.storage)
+    }
+
+    /// Whether this build keeps secrets in locked, guarded pages
+    pub const fn is_locked() -> bool {
+        cfg!(all(feature = "std", not(target_arch = "wasm32")))
+    }
+}
+
+#[cfg(not(all(feature = "std", not(target_arch = "wasm32"))))]
+impl<const N: usize> Drop for SecretBytes<N> {
+    fn drop(&mut self) {
+        self.storage.zeroize();
+    }
+}
+
+impl<const N: usize> fmt::Debug for SecretBytes<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes<{}>([REDACTED])", N)
+    }
+}
+
+/// Hybrid private key material: Dilithium3 and Ed25519 secrets in [`SecretBytes`]
+pub struct HybridSecretKey {
+    public: HybridPublicKey,
+    dilithium: SecretBytes<SECRETKEYBYTES>,
+    ed25519: Ed25519Secret,
+}
+
+/// Ed25519 half of a [`HybridSecretKey`]
+enum Ed25519Secret {
+    /// RFC 8032 secret key, expanded with SHA-512 for each signature
+    Seed(SecretBytes<SECRET_KEY_LENGTH>),
+    /// Scalar and nonce prefix, for keys from [`crate::hd`] derivation
+    Expanded(SecretBytes<EXPANDED_SECRET_KEY_BYTES>),
+}
+
+impl HybridSecretKey {
+    /// Take ownership of freshly generated keypairs, scrubbing the originals
+    pub fn from_keypairs(mut dilithium: DilithiumKeypair, ed25519: SigningKey) -> Self {
+        let public = HybridPublicKey::from_components(dilithium.public.to_bytes(), ed25519.verifying_key());
+        let secret = Self {
+            public,
+            dilithium: SecretBytes::from_bytes(&dilithium.secret.bytes),
+            ed25519: Ed25519Secret::Seed(SecretBytes::new(|secret| secret.copy_from_slice(ed25519.as_bytes()))),
+        };
+        dilithium.secret.bytes.zeroize();
+        // `SigningKey` zeroizes itself on drop
+        secret
+    }
+
+    /// Take ownership of a Dilithium3 keypair and an expanded Ed25519 key
+    ///
+    /// `ed25519` is a canonical scalar followed by a 32-byte nonce prefix.
+    /// The prefix must be unique to this scalar: reusing it with another
+    /// scalar leaks both keys.
+    pub fn from_expanded(mut dilithium: DilithiumKeypair, ed25519: &[u8; EXPANDED_SECRET_KEY_BYTES]) -> Result<Self, CryptoError> {
+        let secret = SecretBytes::from_bytes(ed25519);
+        let ed25519_public = secret.expose(expanded_public);
+        let result = ed25519_public.map(|ed25519_public| Self {
+            public: HybridPublicKey::from_components(dilithium.public.to_bytes(), ed25519_public),
+            dilithium: SecretBytes::from_bytes(&dilithium.secret.bytes),
+            ed25519: Ed25519Secret::Expanded(secret),
+        });
+        dilithium.secret.bytes.zeroize();
+        result
+    }
+
+    /// Parse `Dilithium3 public key || Dilithium3 secret key || Ed25519 secret`
+    ///
+    /// The Ed25519 secret is either a 32-byte RFC 8032 secret key
+    /// ([`HYBRID_SECRET_KEY_BYTES`] in total) or an expanded scalar and nonce
+    /// prefix ([`HYBRID_EXPANDED_SECRET_KEY_BYTES`] in total).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != HYBRID_SECRET_KEY_BYTES && bytes.len() != HYBRID_EXPANDED_SECRET_KEY_BYTES {
+            return Err(CryptoError::InvalidKey {
+                details: "Invalid hybrid secret key length".into(),
+            });
+        }
+        let (dilithium_public, rest) = bytes.split_at(PUBLICKEYBYTES);
+        let (dilithium_secret, ed25519_secret) = rest.split_at(SECRETKEYBYTES);
+
+        let dilithium = SecretBytes::new(|secret| secret.copy_from_slice(dilithium_secret));
+        let (ed25519, ed25519_public) = if ed25519_secret.len() == SECRET_KEY_LENGTH {
+            let secret = SecretBytes::new(|secret| secret.copy_from_slice(ed25519_secret));
+            let public = secret.expose(|secret| SigningKey::from_bytes(secret).verifying_key());
+            (Ed25519Secret::Seed(secret), public)
+        } else {
+            let secret = SecretBytes::new(|secret| secret.copy_from_slice(ed25519_secret));
+            let public = secret.expose(expanded_public)?;
+            (Ed25519Secret::Expanded(secret), public)
+        };
+        let mut public = [0u8; PUBLICKEYBYTES];
+        public.copy_from_slice(dilithium_public);
+
+        Ok(Self {
+            public: HybridPublicKey::from_components(public, ed25519_public),
+            dilithium,
+            ed25519,
+        })
+    }
+
+    /// Encode as `Dilithium3 public key || Dilithium3 secret key || Ed25519 secret`
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(HYBRID_EXPANDED_SECRET_KEY_BYTES));
+        bytes.extend_from_slice(self.public.dilithium_public());
+        self.dilithium.expose(|secret| bytes.extend_from_slice(secret));
+        match &self.ed25519 {
+            Ed25519Secret::Seed(secret) => secret.expose(|secret| bytes.extend_from_slice(secret)),
+            Ed25519Secret::Expanded(secret) => secret.expose(|secret| bytes.extend_from_slice(secret)),
+        }
+        bytes
+    }
+
+    /// Public half of this key
+    pub fn public(&self) -> &HybridPublicKey {
+        &self.public
+    }
+
+    /// Sign `message` with both halves
+    pub fn sign(&self, message: &[u8]) -> HybridSignature {
+        let dilithium = self.dilithium.expose(|secret| {
+            let mut key = DilithiumSecretKey { bytes: *secret };
+            let signature = key.sign(message);
+            key.bytes.zeroize();
+            signature
+        });
+        let ed25519 = match &self.ed25519 {
+            Ed25519Secret::Seed(secret) => secret.expose(|secret| SigningKey::from_bytes(secret).sign(message)),
+            Ed25519Secret::Expanded(secret) => secret.expose(|secret| {
+                let key = expanded_key(secret).expect("expanded scalar validated on construction");
+                raw_sign::<Sha512>(&key, message, self.public.ed25519_public())
+            }),
+        };
+        HybridSignature::from_components(dilithium, ed25519)
+    }
+}
+
+/// Split `scalar || prefix`, rejecting non-canonical and zero scalars
+fn expanded_key(bytes: &[u8; EXPANDED_SECRET_KEY_BYTES]) -> Result<ExpandedSecretKey, CryptoError> {
+    let invalid = || CryptoError::InvalidKey {
+        details: "Invalid expanded Ed25519 scalar".into(),
+    };
+    let (scalar, prefix) = bytes.split_at(32);
+    let mut scalar_bytes = Zeroizing::new([0u8; 32]);
+    scalar_bytes.copy_from_slice(scalar);
+    let scalar = Option::<Scalar>::from(Scalar::from_canonical_bytes(*scalar_bytes)).ok_or_else(invalid)?;
+    if scalar == Scalar::ZERO {
+        return Err(invalid());
+    }
+    let mut hash_prefix = [0u8; 32];
+    hash_prefix.copy_from_slice(prefix);
+    Ok(ExpandedSecretKey { scalar, hash_prefix })
+}
+
+fn expanded_public(bytes: &[u8; EXPANDED_SECRET_KEY_BYTES]) -> Result<VerifyingKey, CryptoError> {
+    let key = expanded_key(bytes)?;
+    Ok(VerifyingKey::from(EdwardsPoint::mul_base(&key.scalar)))
+}
+
+impl fmt::Debug for HybridSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HybridSecretKey").field("public", &self.public).finish_non_exhaustive()
+    }
+}
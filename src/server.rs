@@ -0,0 +1,369 @@
+//! # HTTP Signing Service
+//!
+//! A small HTTP/JSON API for services that want hybrid signatures without
+//! linking this crate. It is meant to listen on localhost only.
+//!
+//! | Method | Path                          | Auth   | Body / Response                                   |
+//! |--------|-------------------------------|--------|---------------------------------------------------|
+//! | GET    | `/v1/health`                  | none   | `{"status":"ok","keys":N}`                        |
+//! | GET    | `/v1/keys/{name}/public-key`  | bearer | `PublicKey`                                       |
+//! | POST   | `/v1/keys/{name}/sign`        | bearer | `{"message":b64}` → `{"signature":Signature}`      |
+//! | POST   | `/v1/verify`                  | none   | `{"public_key","message":b64,"signature"}` → `{"valid",..}` |
+//! | POST   | `/v1/verify/batch`            | none   | `{"items":[..]}` → `{"results":[{"valid",..}]}`   |
+//!
+//! Key endpoints require `Authorization: Bearer <token>` where the SHA-256 of
+//! the token is one of the hashes registered for that key; an unknown key name
+//! is answered with the same `401 unauthorized`. A signature that does not
+//! verify is a result, `{"valid":false,"error":{..}}` with status 200, not an
+//! error. Errors are returned as `{"error":{"code":"...","message":"..."}}`
+//! with a status derived from the underlying [`CryptoError`].
+//!
+//! Signing and verification run on Tokio's blocking pool so that Dilithium
+//! operations do not stall the async executor.
+
+use crate::constant_time;
+use crate::keystore::Keystore;
+use crate::{CryptoError, PrivateKey, PublicKey, Signature};
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{DefaultBodyLimit, Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use log::{debug, info};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Service limits
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Largest accepted request body in bytes
+    pub max_body_bytes: usize,
+    /// Largest number of items in one batch verification
+    pub max_batch_items: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 2 * 1024 * 1024,
+            max_batch_items: 64,
+        }
+    }
+}
+
+struct ServiceKey {
+    private_key: PrivateKey,
+    public_key: PublicKey,
+    token_hashes: Vec<[u8; 32]>,
+}
+
+struct ServiceState {
+    keystore: Keystore,
+    keys: BTreeMap<String, ServiceKey>,
+    config: ServerConfig,
+}
+
+/// Signing service holding decrypted keystore keys
+pub struct SigningService {
+    state: ServiceState,
+}
+
+/// SHA-256 of an authorization token, as registered with [`SigningService::add_key`]
+pub fn token_hash(token: &str) -> [u8; 32] {
+    use sha2::{Sha256, Digest};
+
+    Sha256::digest(token.as_bytes()).into()
+}
+
+impl SigningService {
+    /// Create a service that signs with keys from `keystore`
+    pub fn new(keystore: Keystore, config: ServerConfig) -> Self {
+        Self {
+            state: ServiceState {
+                keystore,
+                keys: BTreeMap::new(),
+                config,
+            },
+        }
+    }
+
+    /// Decrypt a keystore key and authorize the given token hashes to sign with it
+    pub fn add_key(&mut self, name: &str, passphrase: &[u8], token_hashes: Vec<[u8; 32]>) -> Result<(), CryptoError> {
+        if token_hashes.is_empty() {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Key '{}' needs at least one authorization token", name),
+            });
+        }
+        let private_key = self.state.keystore.load_private_key(name, passphrase)?;
+        let public_key = private_key.public_key()?;
+        self.state.keys.insert(name.to_string(), ServiceKey { private_key, public_key, token_hashes });
+        Ok(())
+    }
+
+    /// Build the HTTP router
+    pub fn router(self) -> Router {
+        let max_body_bytes = self.state.config.max_body_bytes;
+        Router::new()
+            .route("/v1/health", get(health))
+            .route("/v1/keys/:name/public-key", get(public_key))
+            .route("/v1/keys/:name/sign", post(sign))
+            .route("/v1/verify", post(verify))
+            .route("/v1/verify/batch", post(verify_batch))
+            .fallback(not_found)
+            .layer(DefaultBodyLimit::max(max_body_bytes))
+            .with_state(Arc::new(self.state))
+    }
+
+    /// Serve requests on a bound listener until the future is dropped
+    pub async fn serve(self, listener: tokio::net::TcpListener) -> Result<(), CryptoError> {
+        if let Ok(addr) = listener.local_addr() {
+            info!("Signing service listening on http://{}", addr);
+        }
+        axum::serve(listener, self.router())
+            .await
+            .map_err(|e| CryptoError::InvalidOperation {
+                details: format!("HTTP server failed: {}", e),
+            })
+    }
+}
+
+/// Structured API error
+#[derive(Debug)]
+pub struct ApiError {
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+}
+
+/// Body of every error response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorBody {
+    pub error: ErrorDetail,
+}
+
+/// Error code and message, as serialized in error responses and batch results
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErrorDetail {
+    pub code: String,
+    pub message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self { status, code, message: message.into() }
+    }
+}
+
+impl From<CryptoError> for ApiError {
+    fn from(error: CryptoError) -> Self {
+        let (status, code) = match &error {
+            CryptoError::InvalidKey { .. } => (StatusCode::BAD_REQUEST, "invalid_key"),
+            CryptoError::SignatureVerification { .. } => (StatusCode::UNPROCESSABLE_ENTITY, "signature_verification_failed"),
+            CryptoError::MalformedEncoding { .. } => (StatusCode::BAD_REQUEST, "malformed_encoding"),
+            CryptoError::UnsupportedAlgorithm(_) => (StatusCode::BAD_REQUEST, "unsupported_algorithm"),
+            CryptoError::InvalidOperation { .. } => (StatusCode::BAD_REQUEST, "invalid_operation"),
+            CryptoError::KeyUsageExceeded { .. } => (StatusCode::FORBIDDEN, "key_usage_exceeded"),
+            CryptoError::KeyNotFound { .. } => (StatusCode::NOT_FOUND, "key_not_found"),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+        };
+        Self::new(status, code, error.to_string())
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(rejection: JsonRejection) -> Self {
+        let code = if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE {
+            "payload_too_large"
+        } else {
+            "invalid_request"
+        };
+        Self::new(rejection.status(), code, rejection.body_text())
+    }
+}
+
+impl From<ApiError> for ErrorDetail {
+    fn from(error: ApiError) -> Self {
+        Self { code: error.code.to_string(), message: error.message }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = ErrorBody {
+            error: ErrorDetail { code: self.code.to_string(), message: self.message },
+        };
+        (self.status, Json(body)).into_response()
+    }
+}
+
+type SharedState = Arc<ServiceState>;
+
+#[derive(Serialize)]
+struct HealthResponse {
+    status: &'static str,
+    keys: usize,
+}
+
+/// Body of `POST /v1/keys/{name}/sign`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignRequest {
+    /// Base64-encoded message
+    pub message: String,
+}
+
+/// Response of `POST /v1/keys/{name}/sign`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SignResponse {
+    pub signature: Signature,
+}
+
+/// Body of `POST /v1/verify` and each item of a batch
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyRequest {
+    pub public_key: PublicKey,
+    /// Base64-encoded message
+    pub message: String,
+    pub signature: Signature,
+}
+
+/// Response of `POST /v1/verify`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyResponse {
+    pub valid: bool,
+    /// Why the signature did not verify
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorDetail>,
+}
+
+/// Body of `POST /v1/verify/batch`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchVerifyRequest {
+    pub items: Vec<VerifyRequest>,
+}
+
+/// Result for one item of a batch verification
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchVerifyResult {
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorDetail>,
+}
+
+/// Response of `POST /v1/verify/batch`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BatchVerifyResponse {
+    pub results: Vec<BatchVerifyResult>,
+}
+
+async fn health(State(state): State<SharedState>) -> Json<HealthResponse> {
+    Json(HealthResponse { status: "ok", keys: state.keys.len() })
+}
+
+async fn not_found() -> ApiError {
+    ApiError::new(StatusCode::NOT_FOUND, "not_found", "No such endpoint")
+}
+
+async fn public_key(
+    State(state): State<SharedState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<PublicKey>, ApiError> {
+    let key = authorize(&headers, state.keys.get(&name))?;
+    Ok(Json(key.public_key.clone()))
+}
+
+async fn sign(
+    State(state): State<SharedState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    body: Result<Json<SignRequest>, JsonRejection>,
+) -> Result<Json<SignResponse>, ApiError> {
+    authorize(&headers, state.keys.get(&name))?;
+
+    let Json(request) = body?;
+    let message = decode_message(&request.message)?;
+
+    // The usage counter lives in the keystore files
+    let signature = blocking(move || {
+        state.keystore.record_usage(&name)?;
+        debug!("Signing {} bytes with '{}'", message.len(), name);
+        state.keys[&name].private_key.sign(&message)
+    }).await??;
+    Ok(Json(SignResponse { signature }))
+}
+
+async fn verify(body: Result<Json<VerifyRequest>, JsonRejection>) -> Result<Json<VerifyResponse>, ApiError> {
+    let Json(request) = body?;
+    let error = blocking(move || verify_one(&request)).await??;
+    Ok(Json(VerifyResponse { valid: error.is_none(), error }))
+}
+
+async fn verify_batch(
+    State(state): State<SharedState>,
+    body: Result<Json<BatchVerifyRequest>, JsonRejection>,
+) -> Result<Json<BatchVerifyResponse>, ApiError> {
+    let Json(request) = body?;
+    if request.items.len() > state.config.max_batch_items {
+        return Err(ApiError::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "batch_too_large",
+            format!("Batch of {} items exceeds limit of {}", request.items.len(), state.config.max_batch_items),
+        ));
+    }
+
+    // Malformed items fail on their own instead of failing the batch
+    let results = blocking(move || {
+        request.items.iter()
+            .map(|item| {
+                let error = verify_one(item).unwrap_or_else(|e| Some(e.into()));
+                BatchVerifyResult { valid: error.is_none(), error }
+            })
+            .collect()
+    }).await?;
+    Ok(Json(BatchVerifyResponse { results }))
+}
+
+/// Verify one request: `None` if the signature is valid, the reason if it is not
+///
+/// Malformed requests, such as bad base64 or truncated encodings, are errors.
+fn verify_one(request: &VerifyRequest) -> Result<Option<ErrorDetail>, ApiError> {
+    let message = decode_message(&request.message)?;
+    match request.public_key.verify(&message, &request.signature) {
+        Ok(()) => Ok(None),
+        Err(e @ CryptoError::SignatureVerification { .. }) => Ok(Some(ApiError::from(e).into())),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Run `task` on the blocking pool
+async fn blocking<T: Send + 'static>(task: impl FnOnce() -> T + Send + 'static) -> Result<T, ApiError> {
+    tokio::task::spawn_blocking(task)
+        .await
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", format!("Blocking task failed: {}", e)))
+}
+
+fn decode_message(message: &str) -> Result<Vec<u8>, ApiError> {
+    BASE64.decode(message).map_err(|e| {
+        ApiError::new(StatusCode::BAD_REQUEST, "invalid_request", format!("Message is not valid base64: {}", e))
+    })
+}
+
+/// Check the bearer token of a key request against `key`
+///
+/// Unknown keys fail exactly like wrong tokens, so unauthenticated callers
+/// cannot enumerate key names.
+fn authorize<'a>(headers: &HeaderMap, key: Option<&'a ServiceKey>) -> Result<&'a ServiceKey, ApiError> {
+    let unauthorized = || ApiError::new(StatusCode::UNAUTHORIZED, "unauthorized", "Missing or invalid bearer token");
+
+    let token = headers.get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or_else(unauthorized)?;
+
+    let hash = token_hash(token.trim());
+    key.filter(|key| constant_time::contains(&key.token_hashes, &hash))
+        .ok_or_else(unauthorized)
+}
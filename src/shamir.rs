@@ -0,0 +1,401 @@
+//! # Seed Sharing
+//!
+//! Shamir secret sharing of a 32-byte master seed over GF(2^8), so that any
+//! `threshold` of `count` custodians can restore it and fewer learn nothing.
+//! Every seed byte is the constant term of its own random polynomial of
+//! degree `threshold - 1`; share `i` holds the 32 polynomial values at `x = i`.
+//! Field arithmetic uses the AES polynomial and runs in constant time.
+//!
+//! A share encodes as `af-share-` followed by the hex of
+//!
+//! ```text
+//! version (1) || group id (8) || threshold (1) || index (1) || value (32) || checksum (4)
+//! ```
+//!
+//! where the checksum is the first 4 bytes of
+//! `SHA-256("af-pqc/shamir/share" || preceding bytes)`. All shares of one split
+//! carry the same random group id, and [`combine`] refuses sets mixing
+//! groups or thresholds, repeating an index, or holding a share inconsistent
+//! with the others.
+//!
+//! A [`DealtShare`] optionally carries the dealer's hybrid signature over the
+//! share, checked by [`combine_dealt`] before any share is used.
+
+use crate::clock::Clock;
+use crate::{CryptoError, Fingerprint, PrivateKey, PublicKey, Signature};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+/// Current share encoding version
+pub const SHARE_VERSION: u8 = 1;
+
+/// Length of a share group identifier
+pub const GROUP_ID_BYTES: usize = 8;
+
+/// Length of an encoded share
+pub const SHARE_BYTES: usize = 1 + GROUP_ID_BYTES + 1 + 1 + 32 + CHECKSUM_BYTES;
+
+/// Largest number of shares of one seed
+pub const MAX_SHARES: u8 = 255;
+
+/// Prefix of the text form of a share
+pub const SHARE_PREFIX: &str = "af-share-";
+
+const CHECKSUM_BYTES: usize = 4;
+
+/// One custodian's share of a master seed
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SeedShare {
+    group_id: [u8; GROUP_ID_BYTES],
+    threshold: u8,
+    index: u8,
+    value: Zeroizing<[u8; 32]>,
+}
+
+impl SeedShare {
+    /// Identifier shared by all shares of one split
+    pub fn group_id(&self) -> [u8; GROUP_ID_BYTES] {
+        self.group_id
+    }
+
+    /// Number of shares needed to restore the seed
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// Position of this share, from 1
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Binary encoding, checksum included
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(SHARE_BYTES));
+        bytes.push(SHARE_VERSION);
+        bytes.extend_from_slice(&self.group_id);
+        bytes.push(self.threshold);
+        bytes.push(self.index);
+        bytes.extend_from_slice(self.value.as_slice());
+        let checksum = checksum(&bytes);
+        bytes.extend_from_slice(&checksum);
+        bytes
+    }
+
+    /// Parse a binary share, checking length, version and checksum
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != SHARE_BYTES {
+            return Err(CryptoError::MalformedEncoding {
+                details: format!("Share must be {} bytes, got {}", SHARE_BYTES, bytes.len()),
+            });
+        }
+        let (body, expected) = bytes.split_at(SHARE_BYTES - CHECKSUM_BYTES);
+        if !crate::constant_time::eq(&checksum(body), expected) {
+            return Err(CryptoError::MalformedEncoding {
+                details: "Share checksum mismatch: the share was mistyped or corrupted".into(),
+            });
+        }
+        if body[0] != SHARE_VERSION {
+            return Err(CryptoError::MalformedEncoding {
+                details: format!("Unsupported share version {}", body[0]),
+            });
+        }
+        let mut group_id = [0u8; GROUP_ID_BYTES];
+        group_id.copy_from_slice(&body[1..1 + GROUP_ID_BYTES]);
+        let (threshold, index) = (body[1 + GROUP_ID_BYTES], body[2 + GROUP_ID_BYTES]);
+        if threshold < 2 || index == 0 {
+            return Err(CryptoError::MalformedEncoding {
+                details: format!("Invalid share {} of threshold {}", index, threshold),
+            });
+        }
+        let mut value = Zeroizing::new([0u8; 32]);
+        value.copy_from_slice(&body[3 + GROUP_ID_BYTES..]);
+        Ok(Self { group_id, threshold, index, value })
+    }
+
+    /// Text encoding: [`SHARE_PREFIX`] followed by hex
+    pub fn encode(&self) -> Zeroizing<String> {
+        let mut text = Zeroizing::new(String::from(SHARE_PREFIX));
+        text.push_str(&Zeroizing::new(hex::encode(self.to_bytes().as_slice())));
+        text
+    }
+
+    /// Message the dealer signs for this share
+    pub fn dealer_message(&self) -> [u8; 32] {
+        Sha256::new()
+            .chain_update(b"af-pqc/shamir/dealer-v1")
+            .chain_update(self.to_bytes().as_slice())
+            .finalize()
+            .into()
+    }
+}
+
+impl FromStr for SeedShare {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let encoded = s.trim().strip_prefix(SHARE_PREFIX).ok_or_else(|| CryptoError::MalformedEncoding {
+            details: format!("Share must start with '{}'", SHARE_PREFIX),
+        })?;
+        let mut bytes = Zeroizing::new([0u8; SHARE_BYTES]);
+        hex::decode_to_slice(encoded, bytes.as_mut_slice()).map_err(|e| CryptoError::MalformedEncoding {
+            details: format!("Share must be '{}' and {} hex characters: {}", SHARE_PREFIX, SHARE_BYTES * 2, e),
+        })?;
+        Self::from_bytes(bytes.as_slice())
+    }
+}
+
+impl TryFrom<String> for SeedShare {
+    type Error = CryptoError;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        Zeroizing::new(text).parse()
+    }
+}
+
+impl From<SeedShare> for String {
+    fn from(share: SeedShare) -> Self {
+        share.encode().as_str().into()
+    }
+}
+
+impl fmt::Debug for SeedShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeedShare")
+            .field("group_id", &hex::encode(self.group_id))
+            .field("threshold", &self.threshold)
+            .field("index", &self.index)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Dealer signature over one share
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DealerSignature {
+    pub public_key: PublicKey,
+    pub signature: Signature,
+}
+
+/// A share as handed to its custodian, optionally signed by the dealer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DealtShare {
+    pub share: SeedShare,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dealer: Option<DealerSignature>,
+}
+
+impl DealtShare {
+    /// Share without a dealer signature
+    pub fn unsigned(share: SeedShare) -> Self {
+        Self { share, dealer: None }
+    }
+
+    /// Share signed by `dealer`, timestamped by `clock`
+    pub fn sign(share: SeedShare, dealer: &PrivateKey, clock: &(impl Clock + ?Sized)) -> Result<Self, CryptoError> {
+        let signature = dealer.sign_with_clock(&share.dealer_message(), clock)?;
+        Self::signed(share, dealer.public_key()?, signature)
+    }
+
+    /// Attach a dealer signature made elsewhere, checking it first
+    pub fn signed(share: SeedShare, public_key: PublicKey, signature: Signature) -> Result<Self, CryptoError> {
+        let dealt = Self { share, dealer: Some(DealerSignature { public_key, signature }) };
+        dealt.verify_dealer()?;
+        Ok(dealt)
+    }
+
+    /// Fingerprint of the dealer after checking its signature, `None` if unsigned
+    pub fn verify_dealer(&self) -> Result<Option<Fingerprint>, CryptoError> {
+        let Some(dealer) = &self.dealer else {
+            return Ok(None);
+        };
+        dealer.public_key.verify(&self.share.dealer_message(), &dealer.signature)
+            .map_err(|e| CryptoError::SignatureVerification {
+                details: format!("Dealer signature on share {} is invalid: {}", self.share.index, e),
+            })?;
+        Ok(Some(dealer.public_key.fingerprint()))
+    }
+}
+
+/// Split `seed` into `count` shares, any `threshold` of which restore it
+#[cfg(feature = "os-rng")]
+pub fn split(seed: &[u8; 32], threshold: u8, count: u8) -> Result<Vec<SeedShare>, CryptoError> {
+    split_with_rng(seed, threshold, count, &mut rand_core::OsRng)
+}
+
+/// Split `seed` into `count` shares using `rng` for the polynomials and group id
+pub fn split_with_rng<R: CryptoRngCore + ?Sized>(seed: &[u8; 32], threshold: u8, count: u8, rng: &mut R) -> Result<Vec<SeedShare>, CryptoError> {
+    if threshold < 2 || threshold > count {
+        return Err(CryptoError::InvalidOperation {
+            details: format!("Threshold must be between 2 and the share count, got {}-of-{}", threshold, count),
+        });
+    }
+
+    let mut group_id = [0u8; GROUP_ID_BYTES];
+    rng.fill_bytes(&mut group_id);
+    // coefficients[0] is the seed, the rest are random
+    let mut coefficients: Vec<Zeroizing<[u8; 32]>> = Vec::with_capacity(threshold.into());
+    coefficients.push(Zeroizing::new(*seed));
+    for _ in 1..threshold {
+        let mut coefficient = Zeroizing::new([0u8; 32]);
+        rng.fill_bytes(coefficient.as_mut_slice());
+        coefficients.push(coefficient);
+    }
+
+    Ok((1..=count)
+        .map(|index| {
+            let mut value = Zeroizing::new([0u8; 32]);
+            for coefficient in coefficients.iter().rev() {
+                for (byte, c) in value.iter_mut().zip(coefficient.iter()) {
+                    *byte = gf_mul(*byte, index) ^ c;
+                }
+            }
+            SeedShare { group_id, threshold, index, value }
+        })
+        .collect())
+}
+
+/// Restore the seed from at least `threshold` shares of one split
+///
+/// Shares beyond the threshold must agree with the polynomial the others
+/// define, so a share from another split or a corrupted share is refused
+/// rather than silently producing a wrong seed.
+pub fn combine(shares: &[SeedShare]) -> Result<Zeroizing<[u8; 32]>, CryptoError> {
+    let first = shares.first().ok_or_else(|| CryptoError::InvalidOperation {
+        details: "No shares given".into(),
+    })?;
+    for share in shares {
+        if share.group_id != first.group_id {
+            return Err(CryptoError::InvalidOperation {
+                details: format!(
+                    "Share {} belongs to group {}, not {}: shares from different splits cannot be combined",
+                    share.index, hex::encode(share.group_id), hex::encode(first.group_id)
+                ),
+            });
+        }
+        if share.threshold != first.threshold {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Share {} has threshold {}, others {}", share.index, share.threshold, first.threshold),
+            });
+        }
+    }
+    let mut indices: Vec<u8> = shares.iter().map(|share| share.index).collect();
+    indices.sort_unstable();
+    if let Some(pair) = indices.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(CryptoError::InvalidOperation {
+            details: format!("Share {} given more than once", pair[0]),
+        });
+    }
+    let threshold = usize::from(first.threshold);
+    if shares.len() < threshold {
+        return Err(CryptoError::InvalidOperation {
+            details: format!("Need {} shares to restore the seed, got {}", threshold, shares.len()),
+        });
+    }
+
+    let (basis, extra) = shares.split_at(threshold);
+    for share in extra {
+        let expected = interpolate(basis, share.index);
+        if !crate::constant_time::eq(expected.as_slice(), share.value.as_slice()) {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Share {} is inconsistent with the other shares", share.index),
+            });
+        }
+    }
+    Ok(interpolate(basis, 0))
+}
+
+/// Check dealer signatures, then restore the seed
+///
+/// Either every share or none is signed. Signed shares must all come from
+/// one dealer, and from `dealer` if given; an expected dealer requires
+/// signed shares. Returns the seed and the dealer fingerprint.
+pub fn combine_dealt(shares: &[DealtShare], dealer: Option<&Fingerprint>) -> Result<(Zeroizing<[u8; 32]>, Option<Fingerprint>), CryptoError> {
+    let mut signer: Option<Fingerprint> = None;
+    for (position, dealt) in shares.iter().enumerate() {
+        let fingerprint = dealt.verify_dealer()?;
+        if position > 0 && fingerprint.is_some() != signer.is_some() {
+            return Err(CryptoError::SignatureVerification {
+                details: "Some shares carry a dealer signature and others do not".into(),
+            });
+        }
+        if let (Some(fingerprint), Some(signer)) = (&fingerprint, &signer) {
+            if fingerprint != signer {
+                return Err(CryptoError::SignatureVerification {
+                    details: format!("Share {} was dealt by {}, not {}", dealt.share.index, fingerprint, signer),
+                });
+            }
+        }
+        signer = fingerprint;
+    }
+    if let Some(expected) = dealer {
+        match &signer {
+            Some(signer) if signer == expected => {}
+            Some(signer) => return Err(CryptoError::SignatureVerification {
+                details: format!("Shares were dealt by {}, expected {}", signer, expected),
+            }),
+            None => return Err(CryptoError::SignatureVerification {
+                details: format!("Shares carry no dealer signature, expected one by {}", expected),
+            }),
+        }
+    }
+
+    let plain: Vec<SeedShare> = shares.iter().map(|dealt| dealt.share.clone()).collect();
+    Ok((combine(&plain)?, signer))
+}
+
+/// Value at `x` of the polynomials through `shares`
+fn interpolate(shares: &[SeedShare], x: u8) -> Zeroizing<[u8; 32]> {
+    let mut result = Zeroizing::new([0u8; 32]);
+    for share in shares {
+        // Lagrange basis polynomial of this share, evaluated at x
+        let (mut numerator, mut denominator) = (1u8, 1u8);
+        for other in shares.iter().filter(|other| other.index != share.index) {
+            numerator = gf_mul(numerator, x ^ other.index);
+            denominator = gf_mul(denominator, share.index ^ other.index);
+        }
+        let weight = gf_mul(numerator, gf_inv(denominator));
+        for (byte, y) in result.iter_mut().zip(share.value.iter()) {
+            *byte ^= gf_mul(*y, weight);
+        }
+    }
+    result
+}
+
+fn checksum(body: &[u8]) -> [u8; CHECKSUM_BYTES] {
+    let digest = Sha256::new().chain_update(b"af-pqc/shamir/share").chain_update(body).finalize();
+    let mut checksum = [0u8; CHECKSUM_BYTES];
+    checksum.copy_from_slice(&digest[..CHECKSUM_BYTES]);
+    checksum
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1, without branches or tables
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+/// Multiplicative inverse in GF(2^8) as a^254
+fn gf_inv(a: u8) -> u8 {
+    let a2 = gf_mul(a, a);
+    let a4 = gf_mul(a2, a2);
+    let a8 = gf_mul(a4, a4);
+    let a16 = gf_mul(a8, a8);
+    let a32 = gf_mul(a16, a16);
+    let a64 = gf_mul(a32, a32);
+    let a128 = gf_mul(a64, a64);
+    // 254 = 128 + 64 + 32 + 16 + 8 + 4 + 2
+    [a64, a32, a16, a8, a4, a2].iter().fold(a128, |acc, &power| gf_mul(acc, power))
+}
@@ -0,0 +1,244 @@
+//! # Timestamp Tokens
+//!
+//! `Signature::created_at` is whatever the signer's clock said. A
+//! [`TimestampToken`] is a timestamp authority's statement that a signature
+//! existed at a given time, modelled on RFC 3161: the authority signs
+//!
+//! ```text
+//! "af-pqc/timestamp/v1" || version (1) || SHA-256 of the signature (32) || time (8, BE) || serial (8, BE)
+//! ```
+//!
+//! with its hybrid key and returns the token, which carries the authority's
+//! public key so it can be checked against a pinned fingerprint.
+//!
+//! Clients send a [`TimestampRequest`], which reveals only the digest, to a
+//! [`TimestampAuthority`] and keep the token next to the signature as a
+//! [`TimestampedSignature`]. [`LocalTimestampAuthority`] is an in-process
+//! authority with its own key, clock and serial counter, for tests and for
+//! running a TSA on top of the keystore.
+
+use crate::clock::Clock;
+use crate::{CryptoError, Fingerprint, PrivateKey, PublicKey, Signature};
+use alloc::format;
+use alloc::vec::Vec;
+use core::fmt;
+use core::sync::atomic::{AtomicU64, Ordering};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Current timestamp token format version
+pub const TIMESTAMP_VERSION: u8 = 1;
+
+/// A timestamp authority's signed statement of when a signature existed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimestampToken {
+    version: u8,
+    #[serde(with = "hex::serde")]
+    signature_digest: [u8; 32],
+    time: u64,
+    serial: u64,
+    authority: PublicKey,
+    authority_signature: Signature,
+}
+
+/// What a client sends to a timestamp authority: the digest of its signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampRequest {
+    #[serde(with = "hex::serde")]
+    signature_digest: [u8; 32],
+}
+
+/// Issuer of timestamp tokens
+pub trait TimestampAuthority {
+    /// Token for the signature digest in `request`
+    fn timestamp(&self, request: &TimestampRequest) -> Result<TimestampToken, CryptoError>;
+}
+
+/// Timestamp authority running in-process with a local key
+///
+/// Serials count up from the first one and are never reused by the same
+/// instance.
+pub struct LocalTimestampAuthority<C> {
+    key: PrivateKey,
+    clock: C,
+    next_serial: AtomicU64,
+}
+
+/// A signature together with a timestamp token covering it
+///
+/// Serialized as the signature's fields plus `timestamp`, so tools that only
+/// know [`Signature`] still read the signature out of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimestampedSignature {
+    #[serde(flatten)]
+    signature: Signature,
+    timestamp: TimestampToken,
+}
+
+impl TimestampRequest {
+    /// Request a token for `signature`
+    pub fn new(signature: &Signature) -> Self {
+        Self { signature_digest: signature_digest(signature) }
+    }
+
+    /// Digest the authority signs
+    pub fn signature_digest(&self) -> &[u8; 32] {
+        &self.signature_digest
+    }
+}
+
+impl<C: Clock> LocalTimestampAuthority<C> {
+    /// Authority signing with `key` at the times of `clock`, serials from 1
+    pub fn new(key: PrivateKey, clock: C) -> Self {
+        Self::with_first_serial(key, clock, 1)
+    }
+
+    /// Authority whose next token gets serial `serial`
+    pub fn with_first_serial(key: PrivateKey, clock: C, serial: u64) -> Self {
+        Self { key, clock, next_serial: AtomicU64::new(serial) }
+    }
+
+    /// Public key verifiers check tokens against
+    pub fn public_key(&self) -> Result<PublicKey, CryptoError> {
+        self.key.public_key()
+    }
+}
+
+impl<C: Clock> TimestampAuthority for LocalTimestampAuthority<C> {
+    fn timestamp(&self, request: &TimestampRequest) -> Result<TimestampToken, CryptoError> {
+        let serial = self.next_serial.fetch_add(1, Ordering::SeqCst);
+        TimestampToken::issue_for(&self.key, request, serial, &self.clock)
+    }
+}
+
+impl<C> fmt::Debug for LocalTimestampAuthority<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LocalTimestampAuthority")
+            .field("next_serial", &self.next_serial.load(Ordering::SeqCst))
+            .finish_non_exhaustive()
+    }
+}
+
+impl TimestampToken {
+    /// Token for `signature` at the current time of `clock`, signed by `authority`
+    pub fn issue(authority: &PrivateKey, signature: &Signature, serial: u64, clock: &(impl Clock + ?Sized)) -> Result<Self, CryptoError> {
+        Self::issue_for(authority, &TimestampRequest::new(signature), serial, clock)
+    }
+
+    /// Token answering `request` at the current time of `clock`, signed by `authority`
+    pub fn issue_for(authority: &PrivateKey, request: &TimestampRequest, serial: u64, clock: &(impl Clock + ?Sized)) -> Result<Self, CryptoError> {
+        let signature_digest = request.signature_digest;
+        let time = clock.now();
+        let authority_signature = authority.sign_with_clock(&token_message(&signature_digest, time, serial), clock)?;
+        Ok(Self {
+            version: TIMESTAMP_VERSION,
+            signature_digest,
+            time,
+            serial,
+            authority: authority.public_key()?,
+            authority_signature,
+        })
+    }
+
+    /// Unix time the authority vouches for
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Serial number assigned by the authority
+    pub fn serial(&self) -> u64 {
+        self.serial
+    }
+
+    /// Public key of the issuing authority
+    pub fn authority(&self) -> &PublicKey {
+        &self.authority
+    }
+
+    /// Check that the token covers `signature` and is signed by its authority
+    ///
+    /// Returns the fingerprint of the authority, which the caller must still
+    /// decide to trust.
+    pub fn verify(&self, signature: &Signature) -> Result<Fingerprint, CryptoError> {
+        if self.version != TIMESTAMP_VERSION {
+            return Err(CryptoError::MalformedEncoding {
+                details: format!("Unsupported timestamp token version {}", self.version),
+            });
+        }
+        if !crate::constant_time::eq(&signature_digest(signature), &self.signature_digest) {
+            return Err(CryptoError::SignatureVerification {
+                details: "Timestamp token covers a different signature".into(),
+            });
+        }
+        self.authority.verify(&token_message(&self.signature_digest, self.time, self.serial), &self.authority_signature)
+            .map_err(|e| CryptoError::SignatureVerification {
+                details: format!("Timestamp token {} is not signed by its authority: {}", self.serial, e),
+            })?;
+        Ok(self.authority.fingerprint())
+    }
+
+    /// Check the token for `signature` against the known key of its authority, returning its time
+    pub fn verify_with_authority(&self, signature: &Signature, authority: &PublicKey) -> Result<u64, CryptoError> {
+        let fingerprint = self.verify(signature)?;
+        if fingerprint != authority.fingerprint() {
+            return Err(CryptoError::SignatureVerification {
+                details: format!("Timestamp token {} was issued by {}, not {}", self.serial, fingerprint, authority.fingerprint()),
+            });
+        }
+        Ok(self.time)
+    }
+}
+
+impl TimestampedSignature {
+    /// Attach `token` to `signature`, checking that it covers the signature
+    pub fn new(signature: Signature, token: TimestampToken) -> Result<Self, CryptoError> {
+        token.verify(&signature)?;
+        Ok(Self { signature, timestamp: token })
+    }
+
+    /// Get a token for `signature` from `authority` and attach it
+    pub fn request(signature: Signature, authority: &(impl TimestampAuthority + ?Sized)) -> Result<Self, CryptoError> {
+        let token = authority.timestamp(&TimestampRequest::new(&signature))?;
+        Self::new(signature, token)
+    }
+
+    /// The timestamped signature
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// The attached token
+    pub fn token(&self) -> &TimestampToken {
+        &self.timestamp
+    }
+
+    /// Split into the signature and its token
+    pub fn into_parts(self) -> (Signature, TimestampToken) {
+        (self.signature, self.timestamp)
+    }
+
+    /// Check the token against the authority's key, returning the time it vouches for
+    pub fn verify_timestamp(&self, authority: &PublicKey) -> Result<u64, CryptoError> {
+        self.timestamp.verify_with_authority(&self.signature, authority)
+    }
+
+    /// Verify the signature by `signer` over `message` and its token by `authority`
+    pub fn verify(&self, message: &[u8], signer: &PublicKey, authority: &PublicKey) -> Result<u64, CryptoError> {
+        signer.verify(message, &self.signature)?;
+        self.verify_timestamp(authority)
+    }
+}
+
+fn signature_digest(signature: &Signature) -> [u8; 32] {
+    Sha256::new().chain_update(b"af-pqc/timestamp/signature").chain_update(&signature.bytes).finalize().into()
+}
+
+fn token_message(signature_digest: &[u8; 32], time: u64, serial: u64) -> Vec<u8> {
+    [
+        b"af-pqc/timestamp/v1".as_slice(),
+        &[TIMESTAMP_VERSION],
+        signature_digest,
+        &time.to_be_bytes(),
+        &serial.to_be_bytes(),
+    ].concat()
+}
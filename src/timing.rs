@@ -0,0 +1,144 @@
+//! # Timing Leakage Tests
+//!
+//! A dudect-style statistical test ("dude, is my code constant time?",
+//! Reparaz, Balasch and Verbauwhede 2017). An operation is timed on inputs
+//! from two classes, a fixed input and fresh random inputs, interleaved in
+//! random order. Welch's t-test then compares the two timing distributions,
+//! both as measured and cropped at several percentiles to cut off
+//! interrupts and other noise. A `|t|` above [`LEAKAGE_THRESHOLD`] means the
+//! timing depends on the input, reported as
+//! [`CryptoError::SideChannelAttack`].
+//!
+//! This module is test tooling, not part of the signing API: it only exists
+//! with the `fuzzing` feature and is hidden from the documentation. Results
+//! depend on the machine and on system load, so `tests/timing.rs` runs them
+//! as ignored tests:
+//! `cargo test --release --features fuzzing --test timing -- --ignored --test-threads 1`.
+
+use crate::CryptoError;
+use alloc::format;
+use alloc::vec::Vec;
+use rand_core::RngCore;
+use std::hint::black_box;
+use std::time::Instant;
+
+/// `|t|` above which timing is considered input-dependent
+pub const LEAKAGE_THRESHOLD: f64 = 4.5;
+
+/// Inputs prepared ahead of each round of measurements
+const BATCH_SIZE: usize = 1024;
+
+/// Cropping percentiles applied on top of the uncropped test
+const CROP_PERCENTILES: [f64; 5] = [0.5, 0.75, 0.9, 0.95, 0.99];
+
+/// Input class of one measurement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Class {
+    /// The same input every time
+    Fixed,
+    /// A fresh random input every time
+    Random,
+}
+
+/// Outcome of a timing test
+#[derive(Debug, Clone, Copy)]
+pub struct TimingReport {
+    /// Number of timed executions
+    pub samples: usize,
+    /// Largest `|t|` over the uncropped and cropped tests
+    pub max_t: f64,
+    /// Percentile of the crop giving [`TimingReport::max_t`], `None` for uncropped
+    pub crop: Option<f64>,
+}
+
+impl TimingReport {
+    /// Whether the fixed and random timings are distinguishable
+    pub fn leaks(&self) -> bool {
+        self.max_t > LEAKAGE_THRESHOLD
+    }
+
+    /// Fail with [`CryptoError::SideChannelAttack`] if `operation` leaks
+    pub fn check(&self, operation: &str) -> Result<(), CryptoError> {
+        if self.leaks() {
+            return Err(CryptoError::SideChannelAttack {
+                details: format!(
+                    "{} timing depends on its input (|t| = {:.2} over {} samples, threshold {})",
+                    operation, self.max_t, self.samples, LEAKAGE_THRESHOLD
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Time `operation` on `samples` inputs drawn by `input`, classes chosen by `rng`
+///
+/// Inputs are prepared in batches ahead of timing, so only `operation` is
+/// measured and input generation does not disturb the caches between runs.
+pub fn measure<I, O>(
+    samples: usize,
+    rng: &mut impl RngCore,
+    mut input: impl FnMut(Class, &mut dyn RngCore) -> I,
+    mut operation: impl FnMut(I) -> O,
+) -> TimingReport {
+    let mut timings: [Vec<f64>; 2] = [Vec::with_capacity(samples / 2), Vec::with_capacity(samples / 2)];
+    let mut remaining = samples;
+    while remaining > 0 {
+        let batch: Vec<(Class, I)> = (0..remaining.min(BATCH_SIZE))
+            .map(|_| {
+                let class = if rng.next_u32() & 1 == 0 { Class::Fixed } else { Class::Random };
+                (class, input(class, rng))
+            })
+            .collect();
+        remaining -= batch.len();
+        for (class, value) in batch {
+            let start = Instant::now();
+            black_box(operation(black_box(value)));
+            let elapsed = start.elapsed().as_nanos() as f64;
+            timings[class as usize].push(elapsed);
+        }
+    }
+    analyze(samples, &timings)
+}
+
+fn analyze(samples: usize, timings: &[Vec<f64>; 2]) -> TimingReport {
+    let mut report = TimingReport { samples, max_t: welch_t(&timings[0], &timings[1]).abs(), crop: None };
+
+    let mut pooled: Vec<f64> = timings.iter().flatten().copied().collect();
+    pooled.sort_by(f64::total_cmp);
+    if pooled.is_empty() {
+        return report;
+    }
+    for percentile in CROP_PERCENTILES {
+        let cutoff = pooled[((pooled.len() - 1) as f64 * percentile) as usize];
+        let cropped = |class: &Vec<f64>| class.iter().copied().filter(|&t| t <= cutoff).collect::<Vec<_>>();
+        let t = welch_t(&cropped(&timings[0]), &cropped(&timings[1])).abs();
+        if t > report.max_t {
+            report.max_t = t;
+            report.crop = Some(percentile);
+        }
+    }
+    report
+}
+
+/// Welch's t statistic, zero when either side has fewer than two samples
+fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+    let (Some((mean_a, var_a)), Some((mean_b, var_b))) = (mean_variance(a), mean_variance(b)) else {
+        return 0.0;
+    };
+    let denominator = (var_a / a.len() as f64 + var_b / b.len() as f64).sqrt();
+    if denominator == 0.0 {
+        return 0.0;
+    }
+    (mean_a - mean_b) / denominator
+}
+
+fn mean_variance(values: &[f64]) -> Option<(f64, f64)> {
+    if values.len() < 2 {
+        return None;
+    }
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / (n - 1.0);
+    Some((mean, variance))
+}
@@ -0,0 +1,295 @@
+//! # Trust Store
+//!
+//! A [`TrustStore`] holds the public keys a verifier trusts, each pinned by
+//! fingerprint under a label, with an optional validity window and the
+//! [`KeyUsage`]s it is trusted for:
+//!
+//! - `sign`: signatures by the key itself;
+//! - `certify`: keys certified by it through a [`KeyCertificate`] chain;
+//! - `timestamp`: timestamp tokens issued by it;
+//! - `witness`: witness logs signed by it (see [`crate::witness::LocalWitness`]).
+//!
+//! [`TrustStore::verify_bundle`] looks up the signer of a
+//! [`SignatureBundle`] by fingerprint, enforces these restrictions and
+//! reports which trusted identity the signature rests on. Validity is checked
+//! at the time vouched for by a trusted timestamp token if the bundle has
+//! one, and at the current time otherwise.
+//!
+//! With the `keystore` feature the store is kept in `$AF_HOME/trust.json`,
+//! replaced atomically with mode `0600` like keystore files.
+//!
+//! [`KeyCertificate`]: crate::bundle::KeyCertificate
+
+use crate::bundle::{BundleVerification, SignatureBundle};
+use crate::{CryptoError, Fingerprint, PublicKey, Signature};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// Current trust store format version
+pub const TRUST_STORE_VERSION: u8 = 1;
+
+/// Trust store file name inside `$AF_HOME`
+#[cfg(feature = "keystore")]
+pub const TRUST_STORE_FILE: &str = "trust.json";
+
+/// What a trusted key may vouch for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyUsage {
+    /// Signatures made by the key
+    Sign,
+    /// Certificates issued by the key
+    Certify,
+    /// Timestamp tokens issued by the key
+    Timestamp,
+    /// Witness logs signed by the key
+    Witness,
+}
+
+/// A pinned public key and the restrictions on trusting it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustedKey {
+    pub label: String,
+    pub public_key: PublicKey,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_before: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub not_after: Option<u64>,
+    pub usages: Vec<KeyUsage>,
+}
+
+/// Trusted public keys, unique by label and by fingerprint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrustStore {
+    version: u8,
+    keys: Vec<TrustedKey>,
+}
+
+/// Outcome of [`TrustStore::verify_bundle`]
+#[derive(Debug, Clone)]
+pub struct TrustedVerification<'a> {
+    /// Trusted key the signature rests on: the signer, or the key certifying it
+    pub identity: &'a TrustedKey,
+    /// Trusted authority of the bundle's timestamp token
+    pub timestamp_authority: Option<&'a TrustedKey>,
+    pub verification: BundleVerification,
+}
+
+impl TrustedKey {
+    /// Trust `public_key` under `label` for `usages`, with no validity window
+    pub fn new(label: &str, public_key: PublicKey, usages: &[KeyUsage]) -> Result<Self, CryptoError> {
+        validate_label(label)?;
+        public_key.hybrid()?;
+        if usages.is_empty() {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Trusted key '{}' needs at least one usage", label),
+            });
+        }
+        let mut usages = usages.to_vec();
+        usages.sort_unstable();
+        usages.dedup();
+        Ok(Self { label: label.into(), public_key, not_before: None, not_after: None, usages })
+    }
+
+    /// Restrict trust to the times from `not_before` to `not_after`, inclusive
+    pub fn with_validity(mut self, not_before: Option<u64>, not_after: Option<u64>) -> Result<Self, CryptoError> {
+        if let (Some(start), Some(end)) = (not_before, not_after) {
+            if start > end {
+                return Err(CryptoError::InvalidOperation {
+                    details: format!("Validity of '{}' starts at {} after it ends at {}", self.label, start, end),
+                });
+            }
+        }
+        self.not_before = not_before;
+        self.not_after = not_after;
+        Ok(self)
+    }
+
+    /// Fingerprint of the pinned key
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.public_key.fingerprint()
+    }
+
+    /// Whether the key is trusted for `usage`
+    pub fn allows(&self, usage: KeyUsage) -> bool {
+        self.usages.contains(&usage)
+    }
+
+    /// Whether `time` lies in the validity window
+    pub fn is_valid_at(&self, time: u64) -> bool {
+        self.not_before.is_none_or(|start| time >= start) && self.not_after.is_none_or(|end| time <= end)
+    }
+
+    /// Fail unless the key is trusted for `usage` at `time`
+    pub fn check(&self, usage: KeyUsage, time: u64) -> Result<(), CryptoError> {
+        if !self.allows(usage) {
+            return Err(CryptoError::SignatureVerification {
+                details: format!("Trusted key '{}' is not trusted for {}", self.label, usage),
+            });
+        }
+        if !self.is_valid_at(time) {
+            return Err(CryptoError::SignatureVerification {
+                details: format!("Trusted key '{}' is not valid at {}", self.label, time),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl TrustStore {
+    /// Empty trust store
+    pub fn new() -> Self {
+        Self { version: TRUST_STORE_VERSION, keys: Vec::new() }
+    }
+
+    /// Trusted keys, sorted by label
+    pub fn keys(&self) -> &[TrustedKey] {
+        &self.keys
+    }
+
+    /// Trusted key with this fingerprint
+    pub fn get(&self, fingerprint: &Fingerprint) -> Option<&TrustedKey> {
+        self.keys.iter().find(|key| key.fingerprint() == *fingerprint)
+    }
+
+    /// Add a trusted key, refusing duplicate labels and fingerprints
+    pub fn add(&mut self, key: TrustedKey) -> Result<(), CryptoError> {
+        validate_label(&key.label)?;
+        if let Some(existing) = self.keys.iter().find(|existing| existing.label == key.label) {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Label '{}' is already used for key {}", existing.label, existing.fingerprint()),
+            });
+        }
+        if let Some(existing) = self.get(&key.fingerprint()) {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Key {} is already trusted as '{}'", key.fingerprint(), existing.label),
+            });
+        }
+        let position = self.keys.partition_point(|existing| existing.label < key.label);
+        self.keys.insert(position, key);
+        Ok(())
+    }
+
+    /// Remove the key with this label, or else this fingerprint
+    pub fn remove(&mut self, label_or_fingerprint: &str) -> Result<TrustedKey, CryptoError> {
+        let fingerprint = Fingerprint::from_str(label_or_fingerprint).ok();
+        let position = self.keys.iter().position(|key| key.label == label_or_fingerprint)
+            .or_else(|| self.keys.iter().position(|key| Some(key.fingerprint()) == fingerprint))
+            .ok_or_else(|| CryptoError::KeyNotFound { name: label_or_fingerprint.into() })?;
+        Ok(self.keys.remove(position))
+    }
+
+    /// Verify a signature by the trusted key `signer` over `message`, as of `time`
+    pub fn verify(&self, message: &[u8], signature: &Signature, signer: &Fingerprint, time: u64) -> Result<&TrustedKey, CryptoError> {
+        let key = self.get(signer).ok_or_else(|| CryptoError::SignatureVerification {
+            details: format!("Signer {} is not in the trust store", signer),
+        })?;
+        key.check(KeyUsage::Sign, time)?;
+        key.public_key.verify(message, signature)?;
+        Ok(key)
+    }
+
+    /// Verify a bundle over `message` against the trusted keys
+    ///
+    /// The signer must be trusted to sign, or be certified through the chain
+    /// by a key trusted to certify. A timestamp token must come from a key
+    /// trusted for timestamps; its time, if present, replaces `now` for the
+    /// validity checks.
+    pub fn verify_bundle(&self, bundle: &SignatureBundle, message: &[u8], now: u64) -> Result<TrustedVerification<'_>, CryptoError> {
+        // An untrusted or forged token fails verification below, so its time is only ever used if it holds
+        let time = bundle.timestamp().map_or(now, |token| token.time());
+        let verification = bundle.verify_with(message, |fingerprint, usage| {
+            self.get(fingerprint).is_some_and(|key| key.allows(usage) && key.is_valid_at(time))
+        })?;
+
+        let trusted = |fingerprint: &Fingerprint| self.get(fingerprint).ok_or_else(|| CryptoError::SignatureVerification {
+            details: format!("Key {} is not in the trust store", fingerprint),
+        });
+        let identity = trusted(&verification.anchor)?;
+        let timestamp_authority = match bundle.timestamp() {
+            Some(token) => Some(trusted(&token.authority().fingerprint())?),
+            None => None,
+        };
+        Ok(TrustedVerification { identity, timestamp_authority, verification })
+    }
+}
+
+#[cfg(feature = "keystore")]
+impl TrustStore {
+    /// Trust store file in the keystore root (`$AF_HOME/trust.json`)
+    pub fn default_path() -> Result<std::path::PathBuf, CryptoError> {
+        Ok(crate::keystore::Keystore::default_root()?.join(TRUST_STORE_FILE))
+    }
+
+    /// Load the trust store at `path`, empty if the file does not exist
+    pub fn load(path: &std::path::Path) -> Result<Self, CryptoError> {
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+        let file: Self = crate::keystore::read_json(path)?;
+        if file.version != TRUST_STORE_VERSION {
+            return Err(CryptoError::Keystore {
+                details: format!("Unsupported trust store version {} in {}", file.version, path.display()),
+            });
+        }
+        // Re-add every key so a hand-edited file cannot hold duplicates
+        let mut store = Self::new();
+        for key in file.keys {
+            key.public_key.hybrid()?;
+            store.add(key)?;
+        }
+        Ok(store)
+    }
+
+    /// Atomically write the trust store to `path`
+    pub fn save(&self, path: &std::path::Path) -> Result<(), CryptoError> {
+        crate::keystore::write_json(path, self)
+    }
+}
+
+impl Default for TrustStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for KeyUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            KeyUsage::Sign => "sign",
+            KeyUsage::Certify => "certify",
+            KeyUsage::Timestamp => "timestamp",
+            KeyUsage::Witness => "witness",
+        })
+    }
+}
+
+impl FromStr for KeyUsage {
+    type Err = CryptoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sign" => Ok(KeyUsage::Sign),
+            "certify" => Ok(KeyUsage::Certify),
+            "timestamp" => Ok(KeyUsage::Timestamp),
+            "witness" => Ok(KeyUsage::Witness),
+            _ => Err(CryptoError::MalformedEncoding {
+                details: format!("Unknown key usage '{}': use sign, certify, timestamp or witness", s),
+            }),
+        }
+    }
+}
+
+/// Labels are 1-64 printable characters
+fn validate_label(label: &str) -> Result<(), CryptoError> {
+    if label.is_empty() || label.chars().count() > 64 || label.chars().any(char::is_control) || label.trim() != label {
+        return Err(CryptoError::InvalidOperation {
+            details: format!("Invalid trust label '{}': use 1-64 printable characters without surrounding spaces", label.escape_debug()),
+        });
+    }
+    Ok(())
+}
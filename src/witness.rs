@@ -0,0 +1,483 @@
+//! # Witness Commitments
+//!
+//! A witness keeps an append-only record of commitments to digests, such as
+//! the hash of a policy when it is created, and a clock that the record can
+//! vouch for. [`WitnessBackend`] abstracts the two operations key generation
+//! needs: proving that a digest was committed, and reading the witness time.
+//!
+//! [`generate_bound_key_with_rng`] checks that a policy hash was committed as
+//! [`EntryType::PolicyCreate`], generates a key timestamped by the witness
+//! clock and signs a [`WitnessBinding`] of the policy hash to the commitment.
+//!
+//! With the `keystore` feature the crate ships [`FileWitness`], a local
+//! commitment log kept as a JSON file. Every entry is chained to the previous
+//! one by hash and signed with the witness's hybrid key:
+//!
+//! ```text
+//! commitment = SHA-256("af-pqc/witness/v1" || sequence (8, BE) || entry type (1) || digest (32) || time (8, BE) || previous commitment (32))
+//! ```
+//!
+//! The log is opened against the fingerprint of the witness key the caller
+//! expects, so a log signed by any other key is refused. Commits reload the
+//! file under an exclusive lock before appending, so processes sharing a log
+//! never drop each other's entries. A commitment followed by a
+//! [`EntryType::PolicyRevoke`] of the same digest no longer verifies, and a
+//! digest is only committed again as the same type once it has been revoked,
+//! so bindings to a live commitment stay valid.
+//!
+//! [`LocalWitness`] is the log in `$AF_HOME/witness.json`, pinned to the key
+//! the trust store trusts for [`KeyUsage::Witness`], which the
+//! `witness-integration` feature makes the default clock and the backend of
+//! [`crate::generate_witness_bound_key`].
+
+use crate::clock::{Clock, WitnessClock};
+#[cfg(feature = "keystore")]
+use crate::trust::{KeyUsage, TrustStore};
+use crate::{CryptoError, Fingerprint, PrivateKey, PublicKey, Signature};
+use alloc::format;
+use alloc::vec::Vec;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Current witness log format version
+pub const WITNESS_VERSION: u8 = 1;
+
+/// Witness log file name inside `$AF_HOME`
+#[cfg(feature = "keystore")]
+pub const WITNESS_LOG_FILE: &str = "witness.json";
+
+/// Lock file serializing commits to the witness log inside `$AF_HOME`
+#[cfg(feature = "keystore")]
+pub const WITNESS_LOCK_FILE: &str = ".witness.json.lock";
+
+/// What a committed digest stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryType {
+    /// Hash of a newly created policy
+    PolicyCreate,
+    /// Hash of a policy replacing an earlier one
+    PolicyUpdate,
+    /// Hash of a policy that no longer applies
+    PolicyRevoke,
+}
+
+/// Evidence that a witness holds a commitment to a digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WitnessProof {
+    pub entry_type: EntryType,
+    pub sequence: u64,
+    pub time: u64,
+    #[serde(with = "hex::serde")]
+    pub commitment_hash: [u8; 32],
+    /// Fingerprint of the witness key that signed the commitment
+    pub witness: Fingerprint,
+}
+
+/// Source of witness commitments and witness time
+pub trait WitnessBackend: Send + Sync {
+    /// Proof that `digest` was committed as `entry_type`
+    fn verify_witness_commitment(&self, digest: [u8; 32], entry_type: EntryType) -> Result<WitnessProof, CryptoError>;
+
+    /// Witness time in seconds since the Unix epoch
+    fn current_timestamp(&self) -> u64;
+}
+
+impl<W: WitnessBackend + ?Sized> WitnessBackend for &W {
+    fn verify_witness_commitment(&self, digest: [u8; 32], entry_type: EntryType) -> Result<WitnessProof, CryptoError> {
+        (**self).verify_witness_commitment(digest, entry_type)
+    }
+
+    fn current_timestamp(&self) -> u64 {
+        (**self).current_timestamp()
+    }
+}
+
+/// A key's signature tying it to a witnessed policy commitment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WitnessBinding {
+    #[serde(with = "hex::serde")]
+    pub policy_hash: [u8; 32],
+    pub proof: WitnessProof,
+    pub signature: Signature,
+}
+
+impl WitnessBinding {
+    /// Message a bound key signs: the policy hash followed by the commitment hash
+    pub fn message(policy_hash: &[u8; 32], commitment_hash: &[u8; 32]) -> Vec<u8> {
+        [policy_hash.as_slice(), commitment_hash].concat()
+    }
+
+    /// Bind `key` to the commitment in `proof`, timestamped by `clock`
+    pub fn sign(policy_hash: [u8; 32], proof: WitnessProof, key: &PrivateKey, clock: &(impl Clock + ?Sized)) -> Result<Self, CryptoError> {
+        let signature = key.sign_with_clock(&Self::message(&policy_hash, &proof.commitment_hash), clock)?;
+        Ok(Self { policy_hash, proof, signature })
+    }
+
+    /// Check the binding signature by `public_key` and that `witness` still holds the commitment
+    pub fn verify(&self, public_key: &PublicKey, witness: &(impl WitnessBackend + ?Sized)) -> Result<(), CryptoError> {
+        public_key.verify(&Self::message(&self.policy_hash, &self.proof.commitment_hash), &self.signature)?;
+        let current = witness.verify_witness_commitment(self.policy_hash, self.proof.entry_type)?;
+        if current != self.proof {
+            return Err(CryptoError::SignatureVerification {
+                details: format!("Witness no longer holds commitment {} for this policy", self.proof.sequence),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Generate a keypair from `rng` bound to the commitment of `policy_hash` in `witness`
+///
+/// Fails unless the witness holds the policy hash as
+/// [`EntryType::PolicyCreate`]. The key is timestamped by the witness clock.
+pub fn generate_bound_key_with_rng<R: CryptoRngCore + ?Sized>(
+    policy_hash: &[u8; 32],
+    witness: &(impl WitnessBackend + ?Sized),
+    rng: &mut R,
+) -> Result<(PrivateKey, PublicKey, WitnessBinding), CryptoError> {
+    let proof = witness.verify_witness_commitment(*policy_hash, EntryType::PolicyCreate)
+        .map_err(|e| CryptoError::InvalidOperation {
+            details: format!("Witness commitment verification failed: {}", e),
+        })?;
+
+    let clock = WitnessClock(witness);
+    let (private_key, public_key) = PrivateKey::generate_with_rng(rng, &clock)?;
+    let binding = WitnessBinding::sign(*policy_hash, proof, &private_key, &clock)?;
+
+    // Verify binding integrity
+    binding.verify(&public_key, witness)?;
+
+    Ok((private_key, public_key, binding))
+}
+
+/// One signed commitment in a witness log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WitnessEntry {
+    pub sequence: u64,
+    pub entry_type: EntryType,
+    #[serde(with = "hex::serde")]
+    pub digest: [u8; 32],
+    pub time: u64,
+    #[serde(with = "hex::serde")]
+    pub previous: [u8; 32],
+    #[serde(with = "hex::serde")]
+    pub commitment_hash: [u8; 32],
+    pub signature: Signature,
+}
+
+impl WitnessEntry {
+    /// Commitment hash over the entry's fields
+    pub fn compute_commitment(&self) -> [u8; 32] {
+        commitment(self.sequence, self.entry_type, &self.digest, self.time, &self.previous)
+    }
+
+    #[cfg(feature = "keystore")]
+    fn proof(&self, witness: Fingerprint) -> WitnessProof {
+        WitnessProof {
+            entry_type: self.entry_type,
+            sequence: self.sequence,
+            time: self.time,
+            commitment_hash: self.commitment_hash,
+            witness,
+        }
+    }
+}
+
+fn commitment(sequence: u64, entry_type: EntryType, digest: &[u8; 32], time: u64, previous: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(b"af-pqc/witness/v1")
+        .chain_update(sequence.to_be_bytes())
+        .chain_update([entry_type as u8])
+        .chain_update(digest)
+        .chain_update(time.to_be_bytes())
+        .chain_update(previous)
+        .finalize()
+        .into()
+}
+
+/// Contents of a witness log file
+#[cfg(feature = "keystore")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WitnessLog {
+    version: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    witness: Option<PublicKey>,
+    entries: Vec<WitnessEntry>,
+}
+
+#[cfg(feature = "keystore")]
+impl WitnessLog {
+    fn new() -> Self {
+        Self { version: WITNESS_VERSION, witness: None, entries: Vec::new() }
+    }
+
+    /// Check the version, the hash chain and every entry's signature
+    fn validate(&self) -> Result<(), CryptoError> {
+        if self.version != WITNESS_VERSION {
+            return Err(CryptoError::MalformedEncoding {
+                details: format!("Unsupported witness log version {}", self.version),
+            });
+        }
+        let Some(witness) = &self.witness else {
+            return match self.entries.is_empty() {
+                true => Ok(()),
+                false => Err(CryptoError::MalformedEncoding {
+                    details: "Witness log has entries but no witness key".into(),
+                }),
+            };
+        };
+        witness.hybrid()?;
+
+        let mut previous = [0u8; 32];
+        let mut time = 0;
+        for (sequence, entry) in self.entries.iter().enumerate() {
+            if entry.sequence != sequence as u64 || entry.previous != previous || entry.time < time {
+                return Err(CryptoError::SignatureVerification {
+                    details: format!("Witness log is broken at entry {}", sequence),
+                });
+            }
+            if !crate::constant_time::eq(&entry.compute_commitment(), &entry.commitment_hash) {
+                return Err(CryptoError::SignatureVerification {
+                    details: format!("Witness entry {} does not match its commitment", sequence),
+                });
+            }
+            witness.verify(&entry.commitment_hash, &entry.signature)
+                .map_err(|e| CryptoError::SignatureVerification {
+                    details: format!("Witness entry {} is not signed by the witness: {}", sequence, e),
+                })?;
+            previous = entry.commitment_hash;
+            time = entry.time;
+        }
+        Ok(())
+    }
+
+    /// Latest `entry_type` commitment to `digest`, unless a later entry revokes it
+    fn find(&self, digest: &[u8; 32], entry_type: EntryType) -> Result<WitnessProof, CryptoError> {
+        let matches = |entry: &WitnessEntry, entry_type| entry.entry_type == entry_type && crate::constant_time::eq(&entry.digest, digest);
+        let position = self.entries.iter().rposition(|entry| matches(entry, entry_type));
+        let (Some(position), Some(witness)) = (position, &self.witness) else {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Witness holds no {:?} commitment to {}", entry_type, hex::encode(digest)),
+            });
+        };
+        if entry_type != EntryType::PolicyRevoke {
+            if let Some(revoke) = self.entries[position + 1..].iter().find(|entry| matches(entry, EntryType::PolicyRevoke)) {
+                return Err(CryptoError::InvalidOperation {
+                    details: format!("Witness revoked {} at entry {}", hex::encode(digest), revoke.sequence),
+                });
+            }
+        }
+        Ok(self.entries[position].proof(witness.fingerprint()))
+    }
+
+    fn latest_time(&self) -> u64 {
+        self.entries.last().map_or(0, |entry| entry.time)
+    }
+}
+
+/// Commitment log kept in a local JSON file
+///
+/// The log is pinned to the fingerprint of its witness key. Opened read-only
+/// it verifies commitments; with the pinned signing key it also appends them.
+/// Every commit holds an exclusive lock on a sibling `.<name>.lock` file,
+/// reloads the log and replaces the file atomically.
+#[cfg(feature = "keystore")]
+pub struct FileWitness<C = crate::clock::SystemClock> {
+    path: std::path::PathBuf,
+    witness: Fingerprint,
+    clock: C,
+    key: Option<PrivateKey>,
+    log: WitnessLog,
+}
+
+#[cfg(feature = "keystore")]
+impl FileWitness {
+    /// Open the log at `path` signed by `witness`, checking every entry; a missing file is an empty log
+    pub fn open(path: impl Into<std::path::PathBuf>, witness: &Fingerprint) -> Result<Self, CryptoError> {
+        Self::open_with_clock(path, witness, crate::clock::SystemClock)
+    }
+}
+
+#[cfg(feature = "keystore")]
+impl<C: Clock> FileWitness<C> {
+    /// Open the log at `path` signed by `witness`, timing new commitments with `clock`
+    pub fn open_with_clock(path: impl Into<std::path::PathBuf>, witness: &Fingerprint, clock: C) -> Result<Self, CryptoError> {
+        let path = path.into();
+        let log = load_log(&path, witness)?;
+        Ok(Self { path, witness: *witness, clock, key: None, log })
+    }
+
+    /// Commit new entries with `key`, which must be the pinned witness key
+    pub fn with_signing_key(mut self, key: PrivateKey) -> Result<Self, CryptoError> {
+        let public_key = key.public_key()?;
+        if public_key.fingerprint() != self.witness {
+            return Err(CryptoError::InvalidKey {
+                details: format!("Witness log {} is pinned to {}, not {}", self.path.display(), self.witness, public_key.fingerprint()),
+            });
+        }
+        self.log.witness = Some(public_key);
+        self.key = Some(key);
+        Ok(self)
+    }
+
+    /// Public key of the witness, once the log has one
+    pub fn public_key(&self) -> Option<&PublicKey> {
+        self.log.witness.as_ref()
+    }
+
+    /// Entries in commit order
+    pub fn entries(&self) -> &[WitnessEntry] {
+        &self.log.entries
+    }
+
+    /// Append a signed commitment to `digest`, persisting the log before returning
+    ///
+    /// The log is reloaded under the lock first, so entries committed by
+    /// other handles since it was opened are kept and chained to. Fails if
+    /// the log already holds a live `entry_type` commitment to `digest`, as
+    /// a newer one would invalidate the keys bound to it.
+    pub fn commit(&mut self, digest: [u8; 32], entry_type: EntryType) -> Result<WitnessProof, CryptoError> {
+        let Some(key) = &self.key else {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Witness log {} is open without a signing key", self.path.display()),
+            });
+        };
+
+        let _lock = crate::keystore::lock_file(&lock_path(&self.path))?;
+        let mut log = load_log(&self.path, &self.witness)?;
+        // The signing key matched the pin when it was set, so it signs the reloaded log too
+        log.witness = self.log.witness.take();
+        self.log = log;
+        if let Ok(live) = self.log.find(&digest, entry_type) {
+            return Err(CryptoError::InvalidOperation {
+                details: format!("Witness already holds {:?} commitment {} to {}", entry_type, live.sequence, hex::encode(digest)),
+            });
+        }
+
+        let sequence = self.log.entries.len() as u64;
+        let time = self.current_timestamp();
+        let previous = self.log.entries.last().map_or([0u8; 32], |entry| entry.commitment_hash);
+        let commitment_hash = commitment(sequence, entry_type, &digest, time, &previous);
+        let signature = key.sign_with_clock(&commitment_hash, &self.clock)?;
+        let entry = WitnessEntry { sequence, entry_type, digest, time, previous, commitment_hash, signature };
+        let proof = entry.proof(self.witness);
+
+        self.log.entries.push(entry);
+        if let Err(e) = crate::keystore::write_json(&self.path, &self.log) {
+            self.log.entries.pop();
+            return Err(e);
+        }
+        Ok(proof)
+    }
+}
+
+/// Read and check the log at `path`, refusing one signed by a key other than `witness`
+#[cfg(feature = "keystore")]
+fn load_log(path: &std::path::Path, witness: &Fingerprint) -> Result<WitnessLog, CryptoError> {
+    let log = if path.exists() { crate::keystore::read_json(path)? } else { WitnessLog::new() };
+    if let Some(signer) = &log.witness {
+        if signer.fingerprint() != *witness {
+            return Err(CryptoError::InvalidKey {
+                details: format!("Witness log {} is signed by {}, not the pinned witness {}", path.display(), signer.fingerprint(), witness),
+            });
+        }
+    }
+    log.validate()?;
+    Ok(log)
+}
+
+/// Lock file next to the log at `path`
+#[cfg(feature = "keystore")]
+fn lock_path(path: &std::path::Path) -> std::path::PathBuf {
+    let name = path.file_name().map_or_else(Default::default, |name| name.to_string_lossy());
+    path.with_file_name(format!(".{}.lock", name))
+}
+
+#[cfg(feature = "keystore")]
+impl<C: Clock> WitnessBackend for FileWitness<C> {
+    fn verify_witness_commitment(&self, digest: [u8; 32], entry_type: EntryType) -> Result<WitnessProof, CryptoError> {
+        self.log.find(&digest, entry_type)
+    }
+
+    /// The clock's time, never earlier than the latest entry
+    fn current_timestamp(&self) -> u64 {
+        self.clock.now().max(self.log.latest_time())
+    }
+}
+
+#[cfg(feature = "keystore")]
+impl<C> core::fmt::Debug for FileWitness<C> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FileWitness")
+            .field("path", &self.path)
+            .field("witness", &self.witness)
+            .field("entries", &self.log.entries.len())
+            .field("signing", &self.key.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// The witness log in `$AF_HOME/witness.json`
+///
+/// The log is pinned to the one key the trust store in `$AF_HOME` trusts for
+/// [`KeyUsage::Witness`]. Commitments are read from the file on every check,
+/// so entries committed by other processes are seen. Its time is the system
+/// time, never earlier than the latest entry it has read.
+#[cfg(feature = "keystore")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalWitness;
+
+#[cfg(feature = "keystore")]
+static LATEST_WITNESS_TIME: core::sync::atomic::AtomicU64 = core::sync::atomic::AtomicU64::new(0);
+
+#[cfg(feature = "keystore")]
+impl LocalWitness {
+    /// Path of the witness log (`$AF_HOME/witness.json`)
+    pub fn path() -> Result<std::path::PathBuf, CryptoError> {
+        Ok(crate::keystore::Keystore::default_root()?.join(WITNESS_LOG_FILE))
+    }
+
+    /// Fingerprint of the witness key pinned in the trust store
+    ///
+    /// Fails unless exactly one key currently valid in the trust store is
+    /// trusted for [`KeyUsage::Witness`].
+    pub fn trusted_key() -> Result<Fingerprint, CryptoError> {
+        let store = TrustStore::load(&TrustStore::default_path()?)?;
+        let now = crate::clock::SystemClock.now();
+        let mut witnesses = store.keys().iter().filter(|key| key.allows(KeyUsage::Witness) && key.is_valid_at(now));
+        match (witnesses.next(), witnesses.next()) {
+            (Some(key), None) => Ok(key.fingerprint()),
+            (None, _) => Err(CryptoError::InvalidOperation {
+                details: "No key in the trust store is trusted for witness; add one with `af-cli trust add --usage witness`".into(),
+            }),
+            (Some(first), Some(second)) => Err(CryptoError::InvalidOperation {
+                details: format!("Keys '{}' and '{}' are both trusted for witness; trust only one", first.label, second.label),
+            }),
+        }
+    }
+
+    /// Open the witness log, pinned to [`LocalWitness::trusted_key`]
+    pub fn open() -> Result<FileWitness, CryptoError> {
+        let witness = FileWitness::open(Self::path()?, &Self::trusted_key()?)?;
+        LATEST_WITNESS_TIME.fetch_max(witness.log.latest_time(), core::sync::atomic::Ordering::SeqCst);
+        Ok(witness)
+    }
+}
+
+#[cfg(feature = "keystore")]
+impl WitnessBackend for LocalWitness {
+    fn verify_witness_commitment(&self, digest: [u8; 32], entry_type: EntryType) -> Result<WitnessProof, CryptoError> {
+        Self::open()?.verify_witness_commitment(digest, entry_type)
+    }
+
+    fn current_timestamp(&self) -> u64 {
+        static LOADED: std::sync::Once = std::sync::Once::new();
+        LOADED.call_once(|| {
+            // An unreadable log only means no witnessed time to respect yet
+            let _ = Self::open();
+        });
+        crate::clock::SystemClock.now().max(LATEST_WITNESS_TIME.load(core::sync::atomic::Ordering::SeqCst))
+    }
+}
@@ -0,0 +1,184 @@
+//! Signing agent over a Unix socket, and `af-cli sign --agent` against it
+
+use af_pqc::agent::{Agent, AgentClient, MAX_FRAME_LEN};
+use af_pqc::clock::FixedClock;
+use af_pqc::keystore::{KeyPolicy, Keystore};
+use af_pqc::rng::DeterministicRng;
+use af_pqc::{PrivateKey, PublicKey, Signature};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+const PASSPHRASE: &str = "agent passphrase";
+const MESSAGE: &[u8] = b"signed through the agent";
+
+/// Keystore with one key named `alice`, and the agent socket path next to it
+fn keystore(policy: KeyPolicy) -> (tempfile::TempDir, Keystore, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let keystore = Keystore::open(dir.path().join("home")).unwrap();
+    let (private_key, _) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([1; 32]), &FixedClock::new(1_700_000_000)).unwrap();
+    keystore.insert("alice", &private_key, policy, PASSPHRASE.as_bytes()).unwrap();
+    let socket = dir.path().join("agent.sock");
+    (dir, keystore, socket)
+}
+
+/// Serve `agent` on `socket` in the background
+async fn start(agent: Agent, socket: &std::path::Path) {
+    let path = socket.to_path_buf();
+    tokio::spawn(async move { agent.serve(&path).await });
+    for _ in 0..100 {
+        if UnixStream::connect(socket).await.is_ok() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("agent did not start");
+}
+
+#[tokio::test]
+async fn signs_with_loaded_keys_and_counts_usage() {
+    let (_dir, keystore, socket) = keystore(KeyPolicy { max_usage: 1, not_after: None });
+    start(Agent::new(keystore.clone()), &socket).await;
+
+    let mut client = AgentClient::connect(&socket).await.unwrap();
+    assert!(client.list_keys().await.unwrap().is_empty());
+    assert!(client.add_key("alice", "wrong passphrase", None).await.is_err());
+    client.add_key("alice", PASSPHRASE, Some(3600)).await.unwrap();
+
+    let keys = client.list_keys().await.unwrap();
+    assert_eq!(keys.len(), 1);
+    assert!(keys[0].expires_in.unwrap() > 3500);
+    let signature = client.sign("alice", MESSAGE).await.unwrap();
+    client.public_key("alice").await.unwrap().verify(MESSAGE, &signature).unwrap();
+    assert_eq!(keystore.usage("alice").unwrap(), 1);
+
+    // The keystore policy still applies to keys held by the agent
+    let error = client.sign("alice", MESSAGE).await.unwrap_err();
+    assert!(error.to_string().contains("usage limit exceeded"), "{}", error);
+    assert!(client.sign("bob", MESSAGE).await.is_err());
+
+    client.remove_key("alice").await.unwrap();
+    assert!(client.remove_key("alice").await.is_err());
+}
+
+#[tokio::test]
+async fn lock_requires_the_same_passphrase() {
+    let (_dir, keystore, socket) = keystore(KeyPolicy::default());
+    let agent = Agent::new(keystore);
+    agent.add_key("alice", PASSPHRASE.as_bytes(), None).unwrap();
+    start(agent, &socket).await;
+
+    let mut client = AgentClient::connect(&socket).await.unwrap();
+    assert!(client.unlock("lock passphrase").await.is_err());
+    assert!(client.lock("").await.is_err());
+    client.lock("lock passphrase").await.unwrap();
+    assert!(client.lock("lock passphrase").await.is_err());
+
+    assert!(client.list_keys().await.unwrap().is_empty());
+    let error = client.sign("alice", MESSAGE).await.unwrap_err();
+    assert!(error.to_string().contains("locked"), "{}", error);
+    let error = client.unlock("wrong passphrase").await.unwrap_err();
+    assert!(error.to_string().contains("Incorrect lock passphrase"), "{}", error);
+
+    client.unlock("lock passphrase").await.unwrap();
+    assert_eq!(client.list_keys().await.unwrap().len(), 1);
+    client.sign("alice", MESSAGE).await.unwrap();
+}
+
+#[tokio::test]
+async fn reaper_forgets_expired_keys() {
+    let (_dir, keystore, socket) = keystore(KeyPolicy::default());
+    let agent = Agent::new(keystore);
+    agent.add_key("alice", PASSPHRASE.as_bytes(), Some(Duration::from_millis(200))).unwrap();
+    assert_eq!(agent.loaded_keys().unwrap(), ["alice"]);
+    start(agent.clone(), &socket).await;
+
+    // No client talks to the agent while the key expires
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(agent.loaded_keys().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn reaper_stops_with_the_server() {
+    let (_dir, keystore, socket) = keystore(KeyPolicy::default());
+    let agent = Agent::new(keystore);
+    agent.add_key("alice", PASSPHRASE.as_bytes(), Some(Duration::from_millis(200))).unwrap();
+    let (serving, path) = (agent.clone(), socket.clone());
+    let server = tokio::spawn(async move { serving.serve(&path).await });
+    for _ in 0..100 {
+        if UnixStream::connect(&socket).await.is_ok() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    // Once serving stops nothing in the background holds or touches the keys
+    server.abort();
+    assert!(server.await.unwrap_err().is_cancelled());
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert_eq!(agent.loaded_keys().unwrap(), ["alice"]);
+}
+
+#[tokio::test]
+async fn oversized_frames_close_the_connection() {
+    let (_dir, keystore, socket) = keystore(KeyPolicy::default());
+    let agent = Agent::new(keystore);
+    agent.add_key("alice", PASSPHRASE.as_bytes(), None).unwrap();
+    start(agent, &socket).await;
+
+    let mut stream = UnixStream::connect(&socket).await.unwrap();
+    stream.write_u32(MAX_FRAME_LEN + 1).await.unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    assert!(response.is_empty());
+
+    // Clients refuse to send frames the agent would reject
+    let mut client = AgentClient::connect(&socket).await.unwrap();
+    let error = client.sign("alice", &vec![0u8; MAX_FRAME_LEN as usize]).await.unwrap_err();
+    assert!(error.to_string().contains("Failed to encode frame"), "{}", error);
+    client.sign("alice", MESSAGE).await.unwrap();
+}
+
+#[tokio::test]
+async fn other_users_are_rejected() {
+    let (_dir, keystore, socket) = keystore(KeyPolicy::default());
+    let agent = Agent::new(keystore);
+    agent.add_key("alice", PASSPHRASE.as_bytes(), None).unwrap();
+    // SAFETY: getuid has no preconditions and cannot fail
+    let uid = unsafe { libc::getuid() };
+    start(agent.with_client_uid(uid.wrapping_add(1)), &socket).await;
+
+    let mut client = AgentClient::connect(&socket).await.unwrap();
+    let error = client.list_keys().await.unwrap_err();
+    assert!(error.to_string().contains("closed the connection") || error.to_string().contains("Failed to"), "{}", error);
+}
+
+#[tokio::test]
+async fn cli_signs_through_the_agent() {
+    let (dir, keystore, socket) = keystore(KeyPolicy::default());
+    let agent = Agent::new(keystore.clone());
+    agent.add_key("alice", PASSPHRASE.as_bytes(), None).unwrap();
+    start(agent, &socket).await;
+
+    let output = dir.path().join("signature.json");
+    let cli = tokio::process::Command::new(env!("CARGO_BIN_EXE_af-cli"))
+        .args(["sign", "--agent", "--key", "alice", "--message"])
+        .arg(std::str::from_utf8(MESSAGE).unwrap())
+        .arg("--output")
+        .arg(&output)
+        .env("AF_HOME", keystore.root())
+        .env("AF_AGENT_SOCK", &socket)
+        // The agent holds the key; the CLI must never ask for its passphrase
+        .env_remove("AF_PASSPHRASE")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .await
+        .unwrap();
+    assert!(cli.status.success(), "{}", String::from_utf8_lossy(&cli.stderr));
+
+    let signature: Signature = serde_json::from_slice(&std::fs::read(&output).unwrap()).unwrap();
+    let public_key: PublicKey = keystore.public_key("alice").unwrap();
+    public_key.verify(MESSAGE, &signature).unwrap();
+    assert_eq!(keystore.usage("alice").unwrap(), 1);
+}
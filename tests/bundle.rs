@@ -0,0 +1,107 @@
+//! Self-describing signature bundles verified against pinned fingerprints
+
+use af_pqc::bundle::{KeyCertificate, SignatureBundle, MAX_CHAIN_LENGTH};
+use af_pqc::clock::FixedClock;
+use af_pqc::rng::DeterministicRng;
+use af_pqc::timestamp::TimestampToken;
+use af_pqc::{CryptoError, PrivateKey};
+
+const RELEASE: &[u8] = b"release v2.4.1 sha256:5f0c...";
+
+fn key(seed: u8) -> PrivateKey {
+    PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([seed; 32]), &FixedClock::new(1_700_000_000)).unwrap().0
+}
+
+fn fingerprint(key: &PrivateKey) -> af_pqc::Fingerprint {
+    key.public_key().unwrap().fingerprint()
+}
+
+#[test]
+fn pinned_signers_verify_without_a_key_file() {
+    let clock = FixedClock::new(1_700_000_100);
+    let signer = key(1);
+    let bundle = SignatureBundle::sign(RELEASE, &signer, &clock).unwrap();
+
+    let json = serde_json::to_string(&bundle).unwrap();
+    assert!(!json.contains("chain") && !json.contains("timestamp"));
+    let bundle: SignatureBundle = serde_json::from_str(&json).unwrap();
+
+    let verification = bundle.verify(RELEASE, &[fingerprint(&key(9)), fingerprint(&signer)]).unwrap();
+    assert_eq!((verification.signer, verification.anchor), (fingerprint(&signer), fingerprint(&signer)));
+    assert_eq!((verification.chain_length, verification.timestamp), (0, None));
+
+    let error = bundle.verify(RELEASE, &[fingerprint(&key(9))]).unwrap_err();
+    assert!(error.to_string().contains("not trusted"), "{}", error);
+    assert!(bundle.verify(b"release v2.4.2", &[fingerprint(&signer)]).is_err());
+}
+
+#[test]
+fn certificate_chains_lead_to_a_pinned_root() {
+    let clock = FixedClock::new(1_700_000_100);
+    let (root, intermediate, service) = (key(1), key(2), key(3));
+    let to_service = KeyCertificate::issue(&intermediate, &service.public_key().unwrap(), &clock).unwrap();
+    let to_intermediate = KeyCertificate::issue(&root, &intermediate.public_key().unwrap(), &clock).unwrap();
+
+    let bundle = SignatureBundle::sign(RELEASE, &service, &clock).unwrap()
+        .with_certificate(to_service.clone()).unwrap()
+        .with_certificate(to_intermediate.clone()).unwrap();
+    let bundle: SignatureBundle = serde_json::from_str(&serde_json::to_string(&bundle).unwrap()).unwrap();
+
+    let verification = bundle.verify(RELEASE, &[fingerprint(&root)]).unwrap();
+    assert_eq!((verification.signer, verification.anchor, verification.chain_length), (fingerprint(&service), fingerprint(&root), 2));
+    // The walk stops at the first pinned key
+    assert_eq!(bundle.verify(RELEASE, &[fingerprint(&intermediate), fingerprint(&root)]).unwrap().chain_length, 1);
+
+    // Certificates in the wrong order, or for another key, break the chain
+    let swapped = SignatureBundle::sign(RELEASE, &service, &clock).unwrap()
+        .with_certificate(to_intermediate).unwrap()
+        .with_certificate(to_service.clone()).unwrap();
+    assert!(matches!(swapped.verify(RELEASE, &[fingerprint(&root)]), Err(CryptoError::SignatureVerification { .. })));
+    let stranger = SignatureBundle::sign(RELEASE, &key(4), &clock).unwrap().with_certificate(to_service).unwrap();
+    assert!(stranger.verify(RELEASE, &[fingerprint(&intermediate)]).is_err());
+}
+
+#[test]
+fn successor_keys_are_vouched_for_by_their_predecessor() {
+    let clock = FixedClock::new(1_700_000_100);
+    let (retired, successor) = (key(5), key(6));
+    let succession = KeyCertificate::issue(&retired, &successor.public_key().unwrap(), &clock).unwrap();
+    succession.verify(&successor.public_key().unwrap()).unwrap();
+    assert!(succession.verify(&retired.public_key().unwrap()).is_err());
+
+    let bundle = SignatureBundle::sign(RELEASE, &successor, &clock).unwrap().with_certificate(succession.clone()).unwrap();
+    assert_eq!(bundle.verify(RELEASE, &[fingerprint(&retired)]).unwrap().anchor, fingerprint(&retired));
+
+    let mut long = SignatureBundle::sign(RELEASE, &successor, &clock).unwrap();
+    for _ in 0..MAX_CHAIN_LENGTH {
+        long = long.with_certificate(succession.clone()).unwrap();
+    }
+    assert!(long.with_certificate(succession).is_err());
+}
+
+#[test]
+fn timestamps_must_come_from_a_pinned_authority() {
+    let (signer, authority) = (key(1), key(7));
+    let bundle = SignatureBundle::sign(RELEASE, &signer, &FixedClock::new(1_700_000_100)).unwrap();
+    let token = TimestampToken::issue(&authority, bundle.signature(), 42, &FixedClock::new(1_700_000_160)).unwrap();
+    assert_eq!((token.time(), token.serial()), (1_700_000_160, 42));
+    assert_eq!(token.verify(bundle.signature()).unwrap(), fingerprint(&authority));
+
+    let stamped = bundle.clone().with_timestamp(token.clone()).unwrap();
+    let stamped: SignatureBundle = serde_json::from_str(&serde_json::to_string(&stamped).unwrap()).unwrap();
+    let verification = stamped.verify(RELEASE, &[fingerprint(&signer), fingerprint(&authority)]).unwrap();
+    assert_eq!(verification.timestamp, Some(1_700_000_160));
+
+    let error = stamped.verify(RELEASE, &[fingerprint(&signer)]).unwrap_err();
+    assert!(error.to_string().contains("Timestamp authority"), "{}", error);
+
+    // A token for another signature is refused
+    let other = SignatureBundle::sign(RELEASE, &key(2), &FixedClock::new(1_700_000_100)).unwrap();
+    assert!(other.with_timestamp(token.clone()).is_err());
+
+    // So is a token whose time was changed after signing
+    let mut json = serde_json::to_value(&token).unwrap();
+    json["time"] = 1_600_000_000u64.into();
+    let backdated: TimestampToken = serde_json::from_value(json).unwrap();
+    assert!(matches!(backdated.verify(bundle.signature()), Err(CryptoError::SignatureVerification { .. })));
+}
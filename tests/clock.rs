@@ -0,0 +1,34 @@
+//! Timestamps on keys and signatures come from the injected clock
+
+use af_pqc::clock::{Clock, FixedClock, MonotonicClock};
+use af_pqc::{AlgorithmVersion, PrivateKey};
+
+#[test]
+fn key_generation_and_signing_use_injected_clock() {
+    let clock = FixedClock::new(1_700_000_000);
+    let (private_key, public_key) = PrivateKey::generate_with_clock(AlgorithmVersion::MandatoryHybrid, &clock).unwrap();
+
+    assert_eq!(private_key.created_at, 1_700_000_000);
+    assert_eq!(private_key.operation_id, 1_700_000_000);
+    assert_eq!(public_key.created_at, 1_700_000_000);
+    assert_eq!(private_key.key_id, "mandatory-hybrid-1700000000");
+
+    clock.advance(60);
+    let signature = private_key.sign_with_clock(b"clocked message", &clock).unwrap();
+    assert_eq!(signature.created_at, 1_700_000_060);
+    assert_eq!(signature.operation_id, 1_700_000_060);
+    public_key.verify(b"clocked message", &signature).unwrap();
+}
+
+#[test]
+fn monotonic_clock_never_goes_backwards() {
+    let source = FixedClock::new(1_000);
+    let clock = MonotonicClock::new(&source);
+    assert_eq!(clock.now(), 1_000);
+
+    source.set(900);
+    assert_eq!(clock.now(), 1_000);
+
+    source.set(1_200);
+    assert_eq!(clock.now(), 1_200);
+}
@@ -0,0 +1,36 @@
+//! Constant-time comparison helpers and key fingerprints
+
+use af_pqc::clock::FixedClock;
+use af_pqc::constant_time::{self, FINGERPRINT_BYTES};
+use af_pqc::rng::DeterministicRng;
+use af_pqc::{CryptoError, Fingerprint, PrivateKey};
+
+#[test]
+fn comparisons_match_ordinary_equality() {
+    assert!(constant_time::eq(b"token", b"token"));
+    assert!(!constant_time::eq(b"token", b"tokem"));
+    assert!(!constant_time::eq(b"token", b"token!"));
+
+    let set = [[1u8; 32], [2u8; 32], [3u8; 32]];
+    assert!(constant_time::contains(&set, &[3u8; 32]));
+    assert!(!constant_time::contains(&set, &[4u8; 32]));
+    assert!(!constant_time::contains::<[u8; 32]>(&[], &[1u8; 32]));
+}
+
+#[test]
+fn fingerprints_identify_key_bytes() {
+    let clock = FixedClock::new(1_700_000_000);
+    let (_, public_key) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([1; 32]), &clock).unwrap();
+    let (_, other) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([2; 32]), &clock).unwrap();
+
+    let mut relabeled = public_key.clone();
+    relabeled.created_at += 1;
+    assert_eq!(relabeled.fingerprint(), public_key.fingerprint());
+    assert_ne!(other.fingerprint(), public_key.fingerprint());
+
+    let fingerprint = public_key.fingerprint();
+    let text = fingerprint.to_string();
+    assert_eq!(text.len(), FINGERPRINT_BYTES * 2);
+    assert_eq!(text.parse::<Fingerprint>().unwrap(), fingerprint);
+    assert!(matches!("abc".parse::<Fingerprint>(), Err(CryptoError::MalformedEncoding { .. })));
+}
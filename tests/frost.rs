@@ -0,0 +1,311 @@
+//! FROST threshold signing of the Ed25519 half, with every guardian simulated in-process
+
+use af_pqc::clock::FixedClock;
+use af_pqc::frost::{self, dkg, DilithiumCustodian, DilithiumPolicy, Identifier, KeyPackage, PublicKeyPackage, SigningPackage};
+use af_pqc::rng::DeterministicRng;
+use af_pqc::CryptoError;
+use crystals_dilithium::dilithium3::Keypair as DilithiumKeypair;
+use rand_core::{CryptoRng, RngCore};
+use serde_json::json;
+use std::collections::BTreeMap;
+
+const MESSAGE: &[u8] = b"release v1.2.0";
+
+/// Replays fixed bytes, for test vectors that specify the randomness
+struct Scripted(Vec<u8>);
+
+impl Scripted {
+    fn new(chunks: &[&str]) -> Self {
+        Self(chunks.iter().flat_map(|chunk| hex::decode(chunk).unwrap()).collect())
+    }
+
+    /// Scalars drawn from 64 random bytes: the canonical encoding then zeros
+    fn scalars(scalars: &[&str]) -> Self {
+        Self(scalars.iter().flat_map(|scalar| [hex::decode(scalar).unwrap(), vec![0; 32]].concat()).collect())
+    }
+}
+
+impl RngCore for Scripted {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        assert!(self.0.len() >= dest.len(), "scripted randomness exhausted");
+        dest.copy_from_slice(&self.0[..dest.len()]);
+        self.0.drain(..dest.len());
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for Scripted {}
+
+fn id(value: u16) -> Identifier {
+    Identifier::new(value).unwrap()
+}
+
+/// Run the DKG among `max_signers` guardians
+fn run_dkg(max_signers: u16, min_signers: u16, rng: &mut DeterministicRng) -> (BTreeMap<Identifier, KeyPackage>, PublicKeyPackage) {
+    let ids: Vec<Identifier> = (1..=max_signers).map(id).collect();
+    let mut secrets = BTreeMap::new();
+    let mut broadcasts = BTreeMap::new();
+    for &identifier in &ids {
+        let (secret, package) = dkg::part1(identifier, max_signers, min_signers, rng).unwrap();
+        secrets.insert(identifier, secret);
+        broadcasts.insert(identifier, package);
+    }
+    let others = |me: Identifier| -> BTreeMap<_, _> {
+        broadcasts.iter().filter(|(id, _)| **id != me).map(|(id, package)| (*id, package.clone())).collect()
+    };
+
+    let mut round2_secrets = BTreeMap::new();
+    let mut inboxes: BTreeMap<Identifier, BTreeMap<Identifier, dkg::Round2Package>> = BTreeMap::new();
+    for (identifier, secret) in secrets {
+        let (round2, outgoing) = dkg::part2(secret, &others(identifier)).unwrap();
+        round2_secrets.insert(identifier, round2);
+        for (recipient, package) in outgoing {
+            inboxes.entry(recipient).or_default().insert(identifier, package);
+        }
+    }
+
+    let mut keys = BTreeMap::new();
+    let mut public = None;
+    for (identifier, round2) in &round2_secrets {
+        let (key, group) = dkg::part3(round2, &others(*identifier), &inboxes[identifier]).unwrap();
+        assert!(public.as_ref().is_none_or(|public| public == &group), "guardians agree on the group");
+        keys.insert(*identifier, key);
+        public = Some(group);
+    }
+    (keys, public.unwrap())
+}
+
+/// Both signing rounds and aggregation among `signers`
+fn threshold_sign(keys: &BTreeMap<Identifier, KeyPackage>, public: &PublicKeyPackage, signers: &[u16], rng: &mut DeterministicRng) -> Result<ed25519_dalek::Signature, CryptoError> {
+    let mut nonces = BTreeMap::new();
+    let mut commitments = BTreeMap::new();
+    for &signer in signers {
+        let (nonce, commitment) = frost::commit(&keys[&id(signer)], rng);
+        nonces.insert(id(signer), nonce);
+        commitments.insert(id(signer), commitment);
+    }
+    let package = SigningPackage::new(commitments, MESSAGE);
+    let shares = nonces.into_iter()
+        .map(|(identifier, nonce)| Ok((identifier, frost::sign(&package, nonce, &keys[&identifier])?)))
+        .collect::<Result<BTreeMap<_, _>, CryptoError>>()?;
+    frost::aggregate(&package, &shares, public)
+}
+
+/// RFC 9591 appendix E.1: FROST(Ed25519, SHA-512), participants 1 and 3 of 2-of-3
+#[test]
+fn rfc9591_signing_vector() {
+    let group_public = "15d21ccd7ee42959562fc8aa63224c8851fb3ec85a3faf66040d380fb9738673";
+    let shares = [
+        (1, "929dcc590407aae7d388761cddb0c0db6f5627aea8e217f4a033f2ec83d93509"),
+        (2, "a91e66e012e4364ac9aaa405fcafd370402d9859f7b6685c07eed76bf409e80d"),
+        (3, "d3cb090a075eb154e82fdb4b3cb507f110040905468bb9c46da8bdea643a9a02"),
+    ];
+    let keys: BTreeMap<Identifier, KeyPackage> = shares.iter()
+        .map(|(identifier, share)| {
+            let key: KeyPackage = serde_json::from_value(json!({
+                "identifier": identifier, "signing_share": share, "group_public": group_public, "min_signers": 2,
+            })).unwrap();
+            (id(*identifier), key)
+        })
+        .collect();
+    let verifying_shares: BTreeMap<String, String> = keys.iter()
+        .map(|(identifier, key)| (identifier.to_string(), hex::encode(key.verifying_share())))
+        .collect();
+    let public: PublicKeyPackage = serde_json::from_value(json!({
+        "verifying_shares": verifying_shares, "group_public": group_public, "min_signers": 2,
+    })).unwrap();
+    assert_eq!(hex::encode(public.group_public_key().as_bytes()), group_public);
+
+    let round_one = [
+        (1, "06894e04ee4aceec8619d5f6a0a180e2f47194d2ac306cba586b555e7c48d765", "40d6f879ff22e22409f7d808fed81f37118e7d3e4af71c0f44c60207553bcbce",
+         "9b116f12589591a7e23fe8048059ab10ab48e67739e7a2fb3890f61a7999478c", "c39b66b7dfccb122da24f13587f9a08c4347cae70046ca15169adf90ba65854d"),
+        (3, "fa5d950626782aade9e33fa781376d4888c2d1de7c37518bc248eb818ed0cdde", "7459a4d14ded0e365b085271be8dc6600d5b88f3978a2174297ffa32001a5afc",
+         "e679a2a971748ccfaabead4dbe8ac1def61275c186c79d471e1e45091ad1e687", "b2a942478453fabb6bd3181c56ba657413447b4136e1daea2484d396d1a516b3"),
+    ];
+    let mut nonces = BTreeMap::new();
+    let mut commitments = BTreeMap::new();
+    for (identifier, hiding_randomness, binding_randomness, hiding, binding) in round_one {
+        let (nonce, commitment) = frost::commit(&keys[&id(identifier)], &mut Scripted::new(&[hiding_randomness, binding_randomness]));
+        let (hiding_commitment, binding_commitment) = commitment.to_bytes();
+        assert_eq!((hex::encode(hiding_commitment), hex::encode(binding_commitment)), (hiding.to_string(), binding.to_string()));
+        nonces.insert(id(identifier), nonce);
+        commitments.insert(id(identifier), commitment);
+    }
+
+    let package = SigningPackage::new(commitments, b"test");
+    let expected_shares = [
+        (1, "60997f0142e43e8005027fe5ab7447dac00d22c2d7ddd9571a02613ba7d81c08"),
+        (3, "79390e78bc59699c7af831f8f5fb478ec871a85f561a8641b5670ac4443f720f"),
+    ];
+    let mut signature_shares = BTreeMap::new();
+    for (identifier, expected) in expected_shares {
+        let share = frost::sign(&package, nonces.remove(&id(identifier)).unwrap(), &keys[&id(identifier)]).unwrap();
+        assert_eq!(hex::encode(share.to_bytes()), expected);
+        signature_shares.insert(id(identifier), share);
+    }
+
+    let signature = frost::aggregate(&package, &signature_shares, &public).unwrap();
+    assert_eq!(
+        hex::encode(signature.to_bytes()),
+        "154fb694ee7fcb37bf2381d94488c2a84b03b3352ad085feca81ad26d45852b7ecfe971ce4da95c4a95db93ac376b053897fca212ef85f99cf696bffeb178f07"
+    );
+}
+
+/// DKG vector of the Zcash Foundation FROST implementation, 2-of-3
+///
+/// The vector omits the proof-of-knowledge nonces, so each guardian's own
+/// proof is taken from the vector rather than regenerated.
+#[test]
+fn dkg_vector() {
+    struct Guardian {
+        constant: &'static str,
+        coefficient: &'static str,
+        commitment: [&'static str; 2],
+        proof: &'static str,
+        received: [(u16, &'static str); 2],
+        signing_share: &'static str,
+    }
+    let guardians = BTreeMap::from([
+        (1, Guardian {
+            constant: "fc36bff380c95d607cd78080f493dd382df50f7007ec1b150b9bfbd8041fd208",
+            coefficient: "2d2c3e2b558e555b1608838e0ded66cd36d8aaa9ed1e39ce8474855d0825b20e",
+            commitment: ["db67948a73033b0c886ed757d97352428df05ad5803aff256bc388c9a0772bfe", "6a25b55a80a287826fbc3ef4d978d15f98f4f573f4d96e798a970780c644f14c"],
+            proof: "64c41c1d0417aef33576c23a5150de2921d6249d7086b10012f942405fc08ed51a872dd021db2ac01e9f4182e950324c5f563421bd835f3f514a60c975cab70c",
+            received: [(2, "ebb85acf4d46173f519a8680eac89f567d3f4a568843b741b251cc2f3d112b05"), (3, "f088e2c4b3a7f81836968c642721031ed8828d2e3d532fecaa85c481e445000c")],
+            signing_share: "2afd4ef9a27f9e636dd627ae56772951b98f929ebaa13b11ede611e82e9baf08",
+        }),
+        (2, Guardian {
+            constant: "2b76dcb99b75fbada4ffdc213b1ae4ec7c1b2a5da25d9ea85741a3ab376ca307",
+            coefficient: "ad167472cc332ee98237a1018ea89a7e002420f9e5e518995a10298405a5870d",
+            commitment: ["17194e192408980956a9cfc388d38456b2e9619c42a57196facd7feaf980a3bd", "0b560ce2f1130dc7066fc47f6e3f1858ef553faf6bbc19d3cd2a502052f95ae5"],
+            proof: "4062dc7fc11144e636f4eddd7f2d8d3e02761671ae913674314a12b91f50bf98e9c410d651c45c3a1766c88524fccb7670de191e12686324c59333e74fc93402",
+            received: [(1, "7ce74f90f61fe466fcad9757527aeda99aa565c3e2298eb11484069415693606"), (3, "7fde55b354d5d8dddc940fe932de5d1a6110b9bc4edeba2db7b32c34074d3a0a")],
+            signing_share: "b9ed88cb30a9ddbc00dbdf7c40d6c76f791989cf9f3119bad899287c5f6c2303",
+        }),
+        (3, Guardian {
+            constant: "61336fd6127a18548f9709e01b64a8214ff561a02bc8a3aa9e575ccfc13ec60d",
+            coefficient: "7c29694bbb90f21c7d9b7a27eab63911898d2b8e118b8b410c2e68b222073a0e",
+            commitment: ["0e9c22b9edbcd06757eee5ba3cfeb74e4fa6470932aa1ab689c86f1fe3dde70c", "0ae6e3e6ff1d612adb5cabb425ee51f2be9952467ce9b6d83095322909a42dd7"],
+            proof: "37df5a836ceaaf9f5e3b9ae6a485bad5c15788f3883386fe43f2921bbe2cb987d23683ca18973065a00d69bbf74f7961889b820e64f98878df7844a4e4785907",
+            received: [(1, "bc3f985e314b276a3c192343816d7562d17d106dd048c77f99f88bf11d8ee804"), (2, "6b3e57fab1e74e61aacfd93d4926172a7e878a48540fe97367721e38485b3a00")],
+            signing_share: "35b2b8fad8352f6e6a7c8fee082f45a339a37f0085c1f662c44c3f10903d970d",
+        }),
+    ]);
+    let broadcast = |guardian: &Guardian| -> dkg::Round1Package {
+        serde_json::from_value(json!({
+            "commitment": guardian.commitment, "proof_r": &guardian.proof[..64], "proof_z": &guardian.proof[64..],
+        })).unwrap()
+    };
+
+    for (&me, guardian) in &guardians {
+        let any_nonce = "0100000000000000000000000000000000000000000000000000000000000000";
+        let (secret, package) = dkg::part1(id(me), 3, 2, &mut Scripted::scalars(&[guardian.constant, guardian.coefficient, any_nonce])).unwrap();
+        assert_eq!(serde_json::to_value(&package).unwrap()["commitment"], json!(guardian.commitment));
+
+        let round1: BTreeMap<Identifier, dkg::Round1Package> = guardians.iter()
+            .filter(|(other, _)| **other != me)
+            .map(|(other, guardian)| (id(*other), broadcast(guardian)))
+            .collect();
+        let (round2, outgoing) = dkg::part2(secret, &round1).unwrap();
+        for (recipient, share) in outgoing {
+            let expected = guardians[&recipient.get()].received.iter().find(|(sender, _)| *sender == me).unwrap().1;
+            assert_eq!(serde_json::to_value(&share).unwrap()["signing_share"], expected);
+        }
+
+        let incoming: BTreeMap<Identifier, dkg::Round2Package> = guardian.received.iter()
+            .map(|(sender, share)| (id(*sender), serde_json::from_value(json!({ "signing_share": share })).unwrap()))
+            .collect();
+        let (key, public) = dkg::part3(&round2, &round1, &incoming).unwrap();
+        assert_eq!(serde_json::to_value(&key).unwrap()["signing_share"], guardian.signing_share);
+        assert_eq!(hex::encode(public.group_public_key().as_bytes()), "3d9a67b93cec3acad2d32349cf7bdffaa23ae031a81174ddadae1c96bb455f4d");
+    }
+}
+
+#[test]
+fn guardians_sign_the_ed25519_half_and_the_custodian_completes_it() {
+    let mut rng = DeterministicRng::from_seed([5; 32]);
+    let (keys, public) = run_dkg(5, 3, &mut rng);
+    assert_eq!(public.identifiers().count(), 5);
+    for key in keys.values() {
+        assert_eq!(public.verifying_share(key.identifier()), Some(key.verifying_share()));
+    }
+
+    for signers in [[1, 2, 3], [2, 4, 5], [1, 3, 5]] {
+        let signature = threshold_sign(&keys, &public, &signers, &mut rng).unwrap();
+        public.group_public_key().verify_strict(MESSAGE, &signature).unwrap();
+    }
+    assert!(threshold_sign(&keys, &public, &[1, 2, 3, 4, 5], &mut rng).is_ok());
+    assert!(matches!(threshold_sign(&keys, &public, &[2, 4], &mut rng), Err(CryptoError::InsufficientGuardianApproval)));
+
+    let clock = FixedClock::new(1_700_000_000);
+    let dilithium = DilithiumKeypair::generate(Some(&[9; 32]));
+    let custodian = DilithiumCustodian::new(dilithium, &public, DilithiumPolicy { max_usage: 2, not_after: Some(1_800_000_000) }, &clock);
+    let hybrid_public = custodian.public_key();
+    assert_eq!(hybrid_public.ed25519_public().unwrap(), public.group_public_key());
+
+    let guardian_signature = threshold_sign(&keys, &public, &[1, 4, 5], &mut rng).unwrap();
+    let signature = custodian.cosign(MESSAGE, &guardian_signature, &clock).unwrap();
+    hybrid_public.verify(MESSAGE, &signature).unwrap();
+    assert_eq!(signature.signer_key_id, custodian.key_id());
+
+    // The custodian refuses messages the guardians did not approve
+    assert!(matches!(custodian.cosign(b"something else", &guardian_signature, &clock), Err(CryptoError::InsufficientGuardianApproval)));
+    assert_eq!(custodian.usage(), 1);
+
+    // ... and anything outside its own policy
+    assert!(matches!(custodian.cosign(MESSAGE, &guardian_signature, &FixedClock::new(1_900_000_000)), Err(CryptoError::InvalidOperation { .. })));
+    custodian.cosign(MESSAGE, &guardian_signature, &clock).unwrap();
+    assert!(matches!(custodian.cosign(MESSAGE, &guardian_signature, &clock), Err(CryptoError::KeyUsageExceeded { count: 2, max: 2 })));
+}
+
+#[test]
+fn cheating_guardians_are_identified() {
+    let mut rng = DeterministicRng::from_seed([6; 32]);
+    let (keys, public) = run_dkg(3, 2, &mut rng);
+
+    let (nonce_1, commitment_1) = frost::commit(&keys[&id(1)], &mut rng);
+    let (nonce_3, commitment_3) = frost::commit(&keys[&id(3)], &mut rng);
+    let package = SigningPackage::new(BTreeMap::from([(id(1), commitment_1), (id(3), commitment_3)]), MESSAGE);
+    let share_1 = frost::sign(&package, nonce_1, &keys[&id(1)]).unwrap();
+    let share_3 = frost::sign(&package, nonce_3, &keys[&id(3)]).unwrap();
+
+    // Guardian 3 answers with guardian 1's share
+    let forged = BTreeMap::from([(id(1), share_1), (id(3), share_1)]);
+    let error = frost::aggregate(&package, &forged, &public).unwrap_err();
+    assert!(error.to_string().contains("guardian 3 is invalid"), "{}", error);
+
+    let missing = BTreeMap::from([(id(1), share_1)]);
+    assert!(frost::aggregate(&package, &missing, &public).is_err());
+    frost::aggregate(&package, &BTreeMap::from([(id(1), share_1), (id(3), share_3)]), &public).unwrap();
+
+    // Nonces only sign the package holding their commitments
+    let (nonce_2, _) = frost::commit(&keys[&id(2)], &mut rng);
+    assert!(frost::sign(&package, nonce_2, &keys[&id(2)]).is_err());
+
+    // A DKG share that does not match the dealer's commitment is rejected
+    let (secret_1, broadcast_1) = dkg::part1(id(1), 2, 2, &mut rng).unwrap();
+    let (secret_2, broadcast_2) = dkg::part1(id(2), 2, 2, &mut rng).unwrap();
+    let (round2_1, to_2) = dkg::part2(secret_1, &BTreeMap::from([(id(2), broadcast_2.clone())])).unwrap();
+    let (_, to_1) = dkg::part2(secret_2, &BTreeMap::from([(id(1), broadcast_1.clone())])).unwrap();
+    let wrong_share = BTreeMap::from([(id(2), to_2[&id(2)].clone())]);
+    let error = dkg::part3(&round2_1, &BTreeMap::from([(id(2), broadcast_2.clone())]), &wrong_share).unwrap_err();
+    assert!(error.to_string().contains("guardian 2"), "{}", error);
+    dkg::part3(&round2_1, &BTreeMap::from([(id(2), broadcast_2)]), &BTreeMap::from([(id(2), to_1[&id(1)].clone())])).unwrap();
+
+    // A round-one package replayed under another identifier fails its proof of knowledge
+    let (secret_3, _) = dkg::part1(id(3), 2, 2, &mut rng).unwrap();
+    let error = dkg::part2(secret_3, &BTreeMap::from([(id(2), broadcast_1)])).unwrap_err();
+    assert!(matches!(error, CryptoError::SignatureVerification { .. }), "{}", error);
+}
@@ -0,0 +1,88 @@
+//! Hierarchical derivation of hybrid keypairs from one master seed
+
+use af_pqc::clock::FixedClock;
+use af_pqc::hd::{self, DerivationPath, ExtendedPrivateKey, ExtendedPublicKey, Segment};
+use af_pqc::kdf::{KeySchedule, KeyScheduleVersion};
+use af_pqc::keystore::{KeyPolicy, Keystore};
+
+const SEED: [u8; 32] = [9; 32];
+const MESSAGE: &[u8] = b"hd test message";
+
+fn path(path: &str) -> DerivationPath {
+    path.parse().unwrap()
+}
+
+#[test]
+fn paths_parse_and_display() {
+    let parsed = path("m/af'/0/service-name");
+    assert_eq!(parsed.to_string(), "m/af'/0/service-name");
+    assert_eq!(parsed.segments().len(), 3);
+    assert!(parsed.segments()[0].is_hardened());
+    assert_eq!(parsed.segments()[2].name(), "service-name");
+    assert_eq!(path("m"), DerivationPath::master());
+    assert_eq!(DerivationPath::master().child(Segment::new("af", true).unwrap()).join(&path("m/0")), path("m/af'/0"));
+
+    for invalid in ["", "af/0", "m/", "m//0", "m/a b", "m/0''", &format!("m/{}", "a".repeat(65))] {
+        assert!(invalid.parse::<DerivationPath>().is_err(), "{:?} parsed", invalid);
+    }
+}
+
+#[test]
+fn paths_derive_independent_deterministic_keys() {
+    let clock = FixedClock::new(1_700_000_000);
+    let (service, service_public) = hd::derive_keypair(&SEED, &path("m/af/0/service-name"), &clock).unwrap();
+    let (_, again) = hd::derive_keypair(&SEED, &path("m/af/0/service-name"), &clock).unwrap();
+    let (_, sibling) = hd::derive_keypair(&SEED, &path("m/af/0/other-service"), &clock).unwrap();
+    let (_, hardened) = hd::derive_keypair(&SEED, &path("m/af/0/service-name'"), &clock).unwrap();
+    let (_, other_seed) = hd::derive_keypair(&[10; 32], &path("m/af/0/service-name"), &clock).unwrap();
+
+    assert_eq!(service_public.bytes, again.bytes);
+    assert!(service.key_id.starts_with("hd-hybrid-"));
+    for other in [&sibling, &hardened, &other_seed] {
+        assert_ne!(other.dilithium_public().unwrap(), service_public.dilithium_public().unwrap());
+        assert_ne!(other.ed25519_public().unwrap(), service_public.ed25519_public().unwrap());
+    }
+
+    let signature = service.sign(MESSAGE).unwrap();
+    service_public.verify(MESSAGE, &signature).unwrap();
+    assert!(sibling.verify(MESSAGE, &signature).is_err());
+}
+
+#[test]
+fn extended_public_key_derives_ed25519_children() {
+    let clock = FixedClock::new(1_700_000_000);
+    let account = ExtendedPrivateKey::master(&SEED).derive_path(&path("m/af'/0'")).unwrap();
+    let xpub = account.extended_public_key();
+    assert_eq!(xpub.path(), &path("m/af'/0'"));
+
+    let relative = path("m/services/service-name");
+    let public_child = xpub.derive_path(&relative).unwrap();
+    let private_child = account.derive_path(&relative).unwrap();
+    assert_eq!(public_child.path(), &path("m/af'/0'/services/service-name"));
+    assert_eq!(public_child, private_child.extended_public_key());
+    let (_, public_key) = private_child.to_keypair(&clock).unwrap();
+    assert_eq!(&public_key.ed25519_public().unwrap(), public_child.ed25519_public());
+
+    assert!(xpub.derive_path(&path("m/service-name'")).is_err());
+
+    let json = serde_json::to_string(&xpub).unwrap();
+    assert_eq!(serde_json::from_str::<ExtendedPublicKey>(&json).unwrap(), xpub);
+}
+
+#[test]
+fn derived_keys_round_trip_through_the_keystore() {
+    let clock = FixedClock::new(1_700_000_000);
+    let (private_key, public_key) = hd::derive_keypair(&SEED, &path("m/af/0/service-name"), &clock).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let keystore = Keystore::open(dir.path().join("keystore")).unwrap();
+    keystore.insert("service", &private_key, KeyPolicy::default(), b"passphrase").unwrap();
+    let signature = keystore.sign("service", b"passphrase", MESSAGE).unwrap();
+    public_key.verify(MESSAGE, &signature).unwrap();
+}
+
+#[test]
+fn legacy_schedule_has_no_derivation_tree() {
+    let legacy = KeySchedule::with_version(&SEED, KeyScheduleVersion::Legacy);
+    assert!(ExtendedPrivateKey::from_schedule(&legacy).is_err());
+}
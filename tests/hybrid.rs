@@ -0,0 +1,105 @@
+//! Exact-length validation of hybrid keys and signatures
+
+use af_pqc::clock::FixedClock;
+use af_pqc::hybrid::{HYBRID_PUBLIC_KEY_BYTES, HYBRID_SIGNATURE_BYTES};
+use af_pqc::rng::DeterministicRng;
+use af_pqc::{AlgorithmVersion, CryptoError, HybridPublicKey, HybridSignature, PrivateKey, PublicKey, Signature};
+
+const MESSAGE: &[u8] = b"strict encoding";
+
+fn signed() -> (PublicKey, Signature) {
+    let clock = FixedClock::new(1_700_000_000);
+    let (private_key, public_key) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([3; 32]), &clock).unwrap();
+    let signature = private_key.sign_with_clock(MESSAGE, &clock).unwrap();
+    (public_key, signature)
+}
+
+fn assert_malformed(result: Result<(), CryptoError>) {
+    match result {
+        Err(CryptoError::MalformedEncoding { .. }) => {}
+        other => panic!("expected MalformedEncoding, got {other:?}"),
+    }
+}
+
+#[test]
+fn trailing_and_missing_bytes_are_malformed() {
+    let (public_key, signature) = signed();
+    public_key.verify(MESSAGE, &signature).unwrap();
+
+    let mut padded = signature.clone();
+    padded.bytes.push(0);
+    assert_malformed(public_key.verify(MESSAGE, &padded));
+
+    let mut truncated = signature.clone();
+    truncated.bytes.pop();
+    assert_malformed(public_key.verify(MESSAGE, &truncated));
+
+    let mut padded_key = public_key.clone();
+    padded_key.bytes.extend_from_slice(&[0; 32]);
+    assert_malformed(padded_key.verify(MESSAGE, &signature));
+}
+
+#[test]
+fn mismatched_signature_algorithm_is_rejected() {
+    let (public_key, mut signature) = signed();
+    #[allow(deprecated)]
+    {
+        signature.algorithm = AlgorithmVersion::Ed25519V1;
+    }
+    assert!(matches!(
+        public_key.verify(MESSAGE, &signature),
+        Err(CryptoError::SignatureVerification { .. })
+    ));
+}
+
+#[test]
+fn newtypes_only_accept_exact_encodings() {
+    let (public_key, signature) = signed();
+    let hybrid_key = public_key.hybrid().unwrap();
+    let hybrid_signature = signature.hybrid().unwrap();
+
+    assert_eq!(hybrid_key.to_bytes(), public_key.bytes);
+    assert_eq!(hybrid_signature.to_bytes(), signature.bytes);
+    assert_eq!(HybridPublicKey::try_from(public_key.bytes.as_slice()).unwrap(), hybrid_key);
+    hybrid_key.verify(MESSAGE, &hybrid_signature).unwrap();
+
+    assert!(HybridPublicKey::from_bytes(&[0; HYBRID_PUBLIC_KEY_BYTES + 1]).is_err());
+    assert!(HybridSignature::from_bytes(&[0; HYBRID_SIGNATURE_BYTES - 1]).is_err());
+    assert!(matches!(
+        hybrid_key.verify(b"other message", &hybrid_signature),
+        Err(CryptoError::SignatureVerification { .. })
+    ));
+}
+
+#[test]
+fn components_can_be_inspected_and_reassembled() {
+    let (public_key, signature) = signed();
+    let dilithium_public = public_key.dilithium_public().unwrap();
+    let ed25519_public = public_key.ed25519_public().unwrap();
+    let dilithium_signature = signature.dilithium_signature().unwrap();
+    let ed25519_signature = signature.ed25519_signature().unwrap();
+
+    // Each half verifies on its own with the component APIs
+    ed25519_dalek::Verifier::verify(&ed25519_public, MESSAGE, &ed25519_signature).unwrap();
+    let hybrid_key = HybridPublicKey::from_components(dilithium_public, ed25519_public);
+    hybrid_key.verify_dilithium(MESSAGE, &dilithium_signature).unwrap();
+    hybrid_key.verify_ed25519(MESSAGE, &ed25519_signature).unwrap();
+
+    let rebuilt_key = PublicKey::from_hybrid(&hybrid_key, public_key.created_at, public_key.operation_id);
+    let rebuilt_signature = Signature::from_hybrid(
+        &HybridSignature::from_components(dilithium_signature, ed25519_signature),
+        signature.created_at,
+        signature.operation_id,
+        signature.signer_key_id.clone(),
+    );
+    assert_eq!(rebuilt_key.bytes, public_key.bytes);
+    assert_eq!(rebuilt_signature.bytes, signature.bytes);
+    rebuilt_key.verify(MESSAGE, &rebuilt_signature).unwrap();
+
+    // Halves from different keys do not combine into a valid signature
+    let clock = FixedClock::new(1_700_000_000);
+    let (other, _) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([4; 32]), &clock).unwrap();
+    let other_signature = other.sign_with_clock(MESSAGE, &clock).unwrap();
+    let mixed = HybridSignature::from_components(dilithium_signature, other_signature.ed25519_signature().unwrap());
+    assert!(hybrid_key.verify(MESSAGE, &mixed).is_err());
+}
@@ -0,0 +1,297 @@
+//! Known-answer tests for both halves of the hybrid scheme and golden vectors
+//! for the hybrid key and signature formats.
+//!
+//! - Dilithium3: `crystals-dilithium` implements round-3 Dilithium (v3.1),
+//!   not FIPS 204 ML-DSA, so it is checked against the NIST round-3 KAT. The
+//!   first `PQCsignKAT` vector is regenerated with NIST's AES-256 CTR-DRBG and
+//!   compared with the SHA-256 that PQClean pins for the reference code.
+//! - Ed25519: RFC 8032 §7.1 test vectors.
+//! - Hybrid: golden public key and signature files in `tests/vectors`. Any
+//!   change to key derivation, signing or serialization fails here. After an
+//!   intentional format change, regenerate them with
+//!   `AF_PQC_UPDATE_VECTORS=1 cargo test --test kat` and bump the format version.
+
+use aes::cipher::{BlockEncrypt, KeyInit};
+use aes::Aes256;
+use af_pqc::clock::FixedClock;
+use af_pqc::rng::DeterministicRng;
+use af_pqc::kdf::{KeySchedule, KeyScheduleVersion, Subkey};
+use af_pqc::{AlgorithmVersion, HybridSecretKey, KeyMaterialInner, PrivateKey, PublicKey, Signature};
+use crystals_dilithium::dilithium3::{Keypair as DilithiumKeypair, PUBLICKEYBYTES, SECRETKEYBYTES, SIGNBYTES};
+use ed25519_dalek::SigningKey;
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+
+/// SHA-256 of the `count = 0` NIST KAT output for Dilithium3 (PQClean `META.yml`)
+const DILITHIUM3_NISTKAT_SHA256: &str = "8439f580566c46b99449b2cbbd597ce59bcd5d184b90c1108b79a08f6bdbbcb1";
+
+const GOLDEN_SEED: [u8; 32] = [0x42; 32];
+const GOLDEN_TIME: u64 = 1_700_000_000;
+const GOLDEN_MESSAGE: &[u8] = b"AF-PQC golden vector";
+
+/// RFC 8032 §7.1: secret key, public key, message, signature
+const RFC8032_VECTORS: &[(&str, &str, &str, &str)] = &[
+    (
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+        "",
+        "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+    ),
+    (
+        "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+        "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+        "72",
+        "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+    ),
+    (
+        "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+        "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+        "af82",
+        "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+    ),
+    (
+        "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42",
+        "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf",
+        "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+        "dc2a4459e7369633a52b1bf277839a00201009a3efbf3ecb69bea2186c26b58909351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704",
+    ),
+];
+
+/// NIST `rng.c` AES-256 CTR-DRBG used to generate the PQC KAT files
+struct KatDrbg {
+    key: [u8; 32],
+    v: [u8; 16],
+}
+
+impl KatDrbg {
+    fn new(entropy: &[u8; 48]) -> Self {
+        let mut drbg = Self { key: [0; 32], v: [0; 16] };
+        drbg.update(Some(entropy));
+        drbg
+    }
+
+    fn next_block(&mut self) -> [u8; 16] {
+        for byte in self.v.iter_mut().rev() {
+            let (value, overflow) = byte.overflowing_add(1);
+            *byte = value;
+            if !overflow {
+                break;
+            }
+        }
+        let mut block = self.v.into();
+        Aes256::new(&self.key.into()).encrypt_block(&mut block);
+        block.into()
+    }
+
+    fn update(&mut self, provided: Option<&[u8; 48]>) {
+        let mut temp = [0u8; 48];
+        for chunk in temp.chunks_mut(16) {
+            chunk.copy_from_slice(&self.next_block());
+        }
+        if let Some(provided) = provided {
+            temp.iter_mut().zip(provided).for_each(|(t, p)| *t ^= p);
+        }
+        self.key.copy_from_slice(&temp[..32]);
+        self.v.copy_from_slice(&temp[32..]);
+    }
+
+    fn fill(&mut self, buffer: &mut [u8]) {
+        for chunk in buffer.chunks_mut(16) {
+            let block = self.next_block();
+            chunk.copy_from_slice(&block[..chunk.len()]);
+        }
+        self.update(None);
+    }
+}
+
+fn kat_line(output: &mut String, label: &str, bytes: &[u8]) {
+    writeln!(output, "{} = {}", label, hex::encode_upper(bytes)).unwrap();
+}
+
+/// First NIST KAT vector: message, keypair and signed message `sig || msg`
+fn dilithium3_kat_vector() -> (String, Vec<u8>, DilithiumKeypair, Vec<u8>) {
+    let entropy: [u8; 48] = std::array::from_fn(|i| i as u8);
+    let mut drbg = KatDrbg::new(&entropy);
+
+    let mut seed = [0u8; 48];
+    drbg.fill(&mut seed);
+    let mut message = vec![0u8; 33];
+    drbg.fill(&mut message);
+
+    let mut drbg = KatDrbg::new(&seed);
+    let mut keypair_seed = [0u8; 32];
+    drbg.fill(&mut keypair_seed);
+    let keypair = DilithiumKeypair::generate(Some(&keypair_seed));
+
+    let signed = [keypair.sign(&message).as_slice(), &message].concat();
+
+    let mut output = String::from("count = 0\n");
+    kat_line(&mut output, "seed", &seed);
+    output.push_str("mlen = 33\n");
+    kat_line(&mut output, "msg", &message);
+    kat_line(&mut output, "pk", &keypair.public.to_bytes());
+    kat_line(&mut output, "sk", &keypair.secret.to_bytes());
+    writeln!(output, "smlen = {}", signed.len()).unwrap();
+    kat_line(&mut output, "sm", &signed);
+
+    (output, message, keypair, signed)
+}
+
+fn hybrid_key(dilithium: DilithiumKeypair, ed25519: SigningKey) -> (PrivateKey, PublicKey) {
+    let private_key = PrivateKey {
+        algorithm: AlgorithmVersion::MandatoryHybrid,
+        inner: KeyMaterialInner::MandatoryHybrid(HybridSecretKey::from_keypairs(dilithium, ed25519)),
+        created_at: GOLDEN_TIME,
+        operation_id: GOLDEN_TIME,
+        usage_count: AtomicU64::new(0),
+        key_id: "kat".to_string(),
+    };
+    let public_key = private_key.public_key().unwrap();
+    (private_key, public_key)
+}
+
+#[test]
+fn dilithium3_matches_nist_kat() {
+    let (output, message, keypair, signed) = dilithium3_kat_vector();
+    assert_eq!(keypair.secret.to_bytes().len(), SECRETKEYBYTES);
+    assert_eq!(hex::encode(Sha256::digest(output.as_bytes())), DILITHIUM3_NISTKAT_SHA256);
+
+    // The hybrid scheme's Dilithium half is exactly the KAT signature
+    let ed25519 = SigningKey::from_bytes(&[1; 32]);
+    let (private_key, public_key) = hybrid_key(keypair, ed25519);
+    assert_eq!(&public_key.bytes[..PUBLICKEYBYTES], &private_key.public_key().unwrap().bytes[..PUBLICKEYBYTES]);
+
+    let signature = private_key.sign_with_clock(&message, &FixedClock::new(GOLDEN_TIME)).unwrap();
+    assert_eq!(&signature.bytes[..SIGNBYTES], &signed[..SIGNBYTES]);
+    public_key.verify(&message, &signature).unwrap();
+}
+
+#[test]
+fn ed25519_matches_rfc8032() {
+    let (_, _, dilithium, _) = dilithium3_kat_vector();
+
+    for (secret, public, message, expected) in RFC8032_VECTORS {
+        let secret: [u8; 32] = hex::decode(secret).unwrap().try_into().unwrap();
+        let message = hex::decode(message).unwrap();
+        let expected = hex::decode(expected).unwrap();
+
+        let (private_key, public_key) = hybrid_key(DilithiumKeypair::from_bytes(&dilithium.to_bytes()), SigningKey::from_bytes(&secret));
+        assert_eq!(hex::encode(&public_key.bytes[PUBLICKEYBYTES..]), *public);
+
+        // Empty messages cannot be signed through the hybrid API, so assemble the signature
+        let bytes = if message.is_empty() {
+            [dilithium.sign(&message).as_slice(), &expected].concat()
+        } else {
+            let signature = private_key.sign_with_clock(&message, &FixedClock::new(GOLDEN_TIME)).unwrap();
+            assert_eq!(&signature.bytes[SIGNBYTES..], expected.as_slice());
+            signature.bytes
+        };
+
+        let signature = Signature {
+            algorithm: AlgorithmVersion::MandatoryHybrid,
+            bytes,
+            created_at: GOLDEN_TIME,
+            operation_id: GOLDEN_TIME,
+            signer_key_id: "kat".to_string(),
+        };
+        public_key.verify(&message, &signature).unwrap();
+    }
+}
+
+fn vector_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/vectors").join(name)
+}
+
+/// Compare `actual` with a golden file, or rewrite it when updating vectors
+fn check_golden(name: &str, actual: &str) {
+    let path = vector_path(name);
+    if std::env::var_os("AF_PQC_UPDATE_VECTORS").is_some() {
+        std::fs::write(&path, format!("{}\n", actual)).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(expected.trim_end(), actual, "{} drifted from its golden vector", name);
+}
+
+#[test]
+fn hybrid_golden_vectors() {
+    let clock = FixedClock::new(GOLDEN_TIME);
+    let (private_key, public_key) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed(GOLDEN_SEED), &clock).unwrap();
+    let signature = private_key.sign_with_clock(GOLDEN_MESSAGE, &clock).unwrap();
+
+    assert_eq!(public_key.bytes.len(), PUBLICKEYBYTES + 32);
+    assert_eq!(signature.bytes.len(), SIGNBYTES + 64);
+    check_golden("hybrid_public_key.json", &serde_json::to_string(&public_key).unwrap());
+    check_golden("hybrid_signature.json", &serde_json::to_string(&signature).unwrap());
+
+    // Vectors written by earlier versions must keep parsing and verifying
+    let stored_key: PublicKey = serde_json::from_str(&std::fs::read_to_string(vector_path("hybrid_public_key.json")).unwrap()).unwrap();
+    let stored_signature: Signature = serde_json::from_str(&std::fs::read_to_string(vector_path("hybrid_signature.json")).unwrap()).unwrap();
+    stored_key.verify(GOLDEN_MESSAGE, &stored_signature).unwrap();
+}
+
+#[test]
+fn seed_derivation_golden_vector() {
+    // Legacy keys take their Dilithium half from the on-disk cache, so only
+    // the deterministic Ed25519 half and metadata are pinned
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_current_dir(dir.path()).unwrap();
+
+    let clock = FixedClock::new(GOLDEN_TIME);
+    for (version, prefix) in [(KeyScheduleVersion::Legacy, "seed_derivation"), (KeyScheduleVersion::V1, "seed_derivation_v1")] {
+        let schedule = KeySchedule::with_version(&GOLDEN_SEED, version);
+        let (private_key, public_key) = af_pqc::generate_key_from_schedule(&schedule, &clock).unwrap();
+        let (_, again) = af_pqc::generate_key_from_schedule(&schedule, &clock).unwrap();
+        assert_eq!(public_key.bytes, again.bytes);
+
+        let summary = format!(
+            "key_id={} operation_id={} ed25519={}",
+            private_key.key_id,
+            private_key.operation_id,
+            hex::encode(&public_key.bytes[PUBLICKEYBYTES..]),
+        );
+        check_golden(&format!("{}.txt", prefix), &summary);
+
+        let signature = private_key.sign_with_clock(GOLDEN_MESSAGE, &clock).unwrap();
+        check_golden(&format!("{}_ed25519_signature.txt", prefix), &hex::encode(&signature.bytes[SIGNBYTES..]));
+    }
+}
+
+#[test]
+fn key_schedule_golden_vector() {
+    let schedule = KeySchedule::new(&GOLDEN_SEED);
+    let mut extension = [0u8; 48];
+    schedule.derive_labeled("golden", &mut extension).unwrap();
+    let summary = [Subkey::DilithiumSeed, Subkey::Ed25519Seed, Subkey::StorageKey, Subkey::CacheName, Subkey::KeyId]
+        .into_iter()
+        .map(|subkey| format!("{}={}", subkey.label(), hex::encode(*schedule.derive(subkey))))
+        .chain([format!("ext/golden={}", hex::encode(extension))])
+        .collect::<Vec<_>>()
+        .join("\n");
+    check_golden("key_schedule_v1.txt", &summary);
+}
+
+#[test]
+fn hd_derivation_golden_vector() {
+    let clock = FixedClock::new(GOLDEN_TIME);
+    let path = "m/af'/0/service-name".parse().unwrap();
+    let (private_key, public_key) = af_pqc::hd::derive_keypair(&GOLDEN_SEED, &path, &clock).unwrap();
+    let xpub = af_pqc::hd::ExtendedPrivateKey::master(&GOLDEN_SEED)
+        .derive_path(&"m/af'".parse().unwrap())
+        .unwrap()
+        .extended_public_key();
+
+    let summary = format!(
+        "path={} key_id={} fingerprint={}\nxpub={}",
+        path,
+        private_key.key_id,
+        public_key.fingerprint(),
+        serde_json::to_string(&xpub).unwrap(),
+    );
+    check_golden("hd_derivation.txt", &summary);
+
+    let signature = private_key.sign_with_clock(GOLDEN_MESSAGE, &clock).unwrap();
+    check_golden("hd_derivation_ed25519_signature.txt", &hex::encode(&signature.bytes[SIGNBYTES..]));
+}
@@ -0,0 +1,80 @@
+//! HKDF key schedule and the legacy derivations it replaces
+
+use af_pqc::clock::FixedClock;
+use af_pqc::kdf::{KeySchedule, KeyScheduleVersion, Subkey};
+use af_pqc::CryptoError;
+
+const SEED: [u8; 32] = [7; 32];
+/// Tests that change the working directory take this lock
+static WORKING_DIRECTORY: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+const SUBKEYS: [Subkey; 5] = [Subkey::DilithiumSeed, Subkey::Ed25519Seed, Subkey::StorageKey, Subkey::CacheName, Subkey::KeyId];
+
+#[test]
+fn subkeys_are_distinct_per_label_and_version() {
+    let v1 = KeySchedule::new(&SEED);
+    let legacy = KeySchedule::with_version(&SEED, KeyScheduleVersion::Legacy);
+    assert_eq!(v1.version(), KeyScheduleVersion::V1);
+
+    let mut derived: Vec<[u8; 32]> = SUBKEYS.iter().map(|&subkey| *v1.derive(subkey)).collect();
+    derived.extend(SUBKEYS.iter().map(|&subkey| *legacy.derive(subkey)));
+    let mut label_only = [0u8; 32];
+    v1.derive_labeled(Subkey::StorageKey.label(), &mut label_only).unwrap();
+    derived.push(label_only);
+
+    for (i, a) in derived.iter().enumerate() {
+        for b in &derived[i + 1..] {
+            assert_ne!(a, b);
+        }
+    }
+    assert_ne!(*KeySchedule::new(&[8; 32]).derive(Subkey::KeyId), *v1.derive(Subkey::KeyId));
+}
+
+#[test]
+fn legacy_schedule_reproduces_earlier_derivations() {
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::{RngCore, SeedableRng};
+
+    let legacy = KeySchedule::with_version(&SEED, KeyScheduleVersion::Legacy);
+    let mut stream = [0u8; 32];
+    ChaCha20Rng::from_seed(SEED).fill_bytes(&mut stream);
+    assert_eq!(*legacy.derive(Subkey::Ed25519Seed), stream);
+    assert!(matches!(legacy.derive_labeled("anything", &mut [0u8; 32]), Err(CryptoError::InvalidOperation { .. })));
+
+    // Seed-derived keys write the Dilithium cache to the working directory
+    let _cwd = WORKING_DIRECTORY.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_current_dir(dir.path()).unwrap();
+    let clock = FixedClock::new(1_700_000_000);
+    let (private_key, _) = af_pqc::generate_key_from_schedule(&legacy, &clock).unwrap();
+    assert!(dir.path().join(format!(".af_dilithium_{}", hex::encode(&legacy.derive(Subkey::CacheName)[..16]))).exists());
+    assert_eq!(private_key.key_id, format!("deterministic-hybrid-{}", hex::encode(&SEED[..8])));
+}
+
+#[cfg(feature = "fs-cache")]
+#[test]
+fn v1_keys_do_not_depend_on_the_cache() {
+    let _cwd = WORKING_DIRECTORY.lock().unwrap_or_else(|e| e.into_inner());
+    let clock = FixedClock::new(1_700_000_000);
+    let schedule = KeySchedule::new(&SEED);
+    let mut public_keys = Vec::new();
+    for _ in 0..2 {
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_current_dir(dir.path()).unwrap();
+        let (_, public_key) = af_pqc::generate_key_from_schedule(&schedule, &clock).unwrap();
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0, "v1 keys must not write a cache");
+        public_keys.push(public_key.bytes);
+    }
+    assert_eq!(public_keys[0], public_keys[1]);
+
+    let (_, from_seed) = af_pqc::generate_key_from_seed_with_clock(&SEED, &clock).unwrap();
+    assert_eq!(from_seed.bytes, public_keys[0]);
+}
+
+#[test]
+fn versions_parse_by_name() {
+    for version in [KeyScheduleVersion::Legacy, KeyScheduleVersion::V1] {
+        assert_eq!(version.to_string().parse::<KeyScheduleVersion>().unwrap(), version);
+    }
+    assert!("v2".parse::<KeyScheduleVersion>().is_err());
+}
@@ -0,0 +1,205 @@
+//! Local keystore: layout, permissions, key management and usage policies
+
+use af_pqc::clock::FixedClock;
+use af_pqc::keystore::{KeyPolicy, Keystore};
+use af_pqc::rng::DeterministicRng;
+use af_pqc::{CryptoError, PrivateKey};
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+const PASSPHRASE: &[u8] = b"correct horse battery staple";
+const MESSAGE: &[u8] = b"keystore message";
+
+fn key(seed: u8) -> PrivateKey {
+    PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([seed; 32]), &FixedClock::new(1_700_000_000)).unwrap().0
+}
+
+fn keystore() -> (tempfile::TempDir, Keystore) {
+    let dir = tempfile::tempdir().unwrap();
+    let keystore = Keystore::open(dir.path().join("home")).unwrap();
+    (dir, keystore)
+}
+
+fn mode(path: &Path) -> u32 {
+    std::fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+fn chmod(path: &Path, mode: u32) {
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode)).unwrap();
+}
+
+#[test]
+fn keys_are_managed_by_name() {
+    let (_dir, keystore) = keystore();
+    let (alice, bob) = (key(1), key(2));
+    let record = keystore.insert("alice", &alice, KeyPolicy::default(), PASSPHRASE).unwrap();
+    assert_eq!(record.key_id, alice.key_id);
+    keystore.insert("bob", &bob, KeyPolicy { max_usage: 5, not_after: None }, PASSPHRASE).unwrap();
+    assert!(keystore.insert("alice", &bob, KeyPolicy::default(), PASSPHRASE).is_err());
+    assert!(keystore.insert("carol", &bob, KeyPolicy::default(), b"").is_err());
+    for name in ["", ".hidden", "a/b", "../escape"] {
+        assert!(keystore.insert(name, &bob, KeyPolicy::default(), PASSPHRASE).is_err(), "{:?}", name);
+    }
+
+    let names: Vec<String> = keystore.list().unwrap().into_iter().map(|record| record.name).collect();
+    assert_eq!(names, ["alice", "bob"]);
+    assert_eq!(keystore.record("bob").unwrap().policy.max_usage, 5);
+    assert_eq!(keystore.public_key("alice").unwrap().bytes, alice.public_key().unwrap().bytes);
+    assert!(matches!(keystore.record("carol"), Err(CryptoError::KeyNotFound { .. })));
+
+    assert_eq!(keystore.default_key().unwrap(), None);
+    assert!(keystore.set_default_key("carol").is_err());
+    keystore.set_default_key("alice").unwrap();
+    keystore.rename("alice", "alice-2025").unwrap();
+    assert_eq!(keystore.default_key().unwrap().as_deref(), Some("alice-2025"));
+    assert_eq!(keystore.record("alice-2025").unwrap().name, "alice-2025");
+    assert!(!keystore.contains("alice"));
+    assert!(keystore.rename("alice-2025", "bob").is_err());
+    assert!(keystore.rename("alice", "dave").is_err());
+
+    keystore.delete("alice-2025").unwrap();
+    assert_eq!(keystore.default_key().unwrap(), None);
+    assert!(matches!(keystore.delete("alice-2025"), Err(CryptoError::KeyNotFound { .. })));
+    assert_eq!(keystore.list().unwrap().len(), 1);
+}
+
+#[test]
+fn private_keys_need_the_passphrase() {
+    let (_dir, keystore) = keystore();
+    let alice = key(1);
+    keystore.insert("alice", &alice, KeyPolicy::default(), PASSPHRASE).unwrap();
+
+    let error = keystore.load_private_key("alice", b"wrong passphrase").err().unwrap();
+    assert!(error.to_string().contains("wrong passphrase"), "{}", error);
+    assert!(keystore.sign("alice", b"wrong passphrase", MESSAGE).is_err());
+    assert_eq!(keystore.usage("alice").unwrap(), 0);
+
+    let loaded = keystore.load_private_key("alice", PASSPHRASE).unwrap();
+    assert_eq!(loaded.key_id, alice.key_id);
+    let signature = keystore.sign("alice", PASSPHRASE, MESSAGE).unwrap();
+    keystore.public_key("alice").unwrap().verify(MESSAGE, &signature).unwrap();
+    assert_eq!(keystore.usage("alice").unwrap(), 1);
+    assert_eq!(keystore.load_private_key("alice", PASSPHRASE).unwrap().usage_count.into_inner(), 1);
+}
+
+#[test]
+fn permissions_are_private_and_enforced() {
+    let (dir, keystore) = keystore();
+    keystore.insert("alice", &key(1), KeyPolicy::default(), PASSPHRASE).unwrap();
+    keystore.set_default_key("alice").unwrap();
+
+    let key_dir = keystore.root().join("keys").join("alice");
+    for path in [keystore.root().to_path_buf(), keystore.root().join("keys"), key_dir.clone()] {
+        assert_eq!(mode(&path), 0o700, "{}", path.display());
+    }
+    for file in ["key.json", "public.json", "secret.enc", "usage.json"] {
+        assert_eq!(mode(&key_dir.join(file)), 0o600, "{}", file);
+    }
+    assert_eq!(mode(&keystore.root().join("config.json")), 0o600);
+
+    chmod(&key_dir.join("secret.enc"), 0o644);
+    let error = keystore.load_private_key("alice", PASSPHRASE).err().unwrap();
+    assert!(error.to_string().contains("Insecure permissions 644"), "{}", error);
+    chmod(&key_dir.join("secret.enc"), 0o600);
+
+    chmod(&key_dir, 0o755);
+    assert!(keystore.record("alice").is_err());
+    chmod(&key_dir, 0o700);
+    keystore.record("alice").unwrap();
+
+    chmod(keystore.root(), 0o750);
+    assert!(Keystore::open(dir.path().join("home")).is_err());
+}
+
+#[test]
+fn partial_keys_are_not_listed() {
+    let (_dir, keystore) = keystore();
+    keystore.insert("alice", &key(1), KeyPolicy::default(), PASSPHRASE).unwrap();
+
+    // An insert in progress, or killed before cleaning up, has no key.json yet
+    let partial = keystore.root().join("keys").join("bob");
+    std::fs::create_dir(&partial).unwrap();
+    chmod(&partial, 0o700);
+    std::fs::write(partial.join("secret.enc"), b"partial").unwrap();
+
+    let names: Vec<String> = keystore.list().unwrap().into_iter().map(|record| record.name).collect();
+    assert_eq!(names, ["alice"]);
+    assert!(!keystore.contains("bob"));
+    // The name stays reserved, as the directory may belong to another process's insert
+    assert!(keystore.insert("bob", &key(2), KeyPolicy::default(), PASSPHRASE).is_err());
+
+    #[cfg(feature = "cli")]
+    {
+        let cli = std::process::Command::new(env!("CARGO_BIN_EXE_af-cli"))
+            .args(["key", "list"])
+            .env("AF_HOME", keystore.root())
+            .output()
+            .unwrap();
+        assert!(cli.status.success(), "{}", String::from_utf8_lossy(&cli.stderr));
+        let listed = String::from_utf8(cli.stdout).unwrap();
+        assert!(listed.contains("alice") && !listed.contains("bob"), "{}", listed);
+    }
+}
+
+#[test]
+fn files_are_replaced_atomically() {
+    let (_dir, keystore) = keystore();
+    keystore.insert("alice", &key(1), KeyPolicy::default(), PASSPHRASE).unwrap();
+    keystore.insert("bob", &key(2), KeyPolicy::default(), PASSPHRASE).unwrap();
+
+    // A temporary file left behind by a crash is replaced, not appended to
+    let config = keystore.root().join("config.json");
+    std::fs::write(keystore.root().join(".config.json.tmp"), b"stale").unwrap();
+    keystore.set_default_key("alice").unwrap();
+    keystore.set_default_key("bob").unwrap();
+    assert_eq!(keystore.default_key().unwrap().as_deref(), Some("bob"));
+    assert_eq!(mode(&config), 0o600);
+
+    let leftovers: Vec<_> = std::fs::read_dir(keystore.root()).unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+}
+
+#[test]
+fn policies_limit_signatures() {
+    let (_dir, keystore) = keystore();
+    keystore.insert("limited", &key(1), KeyPolicy { max_usage: 2, not_after: None }, PASSPHRASE).unwrap();
+    keystore.insert("expired", &key(2), KeyPolicy { max_usage: 10, not_after: Some(1) }, PASSPHRASE).unwrap();
+
+    keystore.sign("limited", PASSPHRASE, MESSAGE).unwrap();
+    keystore.sign("limited", PASSPHRASE, MESSAGE).unwrap();
+    assert!(matches!(
+        keystore.sign("limited", PASSPHRASE, MESSAGE),
+        Err(CryptoError::KeyUsageExceeded { count: 2, max: 2 })
+    ));
+    assert_eq!(keystore.usage("limited").unwrap(), 2);
+
+    let error = keystore.sign("expired", PASSPHRASE, MESSAGE).unwrap_err();
+    assert!(error.to_string().contains("expired at 1"), "{}", error);
+    assert_eq!(keystore.usage("expired").unwrap(), 0);
+}
+
+#[test]
+fn concurrent_signers_never_lose_increments() {
+    const SIGNERS: usize = 8;
+    const ATTEMPTS: usize = 10;
+    const MAX_USAGE: u64 = 50;
+
+    let (_dir, keystore) = keystore();
+    keystore.insert("shared", &key(1), KeyPolicy { max_usage: MAX_USAGE, not_after: None }, PASSPHRASE).unwrap();
+
+    let granted: usize = std::thread::scope(|scope| {
+        let signers: Vec<_> = (0..SIGNERS)
+            .map(|_| {
+                let keystore = Keystore::open(keystore.root()).unwrap();
+                scope.spawn(move || (0..ATTEMPTS).filter(|_| keystore.record_usage("shared").is_ok()).count())
+            })
+            .collect();
+        signers.into_iter().map(|signer| signer.join().unwrap()).sum()
+    });
+
+    assert_eq!(granted as u64, MAX_USAGE);
+    assert_eq!(keystore.usage("shared").unwrap(), MAX_USAGE);
+}
@@ -0,0 +1,109 @@
+//! 24-word mnemonic seeds and seed passphrases
+
+use af_pqc::kdf::{KeySchedule, KeyScheduleVersion, Subkey};
+use af_pqc::mnemonic::{self, MNEMONIC_WORDS};
+use af_pqc::CryptoError;
+
+/// 256-bit entropy vectors from the BIP39 reference implementation
+const VECTORS: [([u8; 32], &str); 3] = [
+    ([0x00; 32], "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art"),
+    ([0x7f; 32], "legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth useful legal winner thank year wave sausage worth title"),
+    ([0xff; 32], "zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo zoo vote"),
+];
+
+fn details(error: CryptoError) -> String {
+    match error {
+        CryptoError::MalformedEncoding { details } => details,
+        other => panic!("unexpected error {:?}", other),
+    }
+}
+
+fn words(seed: &[u8; 32]) -> Vec<String> {
+    mnemonic::encode(seed).split(' ').map(String::from).collect()
+}
+
+#[test]
+fn encoding_matches_bip39_and_round_trips() {
+    for (seed, phrase) in VECTORS {
+        assert_eq!(mnemonic::encode(&seed).as_str(), phrase);
+        assert_eq!(*mnemonic::decode(phrase).unwrap(), seed);
+    }
+
+    let seed = [0x5a; 32];
+    let phrase = mnemonic::encode(&seed);
+    assert_eq!(phrase.split(' ').count(), MNEMONIC_WORDS);
+    assert!(phrase.split(' ').all(mnemonic::is_word));
+    let shouted = format!("  {}\n", phrase.to_uppercase().replace(' ', "\t "));
+    assert_eq!(*mnemonic::decode(&shouted).unwrap(), seed);
+}
+
+#[test]
+fn errors_name_the_offending_word() {
+    // Messages only give positions: no word of the phrase, typed or correct, is repeated
+    let assert_position_only = |phrase: &[String], position: usize| {
+        let message = details(mnemonic::decode(&phrase.join(" ")).unwrap_err());
+        assert_eq!(message, format!("Word {} is not in the BIP39 English word list", position));
+        let seed_words = words(&[0x7f; 32]);
+        let leaked: Vec<&str> = message.split_whitespace()
+            .filter(|token| seed_words.iter().chain(phrase).any(|word| word.eq_ignore_ascii_case(token)))
+            .collect();
+        assert!(leaked.is_empty(), "{:?} in {}", leaked, message);
+    };
+
+    let mut typo = words(&[0x7f; 32]);
+    typo[4] = "wavv".into();
+    assert_position_only(&typo, 5);
+
+    let mut transposed = words(&[0x7f; 32]);
+    transposed[3] = "yaer".into();
+    assert_position_only(&transposed, 4);
+
+    let mut unknown = words(&[0x7f; 32]);
+    unknown[0] = "xylophone".into();
+    assert_position_only(&unknown, 1);
+
+    let short = words(&[0x7f; 32])[..23].join(" ");
+    assert!(details(mnemonic::decode(&short).unwrap_err()).contains("found 23"));
+}
+
+#[test]
+fn checksum_catches_wrong_and_swapped_words() {
+    let mut swapped = words(&[0x7f; 32]);
+    swapped.swap(0, 1);
+    let message = details(mnemonic::decode(&swapped.join(" ")).unwrap_err());
+    assert!(message.contains("checksum"), "{}", message);
+    assert!(message.contains("words 1 and 2 may be swapped"), "{}", message);
+    assert!(message.ends_with("(word 24 carries the checksum)"), "{}", message);
+
+    // "wave" -> "have" is a one-letter typo that still spells a list word
+    let mut typo = words(&[0x7f; 32]);
+    typo[4] = "have".into();
+    let message = details(mnemonic::decode(&typo.join(" ")).unwrap_err());
+    assert!(message.contains("word 5 may be mistyped"), "{}", message);
+    assert!(!message.contains("have") && !message.contains("wave"), "{}", message);
+
+    let mut wrong = words(&[0x00; 32]);
+    wrong[23] = "zoo".into();
+    let message = details(mnemonic::decode(&wrong.join(" ")).unwrap_err());
+    assert!(message.contains("checksum") && !message.contains("zoo"), "{}", message);
+}
+
+#[test]
+fn passphrase_feeds_the_key_schedule() {
+    let seed = [7; 32];
+    let plain = KeySchedule::new(&seed);
+    let empty = KeySchedule::with_passphrase(&seed, "", KeyScheduleVersion::V1);
+    assert_eq!(*empty.derive(Subkey::KeyId), *plain.derive(Subkey::KeyId));
+
+    // PBKDF2-HMAC-SHA-512(seed, "af-pqc/passphrase/TREZOR", 2048), first 32 bytes
+    let mut stretched = [0u8; 32];
+    hex::decode_to_slice("d40895de9637d1f59fd8fa376085f2489a25d1e34a9ef8dccb82d1964caef72f", &mut stretched).unwrap();
+    let protected = KeySchedule::with_passphrase(&seed, "TREZOR", KeyScheduleVersion::V1);
+    assert_eq!(*protected.derive(Subkey::DilithiumSeed), *KeySchedule::new(&stretched).derive(Subkey::DilithiumSeed));
+    assert_ne!(*protected.derive(Subkey::DilithiumSeed), *plain.derive(Subkey::DilithiumSeed));
+
+    // Composed and decomposed forms of the same passphrase give the same keys
+    let composed = KeySchedule::with_passphrase(&seed, "caf\u{e9}", KeyScheduleVersion::V1);
+    let decomposed = KeySchedule::with_passphrase(&seed, "cafe\u{301}", KeyScheduleVersion::V1);
+    assert_eq!(*composed.derive(Subkey::Ed25519Seed), *decomposed.derive(Subkey::Ed25519Seed));
+}
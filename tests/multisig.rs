@@ -0,0 +1,110 @@
+//! Multi-signatures and signing policies
+
+use af_pqc::clock::FixedClock;
+use af_pqc::multisig::{MultiSignature, Policy};
+use af_pqc::rng::DeterministicRng;
+use af_pqc::{CryptoError, PrivateKey, PublicKey};
+
+const CONTRACT: &[u8] = b"Contract #7: delivery of 40 units by 2026-12-01";
+
+fn parties(count: u8) -> (Vec<PrivateKey>, Vec<PublicKey>) {
+    (1..=count)
+        .map(|seed| PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([seed; 32]), &FixedClock::new(1_700_000_000)).unwrap())
+        .unzip()
+}
+
+fn signed_by(keys: &[PrivateKey], indices: &[usize]) -> MultiSignature {
+    let clock = FixedClock::new(1_700_000_100);
+    let mut multisig = MultiSignature::new(CONTRACT);
+    for index in indices {
+        multisig.sign(CONTRACT, &keys[*index], &clock).unwrap();
+    }
+    multisig
+}
+
+#[test]
+fn signatures_accumulate_and_round_trip() {
+    let (keys, public) = parties(3);
+    let clock = FixedClock::new(1_700_000_100);
+    let mut multisig = MultiSignature::new(CONTRACT);
+    assert_eq!(multisig.sign(CONTRACT, &keys[2], &clock).unwrap(), public[2].fingerprint());
+
+    // A signature made elsewhere is added after checking it
+    let signature = keys[0].sign_with_clock(CONTRACT, &clock).unwrap();
+    multisig.add(CONTRACT, &public[0], signature.clone()).unwrap();
+    assert_eq!(multisig.signers().collect::<Vec<_>>(), [public[2].fingerprint(), public[0].fingerprint()]);
+
+    let json = serde_json::to_string(&multisig).unwrap();
+    let mut restored: MultiSignature = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.message_digest(), multisig.message_digest());
+    assert_eq!(restored.verify(CONTRACT, &public, &"2-of-3".parse().unwrap()).unwrap(), [public[0].fingerprint(), public[2].fingerprint()]);
+
+    let again = restored.add(CONTRACT, &public[0], signature.clone()).unwrap_err();
+    assert!(again.to_string().contains("already signed"), "{}", again);
+    assert!(restored.add(CONTRACT, &public[1], signature).is_err(), "signature of another key");
+    assert!(restored.add(b"Contract #8", &public[1], keys[1].sign_with_clock(b"Contract #8", &clock).unwrap()).is_err());
+    assert!(restored.sign(b"Contract #8", &keys[1], &clock).is_err());
+    assert_eq!(restored.signatures().len(), 2);
+}
+
+#[test]
+fn policies_count_listed_signers() {
+    let (keys, public) = parties(4);
+    let two = signed_by(&keys, &[0, 3]);
+    let all = signed_by(&keys, &[0, 1, 2, 3]);
+
+    all.verify(CONTRACT, &public, &Policy::AllOf).unwrap();
+    let error = two.verify(CONTRACT, &public, &Policy::AllOf).unwrap_err();
+    assert!(matches!(&error, CryptoError::SignatureVerification { details } if details.contains("Policy all not met: 2 of 4")), "{}", error);
+
+    two.verify(CONTRACT, &public, &"2-of-4".parse().unwrap()).unwrap();
+    assert!(two.verify(CONTRACT, &public, &"3-of-4".parse().unwrap()).is_err());
+
+    // Signers outside the listed keys do not count
+    assert!(two.verify(CONTRACT, &public[..3], &"2-of-3".parse().unwrap()).is_err());
+    two.verify(CONTRACT, &public[..1], &Policy::AllOf).unwrap();
+
+    // Weights follow the order of the listed keys
+    let weighted: Policy = "weighted:5:3,1,1,2".parse().unwrap();
+    assert_eq!(weighted, Policy::Weighted { threshold: 5, weights: vec![3, 1, 1, 2] });
+    assert_eq!(weighted.to_string(), "weighted:5:3,1,1,2");
+    two.verify(CONTRACT, &public, &weighted).unwrap();
+    let error = signed_by(&keys, &[1, 2, 3]).verify(CONTRACT, &public, &weighted).unwrap_err();
+    assert!(error.to_string().contains("signed weight 4 of 5"), "{}", error);
+
+    assert!(two.verify(b"another contract", &public, &Policy::AllOf).is_err());
+}
+
+#[test]
+fn malformed_policies_and_key_lists_are_refused() {
+    let (keys, public) = parties(3);
+    let multisig = signed_by(&keys, &[0, 1, 2]);
+
+    for policy in ["", "any", "0-of-3", "4-of-3", "2-of-x", "weighted:0:1,1", "weighted:3:1,1", "weighted:1"] {
+        assert!(policy.parse::<Policy>().is_err(), "{}", policy);
+    }
+    assert!(matches!(multisig.verify(CONTRACT, &public, &"2-of-4".parse().unwrap()), Err(CryptoError::InvalidOperation { .. })));
+    assert!(matches!(multisig.verify(CONTRACT, &public, &"weighted:1:1,1".parse().unwrap()), Err(CryptoError::InvalidOperation { .. })));
+    assert!(multisig.verify(CONTRACT, &[], &Policy::AllOf).is_err());
+    assert!(multisig.verify(CONTRACT, &[public[0].clone(), public[0].clone()], &"1-of-2".parse().unwrap()).is_err());
+    assert!(multisig.verify(CONTRACT, &public, &Policy::Threshold { threshold: 0, keys: 3 }).is_err());
+}
+
+#[test]
+fn tampered_signatures_fail_verification() {
+    let (keys, public) = parties(3);
+    let multisig = signed_by(&keys, &[0, 1, 2]);
+
+    // Swap the signatures of the first two signers
+    let mut json = serde_json::to_value(&multisig).unwrap();
+    let signatures = json["signatures"].as_array_mut().unwrap();
+    let first = signatures[0]["signature"].clone();
+    signatures[0]["signature"] = signatures[1]["signature"].clone();
+    signatures[1]["signature"] = first;
+    let swapped: MultiSignature = serde_json::from_value(json).unwrap();
+
+    let error = swapped.verify(CONTRACT, &public, &"1-of-3".parse().unwrap()).unwrap_err();
+    assert!(error.to_string().contains(&format!("Signature of key {} is invalid", public[0].fingerprint())), "{}", error);
+    // An invalid signature of a key outside the list is ignored
+    swapped.verify(CONTRACT, &public[2..], &Policy::AllOf).unwrap();
+}
@@ -0,0 +1,105 @@
+//! Injectable, health-tested randomness for key generation
+
+use af_pqc::clock::FixedClock;
+use af_pqc::rng::{DeterministicRng, HealthTestedRng, APT_WINDOW, DEFAULT_MIN_ENTROPY};
+use af_pqc::{CryptoError, PrivateKey};
+use rand_core::{CryptoRng, RngCore};
+
+/// Generator that cycles through a fixed byte pattern
+struct PatternRng {
+    pattern: Vec<u8>,
+    position: usize,
+}
+
+impl PatternRng {
+    fn new(pattern: &[u8]) -> Self {
+        Self { pattern: pattern.to_vec(), position: 0 }
+    }
+}
+
+impl RngCore for PatternRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            *byte = self.pattern[self.position % self.pattern.len()];
+            self.position += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for PatternRng {}
+
+fn entropy_failure(error: CryptoError) -> (f64, f64) {
+    match error {
+        CryptoError::KeyGeneration { threshold, actual } => (threshold, actual),
+        other => panic!("expected KeyGeneration error, got {other:?}"),
+    }
+}
+
+#[test]
+fn deterministic_rng_reproduces_keys() {
+    let clock = FixedClock::new(1_700_000_000);
+    let (first, first_public) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([7; 32]), &clock).unwrap();
+    let (_, second_public) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([7; 32]), &clock).unwrap();
+    let (_, other_public) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([8; 32]), &clock).unwrap();
+
+    assert_eq!(first_public.bytes, second_public.bytes);
+    assert_ne!(first_public.bytes, other_public.bytes);
+
+    let signature = first.sign_with_clock(b"kat message", &clock).unwrap();
+    second_public.verify(b"kat message", &signature).unwrap();
+}
+
+#[test]
+fn stuck_source_fails_repetition_count_test() {
+    let error = PrivateKey::generate_with_rng(&mut PatternRng::new(&[0x42]), &FixedClock::new(0))
+        .err()
+        .expect("stuck source must be rejected");
+
+    let (threshold, actual) = entropy_failure(error);
+    assert_eq!(threshold, DEFAULT_MIN_ENTROPY);
+    assert!(actual < threshold, "estimate {actual} should be below {threshold}");
+}
+
+#[test]
+fn biased_source_fails_adaptive_proportion_test() {
+    // Never repeats back to back, but each value makes up a quarter of the output
+    let error = PrivateKey::generate_with_rng(&mut PatternRng::new(&[1, 2, 3, 4]), &FixedClock::new(0))
+        .err()
+        .expect("biased source must be rejected");
+
+    let (_, actual) = entropy_failure(error);
+    assert!(actual < 5.0, "estimate {actual} should reflect the bias");
+}
+
+#[test]
+fn health_test_failure_is_sticky_and_clears_output() {
+    let mut rng = HealthTestedRng::new(PatternRng::new(&[0]));
+    let mut buffer = [0xffu8; 16];
+    assert!(rng.fill(&mut buffer).is_err());
+    assert_eq!(buffer, [0u8; 16]);
+    assert!(rng.fill(&mut buffer).is_err());
+}
+
+#[test]
+fn healthy_sources_pass() {
+    let mut rng = HealthTestedRng::new(DeterministicRng::from_seed([1; 32]));
+    let mut buffer = vec![0u8; 64 * APT_WINDOW];
+    rng.fill(&mut buffer).unwrap();
+
+    let mut random = [0u8; 32];
+    af_pqc::secure_random_bytes(&mut random).unwrap();
+    assert_ne!(random, [0u8; 32]);
+}
@@ -0,0 +1,149 @@
+//! Loopback tests for the HTTP signing service
+
+use af_pqc::keystore::{Keystore, KeyPolicy};
+use af_pqc::server::{token_hash, ServerConfig, SigningService};
+use af_pqc::{PrivateKey, PublicKey};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+const TOKEN: &str = "test-token";
+const PASSPHRASE: &[u8] = b"test passphrase";
+
+/// Start a service with one key on an ephemeral loopback port
+async fn start_service(config: ServerConfig) -> (SocketAddr, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let keystore = Keystore::open(dir.path().join("home")).unwrap();
+    let (private_key, _) = PrivateKey::generate().unwrap();
+    keystore.insert("alice", &private_key, KeyPolicy::default(), PASSPHRASE).unwrap();
+
+    let mut service = SigningService::new(keystore, config);
+    service.add_key("alice", PASSPHRASE, vec![token_hash(TOKEN)]).unwrap();
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(service.serve(listener));
+    (addr, dir)
+}
+
+/// Minimal HTTP/1.1 client: returns the status code and parsed JSON body
+async fn request(addr: SocketAddr, method: &str, path: &str, token: Option<&str>, body: Option<&[u8]>) -> (u16, Value) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let body = body.unwrap_or_default();
+
+    let mut head = format!("{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n", method, path, addr);
+    if let Some(token) = token {
+        head.push_str(&format!("Authorization: Bearer {}\r\n", token));
+    }
+    head.push_str(&format!("Content-Type: application/json\r\nContent-Length: {}\r\n\r\n", body.len()));
+
+    stream.write_all(head.as_bytes()).await.unwrap();
+    stream.write_all(body).await.unwrap();
+
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+    let response = String::from_utf8(response).unwrap();
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap_or(Value::Null))
+}
+
+async fn post_json(addr: SocketAddr, path: &str, token: Option<&str>, body: &Value) -> (u16, Value) {
+    request(addr, "POST", path, token, Some(body.to_string().as_bytes())).await
+}
+
+#[tokio::test]
+async fn sign_and_verify_round_trip() {
+    let (addr, _dir) = start_service(ServerConfig::default()).await;
+
+    let (status, health) = request(addr, "GET", "/v1/health", None, None).await;
+    assert_eq!(status, 200);
+    assert_eq!(health["keys"], 1);
+
+    let (status, public_key) = request(addr, "GET", "/v1/keys/alice/public-key", Some(TOKEN), None).await;
+    assert_eq!(status, 200);
+    let parsed: PublicKey = serde_json::from_value(public_key.clone()).unwrap();
+
+    let message = BASE64.encode(b"hello service");
+    let (status, signed) = post_json(addr, "/v1/keys/alice/sign", Some(TOKEN), &json!({ "message": message })).await;
+    assert_eq!(status, 200);
+    let signature = signed["signature"].clone();
+    let typed: af_pqc::Signature = serde_json::from_value(signature.clone()).unwrap();
+    parsed.verify(b"hello service", &typed).unwrap();
+
+    let verify_body = json!({ "public_key": public_key, "message": message, "signature": signature });
+    let (status, verified) = post_json(addr, "/v1/verify", None, &verify_body).await;
+    assert_eq!(status, 200);
+    assert_eq!(verified["valid"], true);
+    assert!(verified.get("error").is_none());
+
+    let tampered = json!({ "public_key": public_key, "message": BASE64.encode(b"other"), "signature": signature });
+    let (status, verified) = post_json(addr, "/v1/verify", None, &tampered).await;
+    assert_eq!(status, 200);
+    assert_eq!(verified["valid"], false);
+    assert_eq!(verified["error"]["code"], "signature_verification_failed");
+
+    // Malformed requests are still errors
+    let malformed = json!({ "public_key": public_key, "message": "not base64!", "signature": signature });
+    let (status, error) = post_json(addr, "/v1/verify", None, &malformed).await;
+    assert_eq!(status, 400);
+    assert_eq!(error["error"]["code"], "invalid_request");
+
+    let batch = json!({ "items": [verify_body, tampered] });
+    let (status, results) = post_json(addr, "/v1/verify/batch", None, &batch).await;
+    assert_eq!(status, 200);
+    assert_eq!(results["results"][0]["valid"], true);
+    assert_eq!(results["results"][1]["valid"], false);
+    assert_eq!(results["results"][1]["error"]["code"], "signature_verification_failed");
+}
+
+#[tokio::test]
+async fn rejects_unauthorized_and_unknown_keys() {
+    let (addr, _dir) = start_service(ServerConfig::default()).await;
+    let body = json!({ "message": BASE64.encode(b"hello") });
+
+    let (status, error) = post_json(addr, "/v1/keys/alice/sign", None, &body).await;
+    assert_eq!(status, 401);
+    assert_eq!(error["error"]["code"], "unauthorized");
+
+    let (status, _) = post_json(addr, "/v1/keys/alice/sign", Some("wrong-token"), &body).await;
+    assert_eq!(status, 401);
+
+    // Unknown names look like bad tokens so key names cannot be probed
+    let (status, error) = post_json(addr, "/v1/keys/mallory/sign", Some(TOKEN), &body).await;
+    assert_eq!(status, 401);
+    assert_eq!(error["error"]["code"], "unauthorized");
+    let (status, _) = post_json(addr, "/v1/keys/mallory/sign", None, &body).await;
+    assert_eq!(status, 401);
+
+    // Public keys need a token too, so they cannot be used to probe names either
+    for (path, token) in [("/v1/keys/alice/public-key", None), ("/v1/keys/alice/public-key", Some("wrong-token")), ("/v1/keys/mallory/public-key", Some(TOKEN))] {
+        let (status, error) = request(addr, "GET", path, token, None).await;
+        assert_eq!(status, 401, "{} {:?}", path, token);
+        assert_eq!(error["error"]["code"], "unauthorized");
+    }
+
+    let (status, error) = post_json(addr, "/v1/keys/alice/sign", Some(TOKEN), &json!({ "message": "" })).await;
+    assert_eq!(status, 400);
+    assert_eq!(error["error"]["code"], "invalid_operation");
+}
+
+#[tokio::test]
+async fn enforces_request_limits() {
+    let config = ServerConfig { max_body_bytes: 1024, max_batch_items: 1 };
+    let (addr, _dir) = start_service(config).await;
+
+    let large = json!({ "message": BASE64.encode(vec![0u8; 4096]) });
+    let (status, error) = post_json(addr, "/v1/keys/alice/sign", Some(TOKEN), &large).await;
+    assert_eq!(status, 413);
+    assert_eq!(error["error"]["code"], "payload_too_large");
+
+    let (status, error) = post_json(addr, "/v1/verify/batch", None, &json!({ "items": [] })).await;
+    assert_eq!(status, 200, "{}", error);
+
+    let (status, error) = request(addr, "POST", "/v1/verify", None, Some(b"not json")).await;
+    assert_eq!(status, 400);
+    assert_eq!(error["error"]["code"], "invalid_request");
+}
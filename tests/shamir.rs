@@ -0,0 +1,132 @@
+//! Shamir sharing of master seeds and dealer-signed shares
+
+use af_pqc::clock::FixedClock;
+use af_pqc::rng::DeterministicRng;
+use af_pqc::shamir::{self, DealtShare, SeedShare};
+use af_pqc::{CryptoError, PrivateKey};
+
+const SEED: [u8; 32] = [0x3c; 32];
+
+fn split(threshold: u8, count: u8, rng_seed: u8) -> Vec<SeedShare> {
+    shamir::split_with_rng(&SEED, threshold, count, &mut DeterministicRng::from_seed([rng_seed; 32])).unwrap()
+}
+
+fn dealer(rng_seed: u8) -> PrivateKey {
+    PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([rng_seed; 32]), &FixedClock::new(1_700_000_000)).unwrap().0
+}
+
+#[test]
+fn any_threshold_subset_restores_the_seed() {
+    let shares = split(3, 5, 1);
+    assert!(shares.iter().all(|share| share.threshold() == 3 && share.group_id() == shares[0].group_id()));
+    assert_eq!(shares.iter().map(SeedShare::index).collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+    for a in 0..5 {
+        for b in a + 1..5 {
+            for c in b + 1..5 {
+                let subset = [shares[c].clone(), shares[a].clone(), shares[b].clone()];
+                assert_eq!(*shamir::combine(&subset).unwrap(), SEED);
+            }
+        }
+    }
+    assert_eq!(*shamir::combine(&shares).unwrap(), SEED);
+
+    let too_few = shamir::combine(&shares[..2]).unwrap_err();
+    assert!(too_few.to_string().contains("Need 3 shares"), "{}", too_few);
+    assert!(shamir::split_with_rng(&SEED, 1, 5, &mut DeterministicRng::from_seed([1; 32])).is_err());
+    assert!(shamir::split_with_rng(&SEED, 4, 3, &mut DeterministicRng::from_seed([1; 32])).is_err());
+}
+
+#[test]
+fn mismatched_share_sets_are_refused() {
+    let shares = split(2, 3, 1);
+    let other = split(2, 3, 2);
+    assert_ne!(shares[0].group_id(), other[0].group_id());
+
+    let mixed = shamir::combine(&[shares[0].clone(), other[1].clone()]).unwrap_err();
+    assert!(mixed.to_string().contains("different splits"), "{}", mixed);
+
+    let repeated = shamir::combine(&[shares[1].clone(), shares[1].clone()]).unwrap_err();
+    assert!(repeated.to_string().contains("Share 2 given more than once"), "{}", repeated);
+
+    let thresholds = shamir::combine(&[shares[0].clone(), split(3, 3, 1)[1].clone()]).unwrap_err();
+    assert!(matches!(thresholds, CryptoError::InvalidOperation { .. }));
+}
+
+#[test]
+fn extra_shares_must_be_consistent() {
+    let shares = split(2, 3, 1);
+    let other = split(2, 3, 2);
+    let consistent: SeedShare = serde_json::from_value(serde_json::to_value(&shares[2]).unwrap()).unwrap();
+    assert_eq!(*shamir::combine(&[shares[0].clone(), shares[1].clone(), consistent]).unwrap(), SEED);
+
+    // Re-encode share 3 of the other split under this split's group id
+    let mut bytes = other[2].to_bytes().to_vec();
+    bytes[1..9].copy_from_slice(&shares[0].group_id());
+    let body = bytes.len() - 4;
+    let checksum = {
+        use sha2::{Digest, Sha256};
+        Sha256::new().chain_update(b"af-pqc/shamir/share").chain_update(&bytes[..body]).finalize()
+    };
+    bytes[body..].copy_from_slice(&checksum[..4]);
+    let foreign: SeedShare = format!("af-share-{}", hex::encode(&bytes)).parse().unwrap();
+
+    let error = shamir::combine(&[shares[0].clone(), shares[1].clone(), foreign]).unwrap_err();
+    assert!(error.to_string().contains("Share 3 is inconsistent"), "{}", error);
+}
+
+#[test]
+fn encoding_round_trips_and_detects_typos() {
+    let share = split(3, 5, 1).remove(3);
+    let text = share.encode();
+    assert!(text.starts_with("af-share-01"));
+    let parsed: SeedShare = text.parse().unwrap();
+    assert_eq!((parsed.index(), parsed.threshold(), parsed.group_id()), (4, 3, share.group_id()));
+    assert_eq!(*parsed.to_bytes(), *share.to_bytes());
+    assert!(!format!("{:?}", share).contains(&text[30..50]), "Debug output is redacted");
+
+    let mut typo = text.to_string();
+    let last = typo.pop().unwrap();
+    typo.push(if last == '0' { '1' } else { '0' });
+    assert!(matches!(typo.parse::<SeedShare>(), Err(CryptoError::MalformedEncoding { details }) if details.contains("checksum")));
+    assert!("af-share-01".parse::<SeedShare>().is_err());
+    assert!(text[9..].parse::<SeedShare>().is_err());
+}
+
+#[test]
+fn dealer_signatures_bind_shares_to_the_dealer() {
+    let clock = FixedClock::new(1_700_000_000);
+    let (alice, mallory) = (dealer(7), dealer(8));
+    let alice_fingerprint = alice.public_key().unwrap().fingerprint();
+    let shares = split(2, 3, 1);
+    let signed: Vec<DealtShare> = shares.iter()
+        .map(|share| DealtShare::sign(share.clone(), &alice, &clock).unwrap())
+        .collect();
+
+    let json = serde_json::to_string(&signed[0]).unwrap();
+    let restored: DealtShare = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.verify_dealer().unwrap(), Some(alice_fingerprint));
+
+    let (seed, signer) = shamir::combine_dealt(&signed[..2], Some(&alice_fingerprint)).unwrap();
+    assert_eq!((*seed, signer), (SEED, Some(alice_fingerprint)));
+
+    let wrong_dealer = mallory.public_key().unwrap().fingerprint();
+    assert!(matches!(shamir::combine_dealt(&signed[..2], Some(&wrong_dealer)), Err(CryptoError::SignatureVerification { .. })));
+
+    let mixed = [signed[0].clone(), DealtShare::sign(shares[1].clone(), &mallory, &clock).unwrap()];
+    assert!(matches!(shamir::combine_dealt(&mixed, None), Err(CryptoError::SignatureVerification { .. })));
+
+    let partly_signed = [signed[0].clone(), DealtShare::unsigned(shares[1].clone())];
+    assert!(matches!(shamir::combine_dealt(&partly_signed, None), Err(CryptoError::SignatureVerification { .. })));
+
+    let unsigned: Vec<DealtShare> = shares.iter().cloned().map(DealtShare::unsigned).collect();
+    assert!(shamir::combine_dealt(&unsigned, Some(&alice_fingerprint)).is_err());
+    assert_eq!(shamir::combine_dealt(&unsigned, None).unwrap().1, None);
+
+    // A signature moved onto another share no longer verifies
+    let mut swapped = signed[1].clone();
+    swapped.dealer = signed[0].dealer.clone();
+    assert!(swapped.verify_dealer().is_err());
+    let dealer = signed[0].dealer.clone().unwrap();
+    assert!(DealtShare::signed(shares[1].clone(), dealer.public_key, dealer.signature).is_err());
+}
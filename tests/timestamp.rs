@@ -0,0 +1,90 @@
+//! RFC 3161-style timestamp tokens from a local authority
+
+use af_pqc::clock::FixedClock;
+use af_pqc::rng::DeterministicRng;
+use af_pqc::timestamp::{LocalTimestampAuthority, TimestampAuthority, TimestampRequest, TimestampToken, TimestampedSignature};
+use af_pqc::{CryptoError, PrivateKey, Signature};
+
+const MESSAGE: &[u8] = b"contract v3, signed 2025-06-15";
+const SIGNED_AT: u64 = 1_750_000_000;
+
+fn key(seed: u8) -> PrivateKey {
+    PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([seed; 32]), &FixedClock::new(1_700_000_000)).unwrap().0
+}
+
+fn signature() -> Signature {
+    key(1).sign_with_clock(MESSAGE, &FixedClock::new(SIGNED_AT)).unwrap()
+}
+
+#[test]
+fn local_authority_numbers_and_times_its_tokens() {
+    let clock = FixedClock::new(SIGNED_AT + 30);
+    let authority = LocalTimestampAuthority::new(key(7), &clock);
+    let request = TimestampRequest::new(&signature());
+
+    let first = authority.timestamp(&request).unwrap();
+    clock.advance(60);
+    let second = authority.timestamp(&request).unwrap();
+    assert_eq!((first.serial(), first.time()), (1, SIGNED_AT + 30));
+    assert_eq!((second.serial(), second.time()), (2, SIGNED_AT + 90));
+    assert_eq!(first.authority().fingerprint(), authority.public_key().unwrap().fingerprint());
+
+    let resumed = LocalTimestampAuthority::with_first_serial(key(7), &clock, 1_000);
+    assert_eq!(resumed.timestamp(&request).unwrap().serial(), 1_000);
+
+    // The request reveals only a digest of the signature
+    let json = serde_json::to_string(&request).unwrap();
+    assert_eq!(json, format!("{{\"signature_digest\":\"{}\"}}", hex::encode(request.signature_digest())));
+}
+
+#[test]
+fn timestamped_signatures_verify_against_the_authority_key() {
+    let authority = LocalTimestampAuthority::new(key(7), FixedClock::new(SIGNED_AT + 5));
+    let authority_key = authority.public_key().unwrap();
+    let signer_key = key(1).public_key().unwrap();
+
+    let stamped = TimestampedSignature::request(signature(), &authority).unwrap();
+    assert_eq!(stamped.verify(MESSAGE, &signer_key, &authority_key).unwrap(), SIGNED_AT + 5);
+    assert!(stamped.verify(b"contract v4", &signer_key, &authority_key).is_err());
+
+    // A token from another authority is refused
+    let impostor = key(8).public_key().unwrap();
+    let error = stamped.verify_timestamp(&impostor).unwrap_err();
+    assert!(error.to_string().contains("was issued by"), "{}", error);
+
+    // A token for another signature cannot be attached
+    let (_, token) = stamped.into_parts();
+    let other = key(2).sign_with_clock(MESSAGE, &FixedClock::new(SIGNED_AT)).unwrap();
+    assert!(matches!(TimestampedSignature::new(other, token), Err(CryptoError::SignatureVerification { .. })));
+}
+
+#[test]
+fn timestamped_signatures_still_read_as_signatures() {
+    let authority = LocalTimestampAuthority::new(key(7), FixedClock::new(SIGNED_AT + 5));
+    let stamped = TimestampedSignature::request(signature(), &authority).unwrap();
+    let json = serde_json::to_string(&stamped).unwrap();
+
+    let plain: Signature = serde_json::from_str(&json).unwrap();
+    assert_eq!(plain.bytes, signature().bytes);
+    key(1).public_key().unwrap().verify(MESSAGE, &plain).unwrap();
+
+    let parsed: TimestampedSignature = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.verify_timestamp(&authority.public_key().unwrap()).unwrap(), SIGNED_AT + 5);
+    assert!(serde_json::from_str::<TimestampedSignature>(&serde_json::to_string(&plain).unwrap()).is_err());
+}
+
+#[test]
+fn tokens_bind_time_and_serial() {
+    let signature = signature();
+    let authority = key(7);
+    let token = TimestampToken::issue(&authority, &signature, 9, &FixedClock::new(SIGNED_AT)).unwrap();
+    let authority_key = authority.public_key().unwrap();
+    assert_eq!(token.verify_with_authority(&signature, &authority_key).unwrap(), SIGNED_AT);
+
+    for (field, value) in [("time", SIGNED_AT + 1), ("serial", 10)] {
+        let mut json = serde_json::to_value(&token).unwrap();
+        json[field] = value.into();
+        let altered: TimestampToken = serde_json::from_value(json).unwrap();
+        assert!(altered.verify_with_authority(&signature, &authority_key).is_err(), "{} changed", field);
+    }
+}
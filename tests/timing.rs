@@ -0,0 +1,132 @@
+//! Statistical timing tests for comparisons, signing, verification and the
+//! keypair cache
+//!
+//! Timings depend on the machine and its load, so these are ignored by
+//! default. Run them on an otherwise idle machine with
+//! `cargo test --release --features fuzzing --test timing -- --ignored --nocapture --test-threads 1`.
+
+use af_pqc::clock::FixedClock;
+use af_pqc::constant_time;
+use af_pqc::rng::DeterministicRng;
+use af_pqc::timing::{self, Class, TimingReport};
+use af_pqc::{Fingerprint, PrivateKey};
+use rand_core::{OsRng, RngCore};
+
+const SEED: [u8; 32] = [0x42; 32];
+const MESSAGE: &[u8] = b"timing test message";
+
+fn report(operation: &str, report: &TimingReport) {
+    println!(
+        "{}: |t| = {:.2} over {} samples (crop {:?}): {}",
+        operation,
+        report.max_t,
+        report.samples,
+        report.crop,
+        if report.leaks() { "leakage detected" } else { "no leakage detected" }
+    );
+}
+
+fn random_array<const N: usize>(rng: &mut dyn RngCore) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn test_key() -> PrivateKey {
+    let clock = FixedClock::new(1_700_000_000);
+    PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed(SEED), &clock).unwrap().0
+}
+
+#[test]
+fn harness_detects_input_dependent_timing() {
+    let result = timing::measure(2_000, &mut OsRng, |class, _| match class {
+        Class::Fixed => 0u32,
+        Class::Random => 20_000,
+    }, |rounds| (0..std::hint::black_box(rounds)).fold(0u64, |acc, i| std::hint::black_box(acc ^ u64::from(i))));
+    assert!(result.leaks(), "{:?}", result);
+    assert!(matches!(result.check("spin"), Err(af_pqc::CryptoError::SideChannelAttack { .. })));
+}
+
+#[test]
+#[ignore = "timing-sensitive; run with --ignored"]
+fn comparisons_are_constant_time() {
+    let secret = Fingerprint::from_bytes(random_array(&mut OsRng));
+
+    let result = timing::measure(200_000, &mut OsRng, |class, rng| match class {
+        Class::Fixed => secret,
+        Class::Random => Fingerprint::from_bytes(random_array(rng)),
+    }, |candidate| candidate == secret);
+    report("fingerprint equality", &result);
+    result.check("fingerprint equality").unwrap();
+
+    let tokens: Vec<[u8; 32]> = (0..4).map(|_| random_array(&mut OsRng)).collect();
+    let result = timing::measure(200_000, &mut OsRng, |class, rng| match class {
+        Class::Fixed => tokens[0],
+        Class::Random => random_array(rng),
+    }, |candidate| constant_time::contains(&tokens, &candidate));
+    report("token lookup", &result);
+    result.check("token lookup").unwrap();
+}
+
+/// Near-miss and random authentication tags must fail in the same time
+#[test]
+#[ignore = "timing-sensitive; run with --ignored"]
+fn cache_decryption_is_constant_time() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_current_dir(dir.path()).unwrap();
+    // Only legacy keys are cached
+    let legacy = af_pqc::kdf::KeySchedule::with_version(&SEED, af_pqc::kdf::KeyScheduleVersion::Legacy);
+    af_pqc::generate_key_from_schedule(&legacy, af_pqc::clock::default_clock()).unwrap();
+    let cache = std::fs::read_dir(dir.path()).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.file_name().unwrap().to_string_lossy().starts_with(".af_dilithium_"))
+        .expect("cache file written");
+    let file_data = std::fs::read(cache).unwrap();
+    af_pqc::fuzzing::parse_cache_file(&SEED, &file_data).unwrap();
+
+    let tag_start = file_data.len() - 16;
+    let mut near_miss = file_data.clone();
+    near_miss[file_data.len() - 1] ^= 1;
+
+    let result = timing::measure(20_000, &mut OsRng, |class, rng| match class {
+        Class::Fixed => near_miss.clone(),
+        Class::Random => {
+            let mut data = file_data.clone();
+            rng.fill_bytes(&mut data[tag_start..]);
+            data
+        }
+    }, |data| af_pqc::fuzzing::parse_cache_file(&SEED, &data).is_err());
+    report("cache decryption", &result);
+    result.check("cache decryption").unwrap();
+}
+
+/// Reported only: Dilithium's rejection sampling repeats a message-dependent
+/// number of times, which is expected and reveals nothing about the key
+#[test]
+#[ignore = "timing-sensitive; run with --ignored"]
+fn sign_timing() {
+    let key = test_key();
+    let result = timing::measure(2_000, &mut OsRng, |class, rng| match class {
+        Class::Fixed => MESSAGE.to_vec(),
+        Class::Random => random_array::<19>(rng).to_vec(),
+    }, |message| key.sign(&message).unwrap());
+    report("sign", &result);
+}
+
+/// Reported only: verification handles public data
+#[test]
+#[ignore = "timing-sensitive; run with --ignored"]
+fn verify_timing() {
+    let key = test_key();
+    let public_key = key.public_key().unwrap();
+    let fixed = key.sign(MESSAGE).unwrap();
+    let result = timing::measure(2_000, &mut OsRng, |class, rng| match class {
+        Class::Fixed => (MESSAGE.to_vec(), fixed.clone()),
+        Class::Random => {
+            let message = random_array::<19>(rng).to_vec();
+            let signature = key.sign(&message).unwrap();
+            (message, signature)
+        }
+    }, |(message, signature)| public_key.verify(&message, &signature).is_ok());
+    report("verify", &result);
+}
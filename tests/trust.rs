@@ -0,0 +1,126 @@
+//! Trust store of pinned public keys with usages and validity windows
+
+use af_pqc::bundle::{KeyCertificate, SignatureBundle};
+use af_pqc::clock::FixedClock;
+use af_pqc::rng::DeterministicRng;
+use af_pqc::timestamp::TimestampToken;
+use af_pqc::trust::{KeyUsage, TrustStore, TrustedKey};
+use af_pqc::{CryptoError, PrivateKey, PublicKey};
+
+const RELEASE: &[u8] = b"release v2.4.1";
+const NOW: u64 = 1_750_000_000;
+
+fn key(seed: u8) -> (PrivateKey, PublicKey) {
+    PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([seed; 32]), &FixedClock::new(1_700_000_000)).unwrap()
+}
+
+fn trusted(label: &str, public_key: &PublicKey, usages: &[KeyUsage]) -> TrustedKey {
+    TrustedKey::new(label, public_key.clone(), usages).unwrap()
+}
+
+#[test]
+fn labels_and_fingerprints_are_unique() {
+    let ((_, alice), (_, bob)) = (key(1), key(2));
+    let mut store = TrustStore::new();
+    store.add(trusted("release", &bob, &[KeyUsage::Sign])).unwrap();
+    store.add(trusted("alice", &alice, &[KeyUsage::Sign, KeyUsage::Sign, KeyUsage::Certify])).unwrap();
+    assert_eq!(store.keys().iter().map(|key| key.label.as_str()).collect::<Vec<_>>(), ["alice", "release"]);
+    assert_eq!(store.keys()[0].usages, [KeyUsage::Sign, KeyUsage::Certify]);
+
+    assert!(store.add(trusted("alice", &bob, &[KeyUsage::Sign])).is_err());
+    let error = store.add(trusted("alice again", &alice, &[KeyUsage::Sign])).unwrap_err();
+    assert!(error.to_string().contains("already trusted as 'alice'"), "{}", error);
+    assert!(TrustedKey::new("", alice.clone(), &[KeyUsage::Sign]).is_err());
+    assert!(TrustedKey::new("alice", alice.clone(), &[]).is_err());
+    assert!(trusted("alice", &alice, &[KeyUsage::Sign]).with_validity(Some(NOW), Some(NOW - 1)).is_err());
+    assert!("sign,certify".parse::<KeyUsage>().is_err());
+    assert_eq!("timestamp".parse::<KeyUsage>().unwrap(), KeyUsage::Timestamp);
+
+    assert_eq!(store.get(&bob.fingerprint()).unwrap().label, "release");
+    assert_eq!(store.remove(&bob.fingerprint().to_string()).unwrap().label, "release");
+    assert_eq!(store.remove("alice").unwrap().fingerprint(), alice.fingerprint());
+    assert!(matches!(store.remove("alice"), Err(CryptoError::KeyNotFound { .. })));
+}
+
+#[test]
+fn verification_reports_the_trusted_identity() {
+    let clock = FixedClock::new(NOW);
+    let ((root, root_public), (service, service_public), (_, other)) = (key(1), key(2), key(3));
+    let mut store = TrustStore::new();
+    store.add(trusted("Root CA", &root_public, &[KeyUsage::Certify])).unwrap();
+    store.add(trusted("Ops", &other, &[KeyUsage::Sign])).unwrap();
+
+    // By fingerprint, for signatures without a bundle
+    let signature = service.sign_with_clock(RELEASE, &clock).unwrap();
+    assert!(store.verify(RELEASE, &signature, &service_public.fingerprint(), NOW).is_err());
+    let error = store.verify(RELEASE, &root.sign_with_clock(RELEASE, &clock).unwrap(), &root_public.fingerprint(), NOW).unwrap_err();
+    assert!(error.to_string().contains("'Root CA' is not trusted for sign"), "{}", error);
+
+    let certificate = KeyCertificate::issue(&root, &service_public, &clock).unwrap();
+    let bundle = SignatureBundle::new(signature, service_public.clone()).unwrap().with_certificate(certificate).unwrap();
+    let verified = store.verify_bundle(&bundle, RELEASE, NOW).unwrap();
+    assert_eq!((verified.identity.label.as_str(), verified.verification.chain_length), ("Root CA", 1));
+
+    // A key trusted only to sign does not certify others
+    let mut store = TrustStore::new();
+    store.add(trusted("Root CA", &root_public, &[KeyUsage::Sign])).unwrap();
+    assert!(store.verify_bundle(&bundle, RELEASE, NOW).is_err());
+
+    // A directly trusted signer that expired falls back to a valid certifier
+    store.add(trusted("Service", &service_public, &[KeyUsage::Sign]).with_validity(None, Some(NOW - 1)).unwrap()).unwrap();
+    assert!(store.verify_bundle(&bundle, RELEASE, NOW).is_err());
+    store.remove("Root CA").unwrap();
+    store.add(trusted("Root CA", &root_public, &[KeyUsage::Certify])).unwrap();
+    assert_eq!(store.verify_bundle(&bundle, RELEASE, NOW).unwrap().identity.label, "Root CA");
+    assert_eq!(store.verify_bundle(&bundle, RELEASE, NOW - 1).unwrap().identity.label, "Service");
+}
+
+#[test]
+fn trusted_timestamps_set_the_validity_time() {
+    let ((signer, signer_public), (authority, authority_public)) = (key(1), key(7));
+    let bundle = SignatureBundle::sign(RELEASE, &signer, &FixedClock::new(NOW)).unwrap();
+    let token = TimestampToken::issue(&authority, bundle.signature(), 1, &FixedClock::new(NOW)).unwrap();
+    let stamped = bundle.clone().with_timestamp(token).unwrap();
+
+    let mut store = TrustStore::new();
+    store.add(trusted("Release 2025", &signer_public, &[KeyUsage::Sign]).with_validity(Some(NOW - 100), Some(NOW + 100)).unwrap()).unwrap();
+    let later = NOW + 1_000;
+    assert!(store.verify_bundle(&bundle, RELEASE, later).is_err(), "expired without a timestamp");
+    assert!(store.verify_bundle(&stamped, RELEASE, later).is_err(), "authority not trusted");
+
+    store.add(trusted("TSA", &authority_public, &[KeyUsage::Sign])).unwrap();
+    assert!(store.verify_bundle(&stamped, RELEASE, later).is_err(), "authority not trusted for timestamps");
+    store.remove("TSA").unwrap();
+    store.add(trusted("TSA", &authority_public, &[KeyUsage::Timestamp])).unwrap();
+    let verified = store.verify_bundle(&stamped, RELEASE, later).unwrap();
+    assert_eq!(verified.identity.label, "Release 2025");
+    assert_eq!((verified.timestamp_authority.unwrap().label.as_str(), verified.verification.timestamp), ("TSA", Some(NOW)));
+}
+
+#[test]
+fn store_files_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("trust.json");
+    assert!(TrustStore::load(&path).unwrap().keys().is_empty());
+
+    let ((_, alice), (_, bob)) = (key(1), key(2));
+    let mut store = TrustStore::new();
+    store.add(trusted("alice", &alice, &[KeyUsage::Sign]).with_validity(Some(1), None).unwrap()).unwrap();
+    store.add(trusted("bob", &bob, &[KeyUsage::Timestamp])).unwrap();
+    store.save(&path).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        assert_eq!(std::fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    let loaded = TrustStore::load(&path).unwrap();
+    assert_eq!(loaded.keys().len(), 2);
+    assert_eq!((loaded.keys()[0].not_before, loaded.keys()[1].usages.as_slice()), (Some(1), [KeyUsage::Timestamp].as_slice()));
+
+    // A hand-edited file trusting one key twice is refused
+    let mut json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    json["keys"][1]["public_key"] = json["keys"][0]["public_key"].clone();
+    std::fs::write(&path, json.to_string()).unwrap();
+    assert!(TrustStore::load(&path).is_err());
+}
@@ -0,0 +1,232 @@
+//! Witness commitment log and keys bound to witnessed policies
+
+use af_pqc::clock::{Clock, FixedClock, WitnessClock};
+use af_pqc::rng::DeterministicRng;
+use af_pqc::trust::{KeyUsage, TrustStore, TrustedKey};
+use af_pqc::witness::{generate_bound_key_with_rng, EntryType, FileWitness, LocalWitness, WitnessBackend};
+use af_pqc::{Fingerprint, PrivateKey, PublicKey};
+use sha2::{Digest, Sha256};
+
+const WITNESSED_AT: u64 = 1_750_000_000;
+
+fn key(seed: u8) -> PrivateKey {
+    PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed([seed; 32]), &FixedClock::new(1_700_000_000)).unwrap().0
+}
+
+fn fingerprint(seed: u8) -> Fingerprint {
+    key(seed).public_key().unwrap().fingerprint()
+}
+
+fn policy_hash(policy: &str) -> [u8; 32] {
+    Sha256::digest(policy.as_bytes()).into()
+}
+
+#[test]
+fn commitments_are_chained_signed_and_persisted() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("witness.json");
+    let clock = FixedClock::new(WITNESSED_AT);
+    let mut witness = FileWitness::open_with_clock(&path, &fingerprint(9), &clock).unwrap().with_signing_key(key(9)).unwrap();
+
+    let first = witness.commit(policy_hash("allow: deploy"), EntryType::PolicyCreate).unwrap();
+    clock.advance(10);
+    let second = witness.commit(policy_hash("allow: deploy, rollback"), EntryType::PolicyUpdate).unwrap();
+    assert_eq!((first.sequence, second.sequence, second.time), (0, 1, WITNESSED_AT + 10));
+    assert_eq!(witness.entries()[1].previous, first.commitment_hash);
+
+    let reopened = FileWitness::open(&path, &fingerprint(9)).unwrap();
+    assert_eq!(reopened.verify_witness_commitment(policy_hash("allow: deploy"), EntryType::PolicyCreate).unwrap(), first);
+    assert!(reopened.verify_witness_commitment(policy_hash("allow: deploy"), EntryType::PolicyUpdate).is_err());
+    assert!(reopened.verify_witness_commitment(policy_hash("allow: all"), EntryType::PolicyCreate).is_err());
+
+    // Only the pinned key may append, and a read-only log refuses to
+    let mut read_only = FileWitness::open(&path, &fingerprint(9)).unwrap();
+    assert!(read_only.commit(policy_hash("allow: all"), EntryType::PolicyCreate).is_err());
+    assert!(FileWitness::open(&path, &fingerprint(9)).unwrap().with_signing_key(key(8)).is_err());
+}
+
+#[test]
+fn logs_are_pinned_to_the_expected_witness() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("witness.json");
+
+    // Even a new log only takes the pinned key
+    assert!(FileWitness::open(&path, &fingerprint(9)).unwrap().with_signing_key(key(8)).is_err());
+    let mut witness = FileWitness::open(&path, &fingerprint(9)).unwrap().with_signing_key(key(9)).unwrap();
+    witness.commit(policy_hash("allow: deploy"), EntryType::PolicyCreate).unwrap();
+
+    // A log replaced by one that is well-formed but signed by another key is refused
+    let forged = dir.path().join("forged.json");
+    let mut forger = FileWitness::open(&forged, &fingerprint(8)).unwrap().with_signing_key(key(8)).unwrap();
+    forger.commit(policy_hash("allow: all"), EntryType::PolicyCreate).unwrap();
+    std::fs::copy(&forged, &path).unwrap();
+    let error = FileWitness::open(&path, &fingerprint(9)).unwrap_err();
+    assert!(error.to_string().contains("not the pinned witness"), "{}", error);
+    assert!(witness.commit(policy_hash("allow: rollback"), EntryType::PolicyCreate).is_err());
+}
+
+#[test]
+fn concurrent_committers_never_lose_entries() {
+    const COMMITTERS: u8 = 4;
+    const COMMITS: u8 = 5;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("witness.json");
+    std::thread::scope(|scope| {
+        for committer in 0..COMMITTERS {
+            // Every handle is opened before any commit, so each starts from an empty log
+            let mut witness = FileWitness::open(&path, &fingerprint(9)).unwrap().with_signing_key(key(9)).unwrap();
+            scope.spawn(move || {
+                for commit in 0..COMMITS {
+                    witness.commit(policy_hash(&format!("{} {}", committer, commit)), EntryType::PolicyCreate).unwrap();
+                }
+            });
+        }
+    });
+
+    let log = FileWitness::open(&path, &fingerprint(9)).unwrap();
+    assert_eq!(log.entries().len(), usize::from(COMMITTERS * COMMITS));
+    for committer in 0..COMMITTERS {
+        for commit in 0..COMMITS {
+            log.verify_witness_commitment(policy_hash(&format!("{} {}", committer, commit)), EntryType::PolicyCreate).unwrap();
+        }
+    }
+}
+
+#[test]
+fn live_commitments_are_not_repeated() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("witness.json");
+    let mut witness = FileWitness::open_with_clock(&path, &fingerprint(9), FixedClock::new(WITNESSED_AT)).unwrap().with_signing_key(key(9)).unwrap();
+    let policy = policy_hash("allow: deploy");
+    let first = witness.commit(policy, EntryType::PolicyCreate).unwrap();
+    let (_, public_key, binding) = generate_bound_key_with_rng(&policy, &witness, &mut DeterministicRng::from_seed([1; 32])).unwrap();
+
+    // Committing the same policy again would move its proof away from the binding
+    let error = witness.commit(policy, EntryType::PolicyCreate).unwrap_err();
+    assert!(error.to_string().contains("already holds PolicyCreate commitment 0"), "{}", error);
+    let mut other = FileWitness::open(&path, &fingerprint(9)).unwrap().with_signing_key(key(9)).unwrap();
+    assert!(other.commit(policy, EntryType::PolicyCreate).is_err());
+    assert_eq!(FileWitness::open(&path, &fingerprint(9)).unwrap().entries().len(), 1);
+    assert_eq!(witness.verify_witness_commitment(policy, EntryType::PolicyCreate).unwrap(), first);
+    binding.verify(&public_key, &witness).unwrap();
+
+    // Other entry types for the same digest are separate commitments
+    witness.commit(policy, EntryType::PolicyUpdate).unwrap();
+    binding.verify(&public_key, &witness).unwrap();
+    let (_, again, later) = generate_bound_key_with_rng(&policy, &witness, &mut DeterministicRng::from_seed([2; 32])).unwrap();
+    later.verify(&again, &witness).unwrap();
+    binding.verify(&public_key, &witness).unwrap();
+}
+
+#[test]
+fn revoked_policies_no_longer_verify() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("witness.json");
+    let mut witness = FileWitness::open_with_clock(&path, &fingerprint(9), FixedClock::new(WITNESSED_AT)).unwrap().with_signing_key(key(9)).unwrap();
+    let policy = policy_hash("allow: deploy");
+    witness.commit(policy, EntryType::PolicyCreate).unwrap();
+    let (_, public_key, binding) = generate_bound_key_with_rng(&policy, &witness, &mut DeterministicRng::from_seed([1; 32])).unwrap();
+    binding.verify(&public_key, &witness).unwrap();
+
+    let revoke = witness.commit(policy, EntryType::PolicyRevoke).unwrap();
+    let error = witness.verify_witness_commitment(policy, EntryType::PolicyCreate).unwrap_err();
+    assert!(error.to_string().contains(&format!("at entry {}", revoke.sequence)), "{}", error);
+    assert!(binding.verify(&public_key, &witness).is_err());
+    assert!(generate_bound_key_with_rng(&policy, &witness, &mut DeterministicRng::from_seed([1; 32])).is_err());
+    assert_eq!(witness.verify_witness_commitment(policy, EntryType::PolicyRevoke).unwrap(), revoke);
+
+    // Committing the policy again after the revocation makes it current again
+    let recreated = witness.commit(policy, EntryType::PolicyCreate).unwrap();
+    assert_eq!(witness.verify_witness_commitment(policy, EntryType::PolicyCreate).unwrap(), recreated);
+}
+
+#[test]
+fn tampered_logs_are_refused() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("witness.json");
+    let mut witness = FileWitness::open_with_clock(&path, &fingerprint(9), FixedClock::new(WITNESSED_AT)).unwrap().with_signing_key(key(9)).unwrap();
+    witness.commit(policy_hash("allow: deploy"), EntryType::PolicyCreate).unwrap();
+    witness.commit(policy_hash("allow: rollback"), EntryType::PolicyCreate).unwrap();
+    let original: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+
+    let edits: [fn(&mut serde_json::Value); 4] = [
+        |log| log["entries"][0]["digest"] = hex::encode(policy_hash("allow: all")).into(),
+        |log| log["entries"][1]["time"] = (WITNESSED_AT - 1).into(),
+        |log| { log["entries"].as_array_mut().unwrap().remove(0); },
+        // Entries stay signed by the original witness key
+        |log| log["witness"] = serde_json::to_value(key(8).public_key().unwrap()).unwrap(),
+    ];
+
+    for (index, edit) in edits.iter().enumerate() {
+        let mut log = original.clone();
+        edit(&mut log);
+        std::fs::write(&path, log.to_string()).unwrap();
+        assert!(FileWitness::open(&path, &fingerprint(9)).is_err(), "edit {} accepted", index);
+    }
+}
+
+#[test]
+fn bound_keys_are_timestamped_by_the_witness() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("witness.json");
+    let clock = FixedClock::new(WITNESSED_AT);
+    let mut witness = FileWitness::open_with_clock(&path, &fingerprint(9), &clock).unwrap().with_signing_key(key(9)).unwrap();
+    let policy = policy_hash("allow: deploy");
+
+    let Err(error) = generate_bound_key_with_rng(&policy, &witness, &mut DeterministicRng::from_seed([1; 32])) else {
+        panic!("bound a key to an uncommitted policy");
+    };
+    assert!(error.to_string().contains("Witness commitment verification failed"), "{}", error);
+    witness.commit(policy, EntryType::PolicyCreate).unwrap();
+
+    // A clock behind the log reads the time of the latest entry
+    let behind = FileWitness::open_with_clock(&path, &fingerprint(9), FixedClock::new(WITNESSED_AT - 500)).unwrap();
+    assert_eq!(WitnessClock(&behind).now(), WITNESSED_AT);
+
+    let (private_key, public_key, binding) =
+        generate_bound_key_with_rng(&policy, &behind, &mut DeterministicRng::from_seed([1; 32])).unwrap();
+    assert_eq!((public_key.created_at, binding.signature.created_at), (WITNESSED_AT, WITNESSED_AT));
+    assert_eq!(private_key.public_key().unwrap().fingerprint(), public_key.fingerprint());
+    binding.verify(&public_key, &behind).unwrap();
+    assert!(binding.verify(&key(2).public_key().unwrap(), &behind).is_err());
+
+    // The same rng gives the same key, bound to the same commitment
+    let (_, again, _) = generate_bound_key_with_rng(&policy, &behind, &mut DeterministicRng::from_seed([1; 32])).unwrap();
+    assert_eq!(again.fingerprint(), public_key.fingerprint());
+}
+
+#[test]
+fn local_witness_reads_the_log_in_af_home() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var("AF_HOME", dir.path());
+    let policy = policy_hash("allow: deploy");
+    let trust_path = TrustStore::default_path().unwrap();
+    let trust = |keys: &[(&str, PublicKey)]| {
+        let mut store = TrustStore::new();
+        for (label, public_key) in keys {
+            store.add(TrustedKey::new(label, public_key.clone(), &[KeyUsage::Witness]).unwrap()).unwrap();
+        }
+        store.save(&trust_path).unwrap();
+    };
+    let error = LocalWitness::trusted_key().unwrap_err();
+    assert!(error.to_string().contains("--usage witness"), "{}", error);
+    trust(&[("witness", key(9).public_key().unwrap())]);
+    assert_eq!(LocalWitness::trusted_key().unwrap(), fingerprint(9));
+    assert!(LocalWitness.verify_witness_commitment(policy, EntryType::PolicyCreate).is_err());
+
+    let mut witness = FileWitness::open(LocalWitness::path().unwrap(), &fingerprint(9)).unwrap().with_signing_key(key(9)).unwrap();
+    let proof = witness.commit(policy, EntryType::PolicyCreate).unwrap();
+    assert_eq!(LocalWitness.verify_witness_commitment(policy, EntryType::PolicyCreate).unwrap(), proof);
+    assert!(LocalWitness.current_timestamp() >= proof.time);
+
+    let (_, public_key, binding) = generate_bound_key_with_rng(&policy, &LocalWitness, &mut DeterministicRng::from_seed([3; 32])).unwrap();
+    binding.verify(&public_key, &LocalWitness).unwrap();
+
+    // The log is only read against the single key trusted for witness
+    trust(&[("witness", key(8).public_key().unwrap())]);
+    assert!(LocalWitness.verify_witness_commitment(policy, EntryType::PolicyCreate).is_err());
+    trust(&[("witness", key(9).public_key().unwrap()), ("witness-2", key(8).public_key().unwrap())]);
+    let error = LocalWitness::trusted_key().unwrap_err();
+    assert!(error.to_string().contains("both trusted for witness"), "{}", error);
+}
@@ -0,0 +1,115 @@
+//! Secret key material never reaches freed heap memory
+//!
+//! A scanning allocator scans every block handed back to it for known secret
+//! bytes. Only heap copies are covered; copies left in stack frames by the
+//! underlying Dilithium and Ed25519 implementations are not.
+
+use af_pqc::clock::FixedClock;
+use af_pqc::keystore::{KeyPolicy, Keystore};
+use af_pqc::rng::{DeterministicRng, STARTUP_SAMPLES};
+use af_pqc::secret::SecretBytes;
+use af_pqc::PrivateKey;
+use crystals_dilithium::dilithium3::Keypair as DilithiumKeypair;
+use rand_core::RngCore;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+const SEED: [u8; 32] = [0x5a; 32];
+const MESSAGE: &[u8] = b"zeroize test message";
+
+/// System allocator that reports freed blocks containing any needle
+struct ScanningAllocator;
+
+static ARMED: AtomicBool = AtomicBool::new(false);
+static LEAKED: AtomicBool = AtomicBool::new(false);
+static NEEDLES: Mutex<Vec<[u8; 32]>> = Mutex::new(Vec::new());
+/// Tests share the allocator state, so they run one at a time
+static SERIAL: Mutex<()> = Mutex::new(());
+
+unsafe impl GlobalAlloc for ScanningAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        if ARMED.load(Ordering::SeqCst) {
+            let block = std::slice::from_raw_parts(ptr, layout.size());
+            // `try_lock` so a block freed while the needles are being set up is skipped
+            if let Ok(needles) = NEEDLES.try_lock() {
+                if needles.iter().any(|needle| block.windows(needle.len()).any(|window| window == needle)) {
+                    LEAKED.store(true, Ordering::SeqCst);
+                }
+            }
+        }
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: ScanningAllocator = ScanningAllocator;
+
+/// Secret bytes that [`PrivateKey::generate_with_rng`] derives from [`SEED`]
+fn needles() -> Vec<[u8; 32]> {
+    let mut rng = DeterministicRng::from_seed(SEED);
+    let mut startup = [0u8; STARTUP_SAMPLES];
+    let mut dilithium_seed = [0u8; 32];
+    let mut ed25519_secret = [0u8; 32];
+    rng.fill_bytes(&mut startup);
+    rng.fill_bytes(&mut dilithium_seed);
+    rng.fill_bytes(&mut ed25519_secret);
+
+    let dilithium = DilithiumKeypair::generate(Some(&dilithium_seed));
+    let mut dilithium_key = [0u8; 32];
+    dilithium_key.copy_from_slice(&dilithium.secret.bytes[32..64]);
+    vec![ed25519_secret, dilithium_key]
+}
+
+/// Run `f` with the allocator armed and report whether any secret leaked
+fn leaks(f: impl FnOnce()) -> bool {
+    *NEEDLES.lock().unwrap() = needles();
+    LEAKED.store(false, Ordering::SeqCst);
+    ARMED.store(true, Ordering::SeqCst);
+    f();
+    ARMED.store(false, Ordering::SeqCst);
+    NEEDLES.lock().unwrap().clear();
+    LEAKED.load(Ordering::SeqCst)
+}
+
+#[test]
+fn generate_and_sign_leave_no_secrets_in_freed_memory() {
+    let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    let leaked = leaks(|| {
+        let clock = FixedClock::new(1_700_000_000);
+        let (private_key, public_key) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed(SEED), &clock).unwrap();
+        let signature = private_key.sign(MESSAGE).unwrap();
+        public_key.verify(MESSAGE, &signature).unwrap();
+        assert_eq!(private_key.public_key().unwrap().bytes, public_key.bytes);
+    });
+    assert!(!leaked, "secret key bytes found in a freed heap block");
+}
+
+#[test]
+fn keystore_round_trip_leaves_no_secrets_in_freed_memory() {
+    let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = tempfile::tempdir().unwrap();
+    let keystore = Keystore::open(dir.path().join("keystore")).unwrap();
+    let leaked = leaks(|| {
+        let clock = FixedClock::new(1_700_000_000);
+        let (private_key, _) = PrivateKey::generate_with_rng(&mut DeterministicRng::from_seed(SEED), &clock).unwrap();
+        keystore.insert("zeroize", &private_key, KeyPolicy::default(), b"passphrase").unwrap();
+        drop(private_key);
+        let loaded = keystore.load_private_key("zeroize", b"passphrase").unwrap();
+        loaded.sign(MESSAGE).unwrap();
+    });
+    assert!(!leaked, "secret key bytes found in a freed heap block");
+}
+
+#[test]
+fn secret_bytes_are_locked_and_redacted() {
+    assert!(SecretBytes::<32>::is_locked());
+    let secret = SecretBytes::<32>::from_bytes(&[0xab; 32]);
+    assert_eq!(secret.expose(|bytes| *bytes), [0xab; 32]);
+    let debug = format!("{:?}", secret);
+    assert!(debug.contains("REDACTED") && !debug.contains("ab"), "{}", debug);
+}
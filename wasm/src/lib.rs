@@ -0,0 +1,174 @@
+//! # AF-PQC WebAssembly Bindings
+//!
+//! wasm-bindgen wrapper for verifying hybrid signatures in the browser or
+//! Node.js. Keys and signatures use the same JSON format as `af-cli`.
+//! Key generation and signing are available with the `signing` feature.
+//!
+//! ```js
+//! import { PublicKey, Signature } from "af-pqc-wasm";
+//!
+//! const key = PublicKey.fromJson(publicKeyJson);
+//! const valid = key.verify(documentBytes, Signature.fromJson(signatureJson));
+//! ```
+
+use af_pqc::CryptoError;
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "signing")]
+use zeroize::Zeroizing;
+
+fn to_js_error(error: CryptoError) -> JsError {
+    JsError::new(&error.to_string())
+}
+
+fn json_error(error: serde_json::Error) -> JsError {
+    JsError::new(&format!("Invalid JSON: {}", error))
+}
+
+/// Map a verification result to `true`/`false`, rethrowing malformed input
+fn verification_result(result: Result<(), CryptoError>) -> Result<bool, JsError> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(CryptoError::SignatureVerification { .. }) => Ok(false),
+        Err(e) => Err(to_js_error(e)),
+    }
+}
+
+/// Hybrid public key for signature verification
+#[wasm_bindgen]
+pub struct PublicKey {
+    inner: af_pqc::PublicKey,
+}
+
+#[wasm_bindgen]
+impl PublicKey {
+    /// Parse a public key from its JSON encoding
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<PublicKey, JsError> {
+        serde_json::from_str(json).map(|inner| PublicKey { inner }).map_err(json_error)
+    }
+
+    /// Serialize to JSON
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        serde_json::to_string(&self.inner).map_err(json_error)
+    }
+
+    /// Check `signature` over `message`; throws only on malformed keys
+    pub fn verify(&self, message: &[u8], signature: &Signature) -> Result<bool, JsError> {
+        verification_result(self.inner.verify(message, &signature.inner))
+    }
+
+    /// Raw hybrid key bytes (Dilithium3 public key || Ed25519 public key)
+    #[wasm_bindgen(getter)]
+    pub fn bytes(&self) -> Vec<u8> {
+        self.inner.bytes.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn algorithm(&self) -> String {
+        format!("{:?}", self.inner.algorithm)
+    }
+
+    /// Creation time in seconds since the Unix epoch
+    #[wasm_bindgen(getter, js_name = createdAt)]
+    pub fn created_at(&self) -> f64 {
+        self.inner.created_at as f64
+    }
+}
+
+/// Hybrid signature with provenance metadata
+#[wasm_bindgen]
+pub struct Signature {
+    inner: af_pqc::Signature,
+}
+
+#[wasm_bindgen]
+impl Signature {
+    /// Parse a signature from its JSON encoding
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<Signature, JsError> {
+        serde_json::from_str(json).map(|inner| Signature { inner }).map_err(json_error)
+    }
+
+    /// Serialize to JSON
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        serde_json::to_string(&self.inner).map_err(json_error)
+    }
+
+    /// Raw hybrid signature bytes (Dilithium3 signature || Ed25519 signature)
+    #[wasm_bindgen(getter)]
+    pub fn bytes(&self) -> Vec<u8> {
+        self.inner.bytes.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn algorithm(&self) -> String {
+        format!("{:?}", self.inner.algorithm)
+    }
+
+    #[wasm_bindgen(getter, js_name = signerKeyId)]
+    pub fn signer_key_id(&self) -> String {
+        self.inner.signer_key_id.clone()
+    }
+
+    /// Signing time in seconds since the Unix epoch
+    #[wasm_bindgen(getter, js_name = createdAt)]
+    pub fn created_at(&self) -> f64 {
+        self.inner.created_at as f64
+    }
+}
+
+/// Verify a JSON-encoded signature over `message` with a JSON-encoded public key
+#[wasm_bindgen]
+pub fn verify(public_key_json: &str, message: &[u8], signature_json: &str) -> Result<bool, JsError> {
+    let public_key = PublicKey::from_json(public_key_json)?;
+    let signature = Signature::from_json(signature_json)?;
+    public_key.verify(message, &signature)
+}
+
+/// Hybrid private key
+#[cfg(feature = "signing")]
+#[wasm_bindgen]
+pub struct PrivateKey {
+    inner: af_pqc::PrivateKey,
+}
+
+#[cfg(feature = "signing")]
+#[wasm_bindgen]
+impl PrivateKey {
+    /// Generate a random key from the platform's crypto API
+    pub fn generate() -> Result<PrivateKey, JsError> {
+        af_pqc::PrivateKey::generate()
+            .map(|(inner, _)| PrivateKey { inner })
+            .map_err(to_js_error)
+    }
+
+    /// Derive a key from a 32-byte seed
+    #[wasm_bindgen(js_name = fromSeed)]
+    pub fn from_seed(seed: Vec<u8>) -> Result<PrivateKey, JsError> {
+        // Owned so the copy in wasm memory is cleared after use
+        let seed = Zeroizing::new(seed);
+        let seed: &[u8; 32] = seed.as_slice().try_into()
+            .map_err(|_| JsError::new(&format!("Seed must be exactly 32 bytes, got {}", seed.len())))?;
+        af_pqc::generate_key_from_seed(seed)
+            .map(|(inner, _)| PrivateKey { inner })
+            .map_err(to_js_error)
+    }
+
+    /// Public half of this key
+    #[wasm_bindgen(js_name = publicKey)]
+    pub fn public_key(&self) -> Result<PublicKey, JsError> {
+        self.inner.public_key().map(|inner| PublicKey { inner }).map_err(to_js_error)
+    }
+
+    /// Sign `message` with both Dilithium3 and Ed25519
+    pub fn sign(&self, message: &[u8]) -> Result<Signature, JsError> {
+        self.inner.sign(message).map(|inner| Signature { inner }).map_err(to_js_error)
+    }
+
+    #[wasm_bindgen(getter, js_name = keyId)]
+    pub fn key_id(&self) -> String {
+        self.inner.key_id.clone()
+    }
+}
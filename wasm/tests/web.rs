@@ -0,0 +1,61 @@
+//! Headless wasm tests, run under Node.js with:
+//!
+//! ```sh
+//! cargo install wasm-bindgen-cli --version <wasm-bindgen version>
+//! cargo test -p af-pqc-wasm --features signing --target wasm32-unknown-unknown
+//! ```
+//!
+//! `.cargo/config.toml` sets `wasm-bindgen-test-runner` as the wasm32 runner.
+
+#![cfg(target_arch = "wasm32")]
+
+use af_pqc_wasm::{verify, PublicKey, Signature};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const PUBLIC_KEY: &str = include_str!("fixtures/public_key.json");
+const SIGNATURE: &str = include_str!("fixtures/signature.json");
+const MESSAGE: &[u8] = b"AF-PQC wasm verification fixture";
+
+#[wasm_bindgen_test]
+fn verifies_fixture_signature() {
+    let public_key = PublicKey::from_json(PUBLIC_KEY).unwrap();
+    let signature = Signature::from_json(SIGNATURE).unwrap();
+
+    assert!(public_key.verify(MESSAGE, &signature).unwrap());
+    assert!(verify(PUBLIC_KEY, MESSAGE, SIGNATURE).unwrap());
+    assert_eq!(public_key.algorithm(), "MandatoryHybrid");
+}
+
+#[wasm_bindgen_test]
+fn rejects_tampered_message() {
+    let public_key = PublicKey::from_json(PUBLIC_KEY).unwrap();
+    let signature = Signature::from_json(SIGNATURE).unwrap();
+
+    assert!(!public_key.verify(b"tampered", &signature).unwrap());
+}
+
+#[wasm_bindgen_test]
+fn parses_round_trip_and_rejects_malformed_keys() {
+    let public_key = PublicKey::from_json(PUBLIC_KEY).unwrap();
+    let reparsed = PublicKey::from_json(&public_key.to_json().unwrap()).unwrap();
+    assert_eq!(reparsed.bytes(), public_key.bytes());
+
+    assert!(PublicKey::from_json("{not json").is_err());
+    let truncated = r#"{"algorithm":"MandatoryHybrid","bytes":[1,2,3],"created_at":0,"operation_id":0}"#;
+    assert!(verify(truncated, MESSAGE, SIGNATURE).is_err());
+}
+
+#[cfg(feature = "signing")]
+#[wasm_bindgen_test]
+fn signs_and_verifies() {
+    use af_pqc_wasm::PrivateKey;
+
+    let private_key = PrivateKey::generate().unwrap();
+    let signature = private_key.sign(MESSAGE).unwrap();
+    assert!(private_key.public_key().unwrap().verify(MESSAGE, &signature).unwrap());
+
+    let seeded = PrivateKey::from_seed(vec![7u8; 32]).unwrap();
+    let again = PrivateKey::from_seed(vec![7u8; 32]).unwrap();
+    assert_eq!(seeded.public_key().unwrap().bytes(), again.public_key().unwrap().bytes());
+    assert!(PrivateKey::from_seed(vec![0u8; 16]).is_err());
+}